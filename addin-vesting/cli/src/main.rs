@@ -1,4 +1,3 @@
-// use std::str::FromStr;
 use chrono::{NaiveDateTime, DateTime, Duration};
 use clap::{
     crate_description, crate_name, crate_version, value_t, App, AppSettings, Arg, SubCommand,
@@ -17,32 +16,49 @@ use solana_client::{
 };
 use solana_program::{
     borsh::try_from_slice_unchecked,
-    msg, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+    clock::{Clock, DEFAULT_MS_PER_SLOT},
+    msg, program_pack::Pack, pubkey::Pubkey, rent::Rent, sysvar,
 };
 use solana_sdk::{
     self,
-    account::Account,
+    account::{from_account, Account},
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
     instruction::Instruction,
     message::Message,
+    packet::PACKET_DATA_SIZE,
     signature::{Keypair, Signer},
     signers::Signers,
     system_instruction,
     transaction::Transaction,
 };
-use spl_associated_token_account::get_associated_token_address;
-use std::{convert::TryInto, process::exit, sync::Arc};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::{collections::HashMap, convert::TryInto, fs, process::exit, str::FromStr, sync::Arc};
 use spl_governance_addin_vesting::{
-    state::{ VestingRecord, VestingSchedule },
+    state::{ LinearVestingSchedule, LockupConfig, VestingAccountType, VestingRecordV2, VestingSchedule, VestingSummary, VestingTimeBase },
     instruction::{
         deposit, deposit_with_realm, withdraw, withdraw_with_realm, change_owner, change_owner_with_realm,
         create_voter_weight_record, set_vote_percentage_with_realm, split, split_with_realm,
+        deposit_linear, deposit_linear_with_realm, split_linear, split_linear_with_realm,
+        deposit_revocable, deposit_revocable_with_realm, revoke, revoke_with_realm, set_revoke_authority,
+        add_schedules, add_schedules_with_realm, withdraw_amount, withdraw_amount_with_realm, merge,
+        propose_owner, accept_owner, accept_owner_with_realm, cancel_owner_proposal,
+        set_lockup_config, recalculate_voter_weight, migrate_vesting_record, attach_realm, detach_realm,
+        move_realm, compact, batch_deposit, batch_deposit_with_realm, reconcile_max_voter_weight,
+        deposit_with_time_base, deposit_with_time_base_with_realm, set_freeze_authority,
+        delegate, undelegate, get_withdrawable_amount, get_vesting_summary, get_voter_weight,
+        set_crank_withdraw, crank_withdraw, crank_withdraw_with_realm, set_manager, clear_manager,
+        set_vote_percentage_policy, with_delegate_voter_weight_record,
     },
-    voter_weight::get_voter_weight_record_address,
+    max_voter_weight::MaxVoterWeightReconciliation,
+    realm_config::VotePercentagePolicy,
+    voter_weight::{get_voter_weight_record_address, ExtendedVoterWeightRecord, VoterWeightInfo, VoterWeightRecord},
 };
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 
+/// Maximum number of compute units which can be requested by a transaction
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
 fn get_signer(
     matches: &ArgMatches<'_>,
     keypair_name: &str,
@@ -65,10 +81,13 @@ fn create_transaction<T: Signers>(
 ) -> Result<Transaction, Box<dyn std::error::Error>> {
     let blockhash = rpc_client.get_latest_blockhash().expect("Can't get recent blockhash");
     let mut instrs = if let Some(compute_unit_price) = compute_unit_price {
+        // Simulate with the maximum limit: instructions may consume more than the default limit
+        let mut simulated_instrs = vec![ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)];
+        simulated_instrs.extend_from_slice(instructions);
         let result = rpc_client.simulate_transaction(
             &Transaction::new_unsigned(
                 Message::new_with_blockhash(
-                    &instructions,
+                    &simulated_instrs,
                     Some(&payer.pubkey()),
                     &blockhash
                 )
@@ -93,6 +112,76 @@ fn create_transaction<T: Signers>(
     Ok(transaction)
 }
 
+/// Checks that the transaction with the instructions (and compute budget instructions) fits into the packet
+fn fits_into_transaction(instructions: &[Instruction], payer: &Pubkey) -> bool {
+    let mut instrs = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+    ];
+    instrs.extend_from_slice(instructions);
+    let message = Message::new(&instrs, Some(payer));
+    let signatures_len = message.header.num_required_signatures as usize;
+    // Serialized transaction: compact array of signatures followed by the message
+    1 + signatures_len * 64 + message.serialize().len() <= PACKET_DATA_SIZE
+}
+
+/// Splits items into groups for which the instructions fit into a single transaction
+fn split_into_transactions<'a, T, F>(items: &'a [T], payer: &Pubkey, instructions: F) -> Vec<&'a [T]>
+where F: Fn(&[T]) -> Vec<Instruction>
+{
+    let mut groups = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let mut end = start + 1;
+        while end < items.len() && fits_into_transaction(&instructions(&items[start..end + 1]), payer) {
+            end += 1;
+        }
+        groups.push(&items[start..end]);
+        start = end;
+    }
+    groups
+}
+
+/// Simulates the instruction and returns the data it passed to `set_return_data`.
+/// Exits with the program logs when the instruction fails
+fn simulate_return_data(
+    rpc_client: &RpcClient,
+    payer: &Pubkey,
+    instruction: Instruction,
+    instruction_name: &str,
+) -> Vec<u8> {
+    let blockhash = rpc_client.get_latest_blockhash().expect("Can't get recent blockhash");
+    let result = rpc_client.simulate_transaction(
+        &Transaction::new_unsigned(
+            Message::new_with_blockhash(
+                &[
+                    ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+                    instruction,
+                ],
+                Some(payer),
+                &blockhash
+            )
+        )
+    ).unwrap_or_else(|err| panic!("Can't simulate {} transaction: {}", instruction_name, err));
+    if let Some(err) = result.value.err {
+        msg!("{} failed: {:?}", instruction_name, err);
+        for log in result.value.logs.unwrap_or_default() {
+            msg!("    {}", log);
+        }
+        exit(1);
+    }
+
+    let return_data = result.value.return_data
+        .unwrap_or_else(|| panic!("{} doesn't return data", instruction_name));
+    base64::decode(&return_data.data.0)
+        .unwrap_or_else(|_| panic!("Can't decode {} return data", instruction_name))
+}
+
+/// Returns the token program (spl-token or spl-token-2022) which owns the mint
+fn get_token_program_id(rpc_client: &RpcClient, mint_pubkey: &Pubkey) -> Pubkey {
+    rpc_client.get_account(mint_pubkey).unwrap().owner
+}
+
 // Lock the vesting contract
 #[allow(clippy::too_many_arguments)]
 fn command_deposit_svc(
@@ -104,12 +193,17 @@ fn command_deposit_svc(
     vesting_owner_pubkey: Pubkey,
     mint_pubkey: Pubkey,
     schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    revoke_authority: Option<Pubkey>,
+    time_base: VestingTimeBase,
     compute_unit_price: Option<u64>,
     confirm: bool,
 ) {
+    let token_program_id = get_token_program_id(&rpc_client, &mint_pubkey);
+
     // If no source token account was given, use the associated source account
     let source_token_pubkey = match possible_source_token_pubkey {
-        None => get_associated_token_address(&source_token_owner.pubkey(), &mint_pubkey),
+        None => get_associated_token_address_with_program_id(&source_token_owner.pubkey(), &mint_pubkey, &token_program_id),
         _ => possible_source_token_pubkey.unwrap(),
     };
 
@@ -122,27 +216,70 @@ fn command_deposit_svc(
         system_instruction::create_account(
             &source_token_owner.pubkey(),
             &vesting_token_pubkey,
-            Rent::default().minimum_balance(spl_token::state::Account::LEN),
-            spl_token::state::Account::LEN as u64,
-            &spl_token::id()
+            Rent::default().minimum_balance(spl_token_2022::state::Account::LEN),
+            spl_token_2022::state::Account::LEN as u64,
+            &token_program_id
         ),
-        spl_token::instruction::initialize_account(
-            &spl_token::id(), 
+        spl_token_2022::instruction::initialize_account(
+            &token_program_id,
             &vesting_token_pubkey,
             &mint_pubkey, 
             &vesting_pubkey
         ).unwrap(),
-        deposit(
-            &vesting_addin_program_id,
-            &spl_token::id(),
-            &vesting_token_pubkey,
-            &source_token_owner.pubkey(),
-            &source_token_pubkey,
-            &vesting_owner_pubkey,
-            &payer.pubkey(),
-            schedules,
-        )
-        .unwrap(),
+        if time_base != VestingTimeBase::UnixTimestamp {
+            deposit_with_time_base(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &mint_pubkey,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+                linear_schedule,
+                revoke_authority,
+                time_base,
+            )
+        } else if let Some(revoke_authority) = revoke_authority {
+            deposit_revocable(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &mint_pubkey,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+                linear_schedule,
+                &revoke_authority,
+            )
+        } else if let Some(linear_schedule) = linear_schedule {
+            deposit_linear(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &mint_pubkey,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                linear_schedule,
+            )
+        } else {
+            deposit(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &mint_pubkey,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+            )
+        }.unwrap(),
     ];
 
     let transaction = create_transaction(
@@ -154,10 +291,12 @@ fn command_deposit_svc(
     ).unwrap();
 
     msg!("Vesting addin program id: {:?}", vesting_addin_program_id,);
-    msg!("SPL Token program id: {:?}", spl_token::id(),);
+    msg!("SPL Token program id: {:?}", token_program_id,);
     msg!("Source token owner pubkey: {:?}", source_token_owner.pubkey(),);
     msg!("Source token pubkey: {:?}", source_token_pubkey,);
     msg!("Vesting owner pubkey: {:?}", vesting_owner_pubkey,);
+    msg!("Revoke authority: {:?}", revoke_authority,);
+    msg!("Time base: {:?}", time_base,);
     msg!("Payer: {:?}", payer.pubkey(),);
     msg!("The vesting account pubkey: {:?}", vesting_pubkey,);
     msg!("The vesting token pubkey: {:?}", vesting_token_pubkey,);
@@ -175,6 +314,114 @@ fn command_deposit_svc(
     }
 }
 
+/// Creates vesting contracts for all deposits: vesting spl-token accounts are created first,
+/// then vestings are created by `BatchDeposit` instructions. Every transaction is filled up to the size limit
+#[allow(clippy::too_many_arguments)]
+fn command_deposit_batch(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    source_token_owner: &dyn Signer,
+    possible_source_token_pubkey: Option<Pubkey>,
+    mint_pubkey: Pubkey,
+    realm: Option<Pubkey>,
+    deposits: Vec<(Pubkey, Vec<VestingSchedule>)>,
+    compute_unit_price: Option<u64>,
+) {
+    let token_program_id = get_token_program_id(&rpc_client, &mint_pubkey);
+
+    // If no source token account was given, use the associated source account
+    let source_token_pubkey = possible_source_token_pubkey.unwrap_or_else(||
+        get_associated_token_address_with_program_id(&source_token_owner.pubkey(), &mint_pubkey, &token_program_id)
+    );
+
+    let send_transaction = |instructions: &[Instruction], signers: &[&dyn Signer]| {
+        let mut instructions = instructions.to_vec();
+        if compute_unit_price.is_none() {
+            instructions.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT));
+        }
+        let transaction = create_transaction(
+            &rpc_client,
+            &instructions,
+            payer,
+            &signers.to_vec(),
+            compute_unit_price,
+        ).unwrap();
+        rpc_client
+            .send_and_confirm_transaction_with_spinner_and_commitment(
+                &transaction,
+                CommitmentConfig::confirmed(),
+            )
+            .unwrap();
+    };
+
+    // Create vesting spl-token accounts
+    let vesting_token_keypairs = deposits.iter().map(|_| Keypair::new()).collect::<Vec<_>>();
+    let create_token_account_instructions = |vesting_token_keypair: &Keypair| {
+        let vesting_token_pubkey = vesting_token_keypair.pubkey();
+        let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+        vec![
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &vesting_token_pubkey,
+                Rent::default().minimum_balance(spl_token_2022::state::Account::LEN),
+                spl_token_2022::state::Account::LEN as u64,
+                &token_program_id
+            ),
+            spl_token_2022::instruction::initialize_account(
+                &token_program_id,
+                &vesting_token_pubkey,
+                &mint_pubkey,
+                &vesting_pubkey
+            ).unwrap(),
+        ]
+    };
+
+    for group in split_into_transactions(&vesting_token_keypairs, &payer.pubkey(), |keypairs| {
+        keypairs.iter().flat_map(create_token_account_instructions).collect()
+    }) {
+        let signers = group.iter().map(|keypair| keypair as &dyn Signer).collect::<Vec<_>>();
+        send_transaction(&group.iter().flat_map(create_token_account_instructions).collect::<Vec<_>>(), &signers);
+    }
+
+    // Create vestings
+    let deposits = vesting_token_keypairs.iter()
+        .zip(deposits)
+        .map(|(vesting_token_keypair, (vesting_owner_pubkey, schedules))| (vesting_token_keypair.pubkey(), vesting_owner_pubkey, schedules))
+        .collect::<Vec<_>>();
+    let batch_deposit_instruction = |deposits: &[(Pubkey, Pubkey, Vec<VestingSchedule>)]| {
+        if let Some(realm_pubkey) = &realm {
+            batch_deposit_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &mint_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &payer.pubkey(),
+                deposits,
+                realm_pubkey,
+            )
+        } else {
+            batch_deposit(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &mint_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &payer.pubkey(),
+                deposits,
+            )
+        }.unwrap()
+    };
+
+    for group in split_into_transactions(&deposits, &payer.pubkey(), |deposits| vec![batch_deposit_instruction(deposits)]) {
+        send_transaction(&[batch_deposit_instruction(group)], &[source_token_owner]);
+        for (vesting_token_pubkey, vesting_owner_pubkey, _) in group {
+            msg!("Vesting token pubkey: {:?}, owner: {:?}", vesting_token_pubkey, vesting_owner_pubkey);
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn command_deposit_with_realm_svc(
     rpc_client: RpcClient,
@@ -187,12 +434,17 @@ fn command_deposit_with_realm_svc(
     mint_pubkey: Pubkey,
     realm_pubkey: Pubkey,
     schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    revoke_authority: Option<Pubkey>,
+    time_base: VestingTimeBase,
     compute_unit_price: Option<u64>,
     confirm: bool,
 ) {
+    let token_program_id = get_token_program_id(&rpc_client, &mint_pubkey);
+
     // If no source token account was given, use the associated source account
     let source_token_pubkey = match possible_source_token_pubkey {
-        None => get_associated_token_address(&source_token_owner.pubkey(), &mint_pubkey),
+        None => get_associated_token_address_with_program_id(&source_token_owner.pubkey(), &mint_pubkey, &token_program_id),
         _ => possible_source_token_pubkey.unwrap(),
     };
 
@@ -205,29 +457,74 @@ fn command_deposit_with_realm_svc(
         system_instruction::create_account(
             &source_token_owner.pubkey(),
             &vesting_token_pubkey,
-            Rent::default().minimum_balance(spl_token::state::Account::LEN),
-            spl_token::state::Account::LEN as u64,
-            &spl_token::id()
+            Rent::default().minimum_balance(spl_token_2022::state::Account::LEN),
+            spl_token_2022::state::Account::LEN as u64,
+            &token_program_id
         ),
-        spl_token::instruction::initialize_account(
-            &spl_token::id(), 
+        spl_token_2022::instruction::initialize_account(
+            &token_program_id,
             &vesting_token_pubkey,
             &mint_pubkey, 
             &vesting_pubkey
         ).unwrap(),
-        deposit_with_realm(
-            &vesting_addin_program_id,
-            &spl_token::id(),
-            &vesting_token_pubkey,
-            &source_token_owner.pubkey(),
-            &source_token_pubkey,
-            &vesting_owner_pubkey,
-            &payer.pubkey(),
-            schedules,
-            &realm_pubkey,
-            &mint_pubkey,
-        )
-        .unwrap(),
+        if time_base != VestingTimeBase::UnixTimestamp {
+            deposit_with_time_base_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+                linear_schedule,
+                revoke_authority,
+                time_base,
+                &realm_pubkey,
+                &mint_pubkey,
+            )
+        } else if let Some(revoke_authority) = revoke_authority {
+            deposit_revocable_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+                linear_schedule,
+                &revoke_authority,
+                &realm_pubkey,
+                &mint_pubkey,
+            )
+        } else if let Some(linear_schedule) = linear_schedule {
+            deposit_linear_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                linear_schedule,
+                &realm_pubkey,
+                &mint_pubkey,
+            )
+        } else {
+            deposit_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_token_pubkey,
+                &source_token_owner.pubkey(),
+                &source_token_pubkey,
+                &vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+                &realm_pubkey,
+                &mint_pubkey,
+            )
+        }.unwrap(),
     ];
 
     let transaction = create_transaction(
@@ -239,10 +536,12 @@ fn command_deposit_with_realm_svc(
     ).unwrap();
 
     msg!("Vesting addin program id: {:?}", vesting_addin_program_id,);
-    msg!("SPL Token program id: {:?}", spl_token::id(),);
+    msg!("SPL Token program id: {:?}", token_program_id,);
     msg!("Source token owner pubkey: {:?}", source_token_owner.pubkey(),);
     msg!("Source token pubkey: {:?}", source_token_pubkey,);
     msg!("Vesting owner pubkey: {:?}", vesting_owner_pubkey,);
+    msg!("Revoke authority: {:?}", revoke_authority,);
+    msg!("Time base: {:?}", time_base,);
     msg!("Payer: {:?}", payer.pubkey(),);
     msg!("Governance program id: {:?}", governance_program_id,);
     msg!("The vesting account pubkey: {:?}", vesting_pubkey,);
@@ -261,60 +560,108 @@ fn command_deposit_with_realm_svc(
     }
 }
 
-fn command_withdraw_svc(
+#[allow(clippy::too_many_arguments)]
+fn command_add_schedules(
     rpc_client: RpcClient,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
-    vesting_owner: &dyn Signer,
+    source_token_owner: &dyn Signer,
+    possible_source_token_pubkey: Option<Pubkey>,
     vesting_token_pubkey: Pubkey,
-    destination_token_pubkey: Pubkey,
+    schedules: Vec<VestingSchedule>,
     compute_unit_price: Option<u64>,
 ) {
+    let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
 
-    let withdraw_instruction = withdraw(
-        &vesting_addin_program_id,
-        &spl_token::id(),
-        &vesting_token_pubkey,
-        &destination_token_pubkey,
-        &vesting_owner.pubkey(),
-    )
+    let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+    let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+
+    let token_program_id = get_token_program_id(&rpc_client, &vesting_record.mint);
+
+    // If no source token account was given, use the associated source account
+    let source_token_pubkey = match possible_source_token_pubkey {
+        None => get_associated_token_address_with_program_id(&source_token_owner.pubkey(), &vesting_record.mint, &token_program_id),
+        _ => possible_source_token_pubkey.unwrap(),
+    };
+
+    msg!("Vesting addin program id: {:?}", vesting_addin_program_id,);
+    msg!("Source token owner pubkey: {:?}", source_token_owner.pubkey(),);
+    msg!("Source token pubkey: {:?}", source_token_pubkey,);
+    msg!("Payer: {:?}", payer.pubkey(),);
+    msg!("The vesting account pubkey: {:?}", vesting_pubkey,);
+    report_schedules(&schedules, &*release_time_formatter(&rpc_client, vesting_record.time_base));
+
+    let add_schedules_instruction = if let Some(realm_pubkey) = vesting_record.realm {
+        add_schedules_with_realm(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &vesting_token_pubkey,
+            &source_token_owner.pubkey(),
+            &source_token_pubkey,
+            &vesting_record.owner,
+            &payer.pubkey(),
+            schedules,
+            &realm_pubkey,
+            &vesting_record.mint,
+        )
+    } else {
+        add_schedules(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &vesting_record.mint,
+            &vesting_token_pubkey,
+            &source_token_owner.pubkey(),
+            &source_token_pubkey,
+            &payer.pubkey(),
+            schedules,
+        )
+    }
     .unwrap();
 
     let transaction = create_transaction(
         &rpc_client,
-        &[withdraw_instruction],
+        &[add_schedules_instruction],
         payer,
-        &[vesting_owner],
+        &[source_token_owner],
         compute_unit_price,
     ).unwrap();
-
     rpc_client.send_transaction(&transaction).unwrap();
 }
 
 #[allow(clippy::too_many_arguments)]
-fn command_withdraw_with_realm_svc(
+fn command_withdraw_svc(
     rpc_client: RpcClient,
-    governance_program_id: Pubkey,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
     vesting_owner: &dyn Signer,
     vesting_token_pubkey: Pubkey,
     mint_pubkey: Pubkey,
-    realm_pubkey: Pubkey,
     destination_token_pubkey: Pubkey,
+    amount: Option<u64>,
     compute_unit_price: Option<u64>,
 ) {
+    let token_program_id = get_token_program_id(&rpc_client, &mint_pubkey);
 
-    let withdraw_instruction = withdraw_with_realm(
-        &vesting_addin_program_id,
-        &spl_token::id(),
-        &vesting_token_pubkey,
-        &destination_token_pubkey,
-        &vesting_owner.pubkey(),
-        &governance_program_id,
-        &realm_pubkey,
-        &mint_pubkey,
-    )
+    let withdraw_instruction = if let Some(amount) = amount {
+        withdraw_amount(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &mint_pubkey,
+            &vesting_token_pubkey,
+            &destination_token_pubkey,
+            &vesting_owner.pubkey(),
+            amount,
+        )
+    } else {
+        withdraw(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &mint_pubkey,
+            &vesting_token_pubkey,
+            &destination_token_pubkey,
+            &vesting_owner.pubkey(),
+        )
+    }
     .unwrap();
 
     let transaction = create_transaction(
@@ -324,30 +671,55 @@ fn command_withdraw_with_realm_svc(
         &[vesting_owner],
         compute_unit_price,
     ).unwrap();
+
     rpc_client.send_transaction(&transaction).unwrap();
 }
 
-fn command_change_owner(
+#[allow(clippy::too_many_arguments)]
+fn command_withdraw_with_realm_svc(
     rpc_client: RpcClient,
+    governance_program_id: Pubkey,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
     vesting_owner: &dyn Signer,
     vesting_token_pubkey: Pubkey,
-    new_vesting_owner_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    destination_token_pubkey: Pubkey,
+    amount: Option<u64>,
     compute_unit_price: Option<u64>,
 ) {
+    let token_program_id = get_token_program_id(&rpc_client, &mint_pubkey);
 
-    let change_owner_instruction = change_owner(
-        &vesting_addin_program_id,
-        &vesting_token_pubkey,
-        &vesting_owner.pubkey(),
-        &new_vesting_owner_pubkey,
-    )
+    let withdraw_instruction = if let Some(amount) = amount {
+        withdraw_amount_with_realm(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &vesting_token_pubkey,
+            &destination_token_pubkey,
+            &vesting_owner.pubkey(),
+            &governance_program_id,
+            &realm_pubkey,
+            &mint_pubkey,
+            amount,
+        )
+    } else {
+        withdraw_with_realm(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &vesting_token_pubkey,
+            &destination_token_pubkey,
+            &vesting_owner.pubkey(),
+            &governance_program_id,
+            &realm_pubkey,
+            &mint_pubkey,
+        )
+    }
     .unwrap();
 
     let transaction = create_transaction(
         &rpc_client,
-        &[change_owner_instruction],
+        &[withdraw_instruction],
         payer,
         &[vesting_owner],
         compute_unit_price,
@@ -356,52 +728,890 @@ fn command_change_owner(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn command_change_owner_with_realm(
+fn command_revoke(
     rpc_client: RpcClient,
     governance_program_id: Pubkey,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
-    vesting_owner: &dyn Signer,
+    revoke_authority: &dyn Signer,
     vesting_token_pubkey: Pubkey,
-    mint_pubkey: Pubkey,
-    realm_pubkey: Pubkey,
-    new_vesting_owner_pubkey: Pubkey,
+    destination_token_pubkey: Pubkey,
     compute_unit_price: Option<u64>,
 ) {
+    let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
 
-    let mut instructions: Vec<Instruction> = Vec::new();
-
-    let new_voter_weight_record_pubkey = get_voter_weight_record_address(&vesting_addin_program_id, &realm_pubkey, &mint_pubkey, &new_vesting_owner_pubkey);
+    let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+    let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+    let token_program_id = get_token_program_id(&rpc_client, &vesting_record.mint);
 
-    let new_voter_weight_record_data_result = rpc_client.get_account_data(&new_voter_weight_record_pubkey);
-    if new_voter_weight_record_data_result.is_err() || new_voter_weight_record_data_result.unwrap().is_empty() {
+    let revoke_instruction = if let Some(realm_pubkey) = vesting_record.realm {
+        let voter_weight_record_pubkey = get_voter_weight_record_address(&vesting_addin_program_id, &realm_pubkey, &vesting_record.mint, &vesting_record.owner);
+        let voter_weight_record_data = rpc_client.get_account_data(&voter_weight_record_pubkey).unwrap();
+        let voter_weight_record: ExtendedVoterWeightRecord = try_from_slice_unchecked(&voter_weight_record_data).unwrap();
 
-        let create_voter_weight_record_instruction = create_voter_weight_record(
+        revoke_with_realm(
             &vesting_addin_program_id,
-            &new_vesting_owner_pubkey,
-            &payer.pubkey(),
+            &token_program_id,
+            &vesting_token_pubkey,
+            &destination_token_pubkey,
+            &revoke_authority.pubkey(),
+            &vesting_record.owner,
+            &governance_program_id,
             &realm_pubkey,
-            &mint_pubkey,
+            &vesting_record.mint,
+        )
+        // Revoked tokens delegated by the owner are undelegated
+        .map(|instruction| match voter_weight_record.delegate {
+            Some(delegate_pubkey) => with_delegate_voter_weight_record(instruction, &realm_pubkey, &vesting_record.mint, &delegate_pubkey),
+            None => instruction,
+        })
+    } else {
+        revoke(
+            &vesting_addin_program_id,
+            &token_program_id,
+            &vesting_record.mint,
+            &vesting_token_pubkey,
+            &destination_token_pubkey,
+            &revoke_authority.pubkey(),
         )
-        .unwrap();
-        instructions.push(create_voter_weight_record_instruction);
+    }
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[revoke_instruction],
+        payer,
+        &[revoke_authority],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_set_revoke_authority(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    revoke_authority: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    new_revoke_authority_pubkey: Option<Pubkey>,
+    compute_unit_price: Option<u64>,
+) {
+
+    let instruction = set_revoke_authority(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &revoke_authority.pubkey(),
+        new_revoke_authority_pubkey,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[revoke_authority],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_set_crank_withdraw(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    allow: bool,
+    compute_unit_price: Option<u64>,
+) {
+    let instruction = set_crank_withdraw(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        allow,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_set_manager(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    manager_pubkey: Option<Pubkey>,
+    compute_unit_price: Option<u64>,
+) {
+    let instruction = if let Some(manager_pubkey) = manager_pubkey {
+        set_manager(
+            &vesting_addin_program_id,
+            &vesting_token_pubkey,
+            &authority.pubkey(),
+            &manager_pubkey,
+            &payer.pubkey(),
+        )
+    } else {
+        clear_manager(
+            &vesting_addin_program_id,
+            &vesting_token_pubkey,
+            &authority.pubkey(),
+        )
+    }
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[authority],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_crank(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    compute_unit_price: Option<u64>,
+) {
+    msg!("\n------------------- CRANK WITHDRAW --------------------\n");
+    let clock_account = rpc_client.get_account(&sysvar::clock::id()).unwrap();
+    let clock: Clock = from_account(&clock_account).expect("Can't decode Clock sysvar");
+
+    let mut token_program_ids: HashMap<Pubkey, Pubkey> = HashMap::new();
+    let mut instructions: Vec<Instruction> = Vec::new();
+    for (vesting_pubkey, vesting_record) in get_vesting_records(&rpc_client, &vesting_addin_program_id, None) {
+        if !vesting_record.allow_crank_withdraw || vesting_record.frozen {
+            continue;
+        }
+        let time = vesting_record.time_base.current_time(&clock);
+        let withdrawable_amount = vesting_record.withdrawable_amount(time).unwrap();
+        if withdrawable_amount == 0 {
+            continue;
+        }
+
+        let token_program_id = *token_program_ids.entry(vesting_record.mint)
+            .or_insert_with(|| get_token_program_id(&rpc_client, &vesting_record.mint));
+        let destination_pubkey = get_associated_token_address_with_program_id(&vesting_record.owner, &vesting_record.mint, &token_program_id);
+        let destination_data_result = rpc_client.get_account_data(&destination_pubkey);
+        if destination_data_result.is_err() || destination_data_result.unwrap().is_empty() {
+            msg!("Skip vesting {}: owner {} has no associated token account", vesting_pubkey, vesting_record.owner);
+            continue;
+        }
+
+        msg!("Withdraw {} from vesting {} to {}", withdrawable_amount, vesting_pubkey, destination_pubkey);
+        let instruction = if let Some(realm_pubkey) = vesting_record.realm {
+            crank_withdraw_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_record.token,
+                &vesting_record.owner,
+                &governance_program_id,
+                &realm_pubkey,
+                &vesting_record.mint,
+            )
+        } else {
+            crank_withdraw(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_record.mint,
+                &vesting_record.token,
+                &vesting_record.owner,
+            )
+        }
+        .unwrap();
+        instructions.push(instruction);
+    }
+
+    let send_instructions = |group: &[Instruction]| -> bool {
+        let transaction = create_transaction(
+            &rpc_client,
+            group,
+            payer,
+            &[payer],
+            compute_unit_price,
+        ).unwrap();
+        match rpc_client.send_and_confirm_transaction(&transaction) {
+            Ok(signature) => {
+                msg!("Transaction {} confirmed", signature);
+                true
+            },
+            Err(err) => {
+                msg!("Transaction failed: {}", err);
+                false
+            },
+        }
+    };
+
+    // A single failing vesting (e.g. frozen after it was read) fails the whole transaction,
+    // so the instructions of the failed transaction are retried one by one
+    let mut cranked_count = 0;
+    for group in split_into_transactions(&instructions, &payer.pubkey(), |group| group.to_vec()) {
+        if send_instructions(group) {
+            cranked_count += group.len();
+        } else if group.len() > 1 {
+            for instruction in group {
+                if send_instructions(std::slice::from_ref(instruction)) {
+                    cranked_count += 1;
+                }
+            }
+        }
+    }
+    msg!("Cranked vestings: {} of {}", cranked_count, instructions.len());
+}
+
+fn command_change_owner(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    new_vesting_owner_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let change_owner_instruction = change_owner(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &new_vesting_owner_pubkey,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[change_owner_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_change_owner_with_realm(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    new_vesting_owner_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let mut instructions: Vec<Instruction> = Vec::new();
+
+    let new_voter_weight_record_pubkey = get_voter_weight_record_address(&vesting_addin_program_id, &realm_pubkey, &mint_pubkey, &new_vesting_owner_pubkey);
+
+    let new_voter_weight_record_data_result = rpc_client.get_account_data(&new_voter_weight_record_pubkey);
+    if new_voter_weight_record_data_result.is_err() || new_voter_weight_record_data_result.unwrap().is_empty() {
+
+        let create_voter_weight_record_instruction = create_voter_weight_record(
+            &vesting_addin_program_id,
+            &new_vesting_owner_pubkey,
+            &payer.pubkey(),
+            &realm_pubkey,
+            &mint_pubkey,
+        )
+        .unwrap();
+        instructions.push(create_voter_weight_record_instruction);
     }
 
     let change_owner_instruction = change_owner_with_realm(
         &vesting_addin_program_id,
         &vesting_token_pubkey,
         &vesting_owner.pubkey(),
-        &new_vesting_owner_pubkey,
-        &governance_program_id,
-        &realm_pubkey,
-        &mint_pubkey,
+        &new_vesting_owner_pubkey,
+        &governance_program_id,
+        &realm_pubkey,
+        &mint_pubkey,
+    )
+    .unwrap();
+    instructions.push(change_owner_instruction);
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &instructions,
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_attach_realm(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let attach_realm_instruction = attach_realm(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &governance_program_id,
+        &realm_pubkey,
+        &mint_pubkey,
+        &payer.pubkey(),
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[attach_realm_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_detach_realm(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let detach_realm_instruction = detach_realm(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &governance_program_id,
+        &realm_pubkey,
+        &mint_pubkey,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[detach_realm_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_move_realm(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    new_governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    new_realm_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let move_realm_instruction = move_realm(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &governance_program_id,
+        &realm_pubkey,
+        &new_governance_program_id,
+        &new_realm_pubkey,
+        &mint_pubkey,
+        &payer.pubkey(),
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[move_realm_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_compact(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    rent_receiver_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let compact_instruction = compact(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &rent_receiver_pubkey,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[compact_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_propose_owner(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    proposed_vesting_owner_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let propose_owner_instruction = propose_owner(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &proposed_vesting_owner_pubkey,
+        &payer.pubkey(),
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[propose_owner_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_accept_owner(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    proposed_vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+    let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+
+    let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+    let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+
+    let mut instructions: Vec<Instruction> = Vec::new();
+
+    if let Some(realm_pubkey) = vesting_record.realm {
+        let new_voter_weight_record_pubkey = get_voter_weight_record_address(
+            &vesting_addin_program_id,
+            &realm_pubkey,
+            &vesting_record.mint,
+            &proposed_vesting_owner.pubkey(),
+        );
+
+        let new_voter_weight_record_data_result = rpc_client.get_account_data(&new_voter_weight_record_pubkey);
+        if new_voter_weight_record_data_result.is_err() || new_voter_weight_record_data_result.unwrap().is_empty() {
+            let create_voter_weight_record_instruction = create_voter_weight_record(
+                &vesting_addin_program_id,
+                &proposed_vesting_owner.pubkey(),
+                &payer.pubkey(),
+                &realm_pubkey,
+                &vesting_record.mint,
+            )
+            .unwrap();
+            instructions.push(create_voter_weight_record_instruction);
+        }
+
+        instructions.push(
+            accept_owner_with_realm(
+                &vesting_addin_program_id,
+                &vesting_token_pubkey,
+                &vesting_record.owner,
+                &proposed_vesting_owner.pubkey(),
+                &governance_program_id,
+                &realm_pubkey,
+                &vesting_record.mint,
+            )
+            .unwrap()
+        );
+    } else {
+        instructions.push(
+            accept_owner(
+                &vesting_addin_program_id,
+                &vesting_token_pubkey,
+                &vesting_record.owner,
+                &proposed_vesting_owner.pubkey(),
+            )
+            .unwrap()
+        );
+    }
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &instructions,
+        payer,
+        &[proposed_vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_cancel_owner_proposal(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let cancel_owner_proposal_instruction = cancel_owner_proposal(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[cancel_owner_proposal_instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_create_voter_weight_record(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    record_owner_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+
+    let instruction = create_voter_weight_record(
+        &vesting_addin_program_id,
+        &record_owner_pubkey,
+        &payer.pubkey(),
+        &realm_pubkey,
+        &mint_pubkey,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[payer],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_set_vote_percentage_with_realm(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_authority: &dyn Signer,
+    vesting_owner_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    percentage: u16,
+    compute_unit_price: Option<u64>,
+) {
+
+    let instruction = set_vote_percentage_with_realm(
+        &vesting_addin_program_id,
+        &vesting_owner_pubkey,
+        &vesting_authority.pubkey(),
+        &governance_program_id,
+        &realm_pubkey,
+        &mint_pubkey,
+        percentage,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[vesting_authority],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_delegate(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    delegate_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    amount: u64,
+    compute_unit_price: Option<u64>,
+) {
+    let mut instructions: Vec<Instruction> = Vec::new();
+
+    let delegate_record_pubkey = get_voter_weight_record_address(&vesting_addin_program_id, &realm_pubkey, &mint_pubkey, &delegate_pubkey);
+    let delegate_record_data_result = rpc_client.get_account_data(&delegate_record_pubkey);
+    if delegate_record_data_result.is_err() || delegate_record_data_result.unwrap().is_empty() {
+        instructions.push(
+            create_voter_weight_record(
+                &vesting_addin_program_id,
+                &delegate_pubkey,
+                &payer.pubkey(),
+                &realm_pubkey,
+                &mint_pubkey,
+            ).unwrap()
+        );
+    }
+
+    instructions.push(
+        delegate(
+            &vesting_addin_program_id,
+            &governance_program_id,
+            &realm_pubkey,
+            &mint_pubkey,
+            &vesting_owner.pubkey(),
+            &delegate_pubkey,
+            &payer.pubkey(),
+            amount,
+        ).unwrap()
+    );
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &instructions,
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_undelegate(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    delegate_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    amount: u64,
+    compute_unit_price: Option<u64>,
+) {
+    let instruction = undelegate(
+        &vesting_addin_program_id,
+        &governance_program_id,
+        &realm_pubkey,
+        &mint_pubkey,
+        &vesting_owner.pubkey(),
+        &delegate_pubkey,
+        amount,
+    )
+    .unwrap();
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[vesting_owner],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_split(
+    rpc_client: RpcClient,
+    governance_program_id: Pubkey,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    new_vesting_owner_pubkey: Pubkey,
+    schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    compute_unit_price: Option<u64>,
+) {
+    let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+
+    let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+    let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+    let token_program_id = get_token_program_id(&rpc_client, &vesting_record.mint);
+
+    let new_vesting_token_keypair = Keypair::new();
+    let new_vesting_token_pubkey = new_vesting_token_keypair.pubkey();
+
+    let (new_vesting_pubkey, _) = Pubkey::find_program_address(
+        &[new_vesting_token_pubkey.as_ref()],
+        &vesting_addin_program_id,
+    );
+
+    msg!("Vesting addin program id: {:?}", vesting_addin_program_id);
+    msg!("SPL Token program id: {:?}", token_program_id);
+    msg!("Payer: {:?}", payer.pubkey(),);
+    msg!("New vesting owner pubkey: {:?}", new_vesting_owner_pubkey);
+    msg!("New vesting account pubkey: {:?}", new_vesting_pubkey);
+    msg!("New vesting token pubkey: {:?}", new_vesting_token_pubkey);
+    let format_time = release_time_formatter(&rpc_client, vesting_record.time_base);
+    report_schedules(&schedules, &*format_time);
+    if let Some(linear_schedule) = &linear_schedule {
+        report_linear_schedule(linear_schedule, 0, &*format_time);
+    }
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &new_vesting_token_pubkey,
+            Rent::default().minimum_balance(spl_token_2022::state::Account::LEN),
+            spl_token_2022::state::Account::LEN as u64,
+            &token_program_id
+        ),
+
+        spl_token_2022::instruction::initialize_account(
+            &token_program_id,
+            &new_vesting_token_pubkey,
+            &vesting_record.mint,
+            &new_vesting_pubkey,
+        ).unwrap(),
+
+        if let (Some(realm_pubkey), Some(linear_schedule)) = (vesting_record.realm, linear_schedule.clone()) {
+            split_linear_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_token_pubkey,
+                &vesting_owner.pubkey(),
+                &new_vesting_token_pubkey,
+                &new_vesting_owner_pubkey,
+                &payer.pubkey(),
+                linear_schedule,
+                &governance_program_id,
+                &realm_pubkey,
+                &vesting_record.mint,
+            )
+        } else if let Some(realm_pubkey) = vesting_record.realm {
+            split_with_realm(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_token_pubkey,
+                &vesting_owner.pubkey(),
+                &new_vesting_token_pubkey,
+                &new_vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+                &governance_program_id,
+                &realm_pubkey,
+                &vesting_record.mint,
+            )
+        } else if let Some(linear_schedule) = linear_schedule {
+            split_linear(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_record.mint,
+                &vesting_token_pubkey,
+                &vesting_owner.pubkey(),
+                &new_vesting_token_pubkey,
+                &new_vesting_owner_pubkey,
+                &payer.pubkey(),
+                linear_schedule,
+            )
+        } else {
+            split(
+                &vesting_addin_program_id,
+                &token_program_id,
+                &vesting_record.mint,
+                &vesting_token_pubkey,
+                &vesting_owner.pubkey(),
+                &new_vesting_token_pubkey,
+                &new_vesting_owner_pubkey,
+                &payer.pubkey(),
+                schedules,
+            )
+        }.unwrap(),
+    ];
+
+    let transaction = create_transaction(
+        &rpc_client,
+        &instructions,
+        payer,
+        &[vesting_owner, &new_vesting_token_keypair],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
+
+fn command_merge(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    source_vesting_token_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+    let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+
+    let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+    let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+    let token_program_id = get_token_program_id(&rpc_client, &vesting_record.mint);
+
+    let merge_instruction = merge(
+        &vesting_addin_program_id,
+        &token_program_id,
+        &vesting_record.mint,
+        &vesting_token_pubkey,
+        &source_vesting_token_pubkey,
+        &vesting_owner.pubkey(),
+        &vesting_owner.pubkey(),
+        &payer.pubkey(),
     )
     .unwrap();
-    instructions.push(change_owner_instruction);
 
     let transaction = create_transaction(
         &rpc_client,
-        &instructions,
+        &[merge_instruction],
         payer,
         &[vesting_owner],
         compute_unit_price,
@@ -409,22 +1619,27 @@ fn command_change_owner_with_realm(
     rpc_client.send_transaction(&transaction).unwrap();
 }
 
-fn command_create_voter_weight_record(
+#[allow(clippy::too_many_arguments)]
+fn command_set_lockup_config(
     rpc_client: RpcClient,
+    governance_program_id: Pubkey,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
-    record_owner_pubkey: Pubkey,
+    realm_authority: &dyn Signer,
     mint_pubkey: Pubkey,
     realm_pubkey: Pubkey,
+    lockup: Option<LockupConfig>,
     compute_unit_price: Option<u64>,
 ) {
 
-    let instruction = create_voter_weight_record(
+    let instruction = set_lockup_config(
         &vesting_addin_program_id,
-        &record_owner_pubkey,
-        &payer.pubkey(),
+        &governance_program_id,
         &realm_pubkey,
         &mint_pubkey,
+        &realm_authority.pubkey(),
+        &payer.pubkey(),
+        lockup,
     )
     .unwrap();
 
@@ -432,34 +1647,33 @@ fn command_create_voter_weight_record(
         &rpc_client,
         &[instruction],
         payer,
-        &[payer],
+        &[payer, realm_authority],
         compute_unit_price,
     ).unwrap();
     rpc_client.send_transaction(&transaction).unwrap();
 }
 
 #[allow(clippy::too_many_arguments)]
-fn command_set_vote_percentage_with_realm(
+fn command_set_vote_percentage_policy(
     rpc_client: RpcClient,
     governance_program_id: Pubkey,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
-    vesting_authority: &dyn Signer,
-    vesting_owner_pubkey: Pubkey,
+    realm_authority: &dyn Signer,
     mint_pubkey: Pubkey,
     realm_pubkey: Pubkey,
-    percentage: u16,
+    policy: Option<VotePercentagePolicy>,
     compute_unit_price: Option<u64>,
 ) {
 
-    let instruction = set_vote_percentage_with_realm(
+    let instruction = set_vote_percentage_policy(
         &vesting_addin_program_id,
-        &vesting_owner_pubkey,
-        &vesting_authority.pubkey(),
         &governance_program_id,
         &realm_pubkey,
         &mint_pubkey,
-        percentage,
+        &realm_authority.pubkey(),
+        &payer.pubkey(),
+        policy,
     )
     .unwrap();
 
@@ -467,155 +1681,350 @@ fn command_set_vote_percentage_with_realm(
         &rpc_client,
         &[instruction],
         payer,
-        &[vesting_authority],
+        &[payer, realm_authority],
         compute_unit_price,
     ).unwrap();
     rpc_client.send_transaction(&transaction).unwrap();
 }
 
 #[allow(clippy::too_many_arguments)]
-fn command_split(
+fn command_set_freeze_authority(
     rpc_client: RpcClient,
     governance_program_id: Pubkey,
     vesting_addin_program_id: Pubkey,
     payer: &dyn Signer,
-    vesting_owner: &dyn Signer,
-    vesting_token_pubkey: Pubkey,
-    new_vesting_owner_pubkey: Pubkey,
-    schedules: Vec<VestingSchedule>,
+    realm_authority: &dyn Signer,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    freeze_authority: Option<Pubkey>,
     compute_unit_price: Option<u64>,
 ) {
-    let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+    let instruction = set_freeze_authority(
+        &vesting_addin_program_id,
+        &governance_program_id,
+        &realm_pubkey,
+        &mint_pubkey,
+        &realm_authority.pubkey(),
+        &payer.pubkey(),
+        freeze_authority,
+    )
+    .unwrap();
 
-    let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
-    let vesting_record: VestingRecord = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[payer, realm_authority],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
 
-    let new_vesting_token_keypair = Keypair::new();
-    let new_vesting_token_pubkey = new_vesting_token_keypair.pubkey();
+fn command_recalculate_voter_weight(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+    let vesting_token_pubkeys = get_vesting_records_by_owner(&rpc_client, &vesting_addin_program_id, &vesting_owner_pubkey)
+        .into_iter()
+        .filter(|(_, vesting_record)| vesting_record.mint == mint_pubkey && vesting_record.realm == Some(realm_pubkey))
+        .map(|(_, vesting_record)| vesting_record.token)
+        .collect::<Vec<_>>();
 
-    let (new_vesting_pubkey, _) = Pubkey::find_program_address(
-        &[new_vesting_token_pubkey.as_ref()],
+    let voter_weight_record_pubkey = get_voter_weight_record_address(&vesting_addin_program_id, &realm_pubkey, &mint_pubkey, &vesting_owner_pubkey);
+    let voter_weight_record_data = rpc_client.get_account_data(&voter_weight_record_pubkey).unwrap();
+    let voter_weight_record: ExtendedVoterWeightRecord = try_from_slice_unchecked(&voter_weight_record_data).unwrap();
+
+    let instruction = recalculate_voter_weight(
         &vesting_addin_program_id,
-    );
+        &realm_pubkey,
+        &mint_pubkey,
+        &vesting_owner_pubkey,
+        voter_weight_record.delegate.as_ref(),
+        &vesting_token_pubkeys,
+    )
+    .unwrap();
 
-    msg!("Vesting addin program id: {:?}", vesting_addin_program_id);
-    msg!("SPL Token program id: {:?}", spl_token::id());
-    msg!("Payer: {:?}", payer.pubkey(),);
-    msg!("New vesting owner pubkey: {:?}", new_vesting_owner_pubkey);
-    msg!("New vesting account pubkey: {:?}", new_vesting_pubkey);
-    msg!("New vesting token pubkey: {:?}", new_vesting_token_pubkey);
-    report_schedules(&schedules);
+    let transaction = create_transaction(
+        &rpc_client,
+        &[instruction],
+        payer,
+        &[payer],
+        compute_unit_price,
+    ).unwrap();
+    rpc_client.send_transaction(&transaction).unwrap();
+}
 
-    let instructions = [
-        system_instruction::create_account(
-            &payer.pubkey(),
-            &new_vesting_token_pubkey,
-            Rent::default().minimum_balance(spl_token::state::Account::LEN),
-            spl_token::state::Account::LEN as u64,
-            &spl_token::id()
-        ),
+fn get_vesting_records_by_owner(
+    rpc_client: &RpcClient,
+    vesting_addin_program_id: &Pubkey,
+    vesting_owner_pubkey: &Pubkey,
+) -> Vec<(Pubkey, VestingRecordV2)> {
+    get_vesting_records(rpc_client, vesting_addin_program_id, Some(vesting_owner_pubkey))
+}
 
-        spl_token::instruction::initialize_account(
-            &spl_token::id(),
-            &new_vesting_token_pubkey,
-            &vesting_record.mint,
-            &new_vesting_pubkey,
-        ).unwrap(),
+/// Returns vesting records of both VestingRecordV1 and VestingRecordV2 layouts (optionally filtered by owner)
+fn get_vesting_records(
+    rpc_client: &RpcClient,
+    vesting_addin_program_id: &Pubkey,
+    vesting_owner_pubkey: Option<&Pubkey>,
+) -> Vec<(Pubkey, VestingRecordV2)> {
+    vec![VestingAccountType::VestingRecordV1, VestingAccountType::VestingRecordV2]
+        .into_iter()
+        .flat_map(|account_type| {
+            let mut filter_bytes: Vec<u8> = vec![account_type as u8];
+            if let Some(vesting_owner_pubkey) = vesting_owner_pubkey {
+                filter_bytes.extend_from_slice(vesting_owner_pubkey.as_ref());
+            }
+            rpc_client.get_program_accounts_with_config(
+                vesting_addin_program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        rpc_filter::RpcFilterType::Memcmp(
+                            #[allow(deprecated)]
+                            rpc_filter::Memcmp {
+                                offset: 0,
+                                bytes: rpc_filter::MemcmpEncodedBytes::Bytes(filter_bytes),
+                                encoding: None,
+                            },
+                        )
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                        data_slice: None,
+                        commitment: None,
+                        min_context_slot: None,
+                    },
+                    with_context: Some(false),
+                }
+            ).unwrap()
+        })
+        .map(|(vesting_account_pubkey, vesting_account): (Pubkey, Account)| {
+            let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_account.data).unwrap();
+            (vesting_account_pubkey, vesting_record)
+        })
+        .collect()
+}
 
-        if let Some(realm_pubkey) = vesting_record.realm {
-            split_with_realm(
-                &vesting_addin_program_id,
-                &spl_token::id(),
-                &vesting_token_pubkey,
-                &vesting_owner.pubkey(),
-                &new_vesting_token_pubkey,
-                &new_vesting_owner_pubkey,
-                &payer.pubkey(),
-                schedules,
-                &governance_program_id,
-                &realm_pubkey,
-                &vesting_record.mint,
-            )
-        } else {
-            split(
-                &vesting_addin_program_id,
-                &spl_token::id(),
-                &vesting_token_pubkey,
-                &vesting_owner.pubkey(),
-                &new_vesting_token_pubkey,
-                &new_vesting_owner_pubkey,
-                &payer.pubkey(),
-                schedules,
-            )
-        }.unwrap(),
-    ];
+fn command_migrate_vesting_record(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+    compute_unit_price: Option<u64>,
+) {
+    let instruction = migrate_vesting_record(
+        &vesting_addin_program_id,
+        &vesting_token_pubkey,
+        &payer.pubkey(),
+    )
+    .unwrap();
 
     let transaction = create_transaction(
         &rpc_client,
-        &instructions,
+        &[instruction],
         payer,
-        &[vesting_owner, &new_vesting_token_keypair],
+        &[payer],
         compute_unit_price,
     ).unwrap();
     rpc_client.send_transaction(&transaction).unwrap();
 }
 
-fn command_list(
+fn command_list(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+) {
+    msg!("\n----------------- LOCKED TOKENS LIST ------------------\n");
+    let records = get_vesting_records(&rpc_client, &vesting_addin_program_id, None);
+
+    struct Info {
+        token: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+    }
+    let mut accounts = records
+        .into_iter()
+        .map(|(_, vesting_record)| {
+            let amount = vesting_record.total_amount().unwrap();
+            Info {token: vesting_record.token, owner: vesting_record.owner, amount}
+        })
+        .collect::<Vec<_>>();
+    accounts.sort_by(|l, r| l.amount.cmp(&r.amount).reverse());
+
+    let total_amount = accounts.iter().map(|v| v.amount).sum::<u64>();
+    msg!("Total amount: {}.{:09}", total_amount/1_000_000_000, total_amount%1_000_000_000);
+    
+    msg!("Vesting                                         Owner                                                      Amount");
+    for account in accounts {
+        msg!("{}\t{}\t{:12}.{:09}", 
+            account.token, 
+            account.owner, 
+            account.amount/1_000_000_000, 
+            account.amount%1_000_000_000,
+        );
+    }
+}
+
+/// Returns addresses of voter weight records of the realm sorted in ascending order.
+/// Only addresses are requested from the node, so the full account set can be fetched at once
+fn get_realm_voter_weight_record_addresses(
+    rpc_client: &RpcClient,
+    vesting_addin_program_id: &Pubkey,
+    realm_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+) -> Vec<Pubkey> {
+    let mut voter_weight_record_addresses = rpc_client.get_program_accounts_with_config(
+        vesting_addin_program_id,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                (0, VoterWeightRecord::ACCOUNT_DISCRIMINATOR.to_vec()),
+                (8, realm_pubkey.to_bytes().to_vec()),
+                (40, mint_pubkey.to_bytes().to_vec()),
+            ].into_iter().map(|(offset, bytes)|
+                rpc_filter::RpcFilterType::Memcmp(
+                    #[allow(deprecated)]
+                    rpc_filter::Memcmp {
+                        offset,
+                        bytes: rpc_filter::MemcmpEncodedBytes::Bytes(bytes),
+                        encoding: None,
+                    },
+                )
+            ).collect()),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                data_slice: Some(solana_account_decoder::UiDataSliceConfig { offset: 0, length: 0 }),
+                commitment: None,
+                min_context_slot: None,
+            },
+            with_context: Some(false),
+        }
+    ).unwrap()
+    .into_iter()
+    .map(|(voter_weight_record_pubkey, _)| voter_weight_record_pubkey)
+    .collect::<Vec<_>>();
+    voter_weight_record_addresses.sort();
+    voter_weight_record_addresses
+}
+
+fn command_audit(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    realm_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+) {
+    msg!("\n------------------ MAX VOTER WEIGHT AUDIT -------------------\n");
+    let voter_weight_record_addresses = get_realm_voter_weight_record_addresses(&rpc_client, &vesting_addin_program_id, &realm_pubkey, &mint_pubkey);
+    msg!("Voter weight records: {}", voter_weight_record_addresses.len());
+
+    let reconcile_instruction = |after: Option<Pubkey>, voter_weight_record_addresses: &[Pubkey]| {
+        reconcile_max_voter_weight(&vesting_addin_program_id, &realm_pubkey, &mint_pubkey, after, voter_weight_record_addresses).unwrap()
+    };
+
+    // An empty page still reports the max voter weight
+    let mut pages = split_into_transactions(&voter_weight_record_addresses, &payer.pubkey(), |page| vec![reconcile_instruction(None, page)]);
+    if pages.is_empty() {
+        pages.push(&[]);
+    }
+
+    // Every page continues after the last record of the previous one, so no record is counted twice
+    let mut max_voter_weight = 0u64;
+    let mut weighted_amount = 0u64;
+    let mut last_record = None;
+    for page in pages {
+        let return_data = simulate_return_data(&rpc_client, &payer.pubkey(), reconcile_instruction(last_record, page), "ReconcileMaxVoterWeight");
+        let reconciliation: MaxVoterWeightReconciliation = try_from_slice_unchecked(&return_data)
+            .expect("Can't decode ReconcileMaxVoterWeight return data");
+        max_voter_weight = reconciliation.max_voter_weight;
+        weighted_amount += reconciliation.weighted_amount;
+        last_record = reconciliation.last_record;
+    }
+
+    msg!("Max voter weight:           {}.{:09}", max_voter_weight/1_000_000_000, max_voter_weight%1_000_000_000);
+    msg!("Weighted amount of records: {}.{:09}", weighted_amount/1_000_000_000, weighted_amount%1_000_000_000);
+    if max_voter_weight == weighted_amount {
+        msg!("OK: max voter weight matches the voter weight records");
+    } else {
+        msg!("MISMATCH: difference {}", i128::from(max_voter_weight) - i128::from(weighted_amount));
+        exit(1);
+    }
+}
+
+fn command_get_withdrawable_amount(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_token_pubkey: Pubkey,
+) {
+    let instruction = get_withdrawable_amount(&vesting_addin_program_id, &vesting_token_pubkey).unwrap();
+    let return_data = simulate_return_data(&rpc_client, &payer.pubkey(), instruction, "GetWithdrawableAmount");
+    let withdrawable_amount: u64 = try_from_slice_unchecked(&return_data)
+        .expect("Can't decode GetWithdrawableAmount return data");
+
+    msg!("Withdrawable amount: {}", withdrawable_amount);
+}
+
+fn command_get_vesting_summary(
     rpc_client: RpcClient,
     vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
 ) {
-    msg!("\n----------------- LOCKED TOKENS LIST ------------------\n");
-    let records: Vec<(Pubkey,Account)> =
-    rpc_client.get_program_accounts_with_config(
-        &vesting_addin_program_id,
-        RpcProgramAccountsConfig {
-            filters: Some(vec![
-                rpc_filter::RpcFilterType::Memcmp(
-                    #[allow(deprecated)]
-                    rpc_filter::Memcmp {
-                        offset: 0,
-                        bytes: rpc_filter::MemcmpEncodedBytes::Bytes(vec![1]),
-                        encoding: None,
-                    },
-                )
-            ]),
-            account_config: RpcAccountInfoConfig {
-                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
-                data_slice: None,
-                commitment: None,
-                min_context_slot: None,
-            },
-            with_context: Some(false),
-        }
-    ).unwrap();
-
-    struct Info {
-        token: Pubkey,
-        owner: Pubkey,
-        amount: u64,
-    }
-    let mut accounts = records
+    let vesting_addresses = get_vesting_records_by_owner(&rpc_client, &vesting_addin_program_id, &vesting_owner_pubkey)
         .into_iter()
-        .map(|(_, account)| {
-            let vesting_record: VestingRecord = try_from_slice_unchecked(&account.data).unwrap();
-            let amount = vesting_record.schedule.iter().map(|v| v.amount).sum::<u64>();
-            Info {token: vesting_record.token, owner: vesting_record.owner, amount}
-        })
+        .filter(|(_, vesting_record)| vesting_record.mint == mint_pubkey)
+        .map(|(vesting_pubkey, _)| vesting_pubkey)
         .collect::<Vec<_>>();
-    accounts.sort_by(|l, r| l.amount.cmp(&r.amount).reverse());
 
-    let total_amount = accounts.iter().map(|v| v.amount).sum::<u64>();
-    msg!("Total amount: {}.{:09}", total_amount/1_000_000_000, total_amount%1_000_000_000);
-    
-    msg!("Vesting                                         Owner                                                      Amount");
-    for account in accounts {
-        msg!("{}\t{}\t{:12}.{:09}", 
-            account.token, 
-            account.owner, 
-            account.amount/1_000_000_000, 
-            account.amount%1_000_000_000,
-        );
+    let summary_instruction = |vesting_addresses: &[Pubkey]| {
+        get_vesting_summary(&vesting_addin_program_id, vesting_addresses).unwrap()
+    };
+
+    let mut summary = VestingSummary::default();
+    for page in split_into_transactions(&vesting_addresses, &payer.pubkey(), |page| vec![summary_instruction(page)]) {
+        let return_data = simulate_return_data(&rpc_client, &payer.pubkey(), summary_instruction(page), "GetVestingSummary");
+        let page_summary: VestingSummary = try_from_slice_unchecked(&return_data)
+            .expect("Can't decode GetVestingSummary return data");
+        summary.vesting_count += page_summary.vesting_count;
+        summary.total_amount += page_summary.total_amount;
+        summary.withdrawable_amount += page_summary.withdrawable_amount;
+        summary.frozen_amount += page_summary.frozen_amount;
+    }
+
+    msg!("Vesting accounts: {}", summary.vesting_count);
+    msg!("Total amount: {}", summary.total_amount);
+    msg!("Withdrawable amount: {}", summary.withdrawable_amount);
+    msg!("Frozen amount: {}", summary.frozen_amount);
+}
+
+fn command_get_voter_weight(
+    rpc_client: RpcClient,
+    vesting_addin_program_id: Pubkey,
+    payer: &dyn Signer,
+    vesting_owner_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    realm_pubkey: Pubkey,
+) {
+    let instruction = get_voter_weight(&vesting_addin_program_id, &realm_pubkey, &mint_pubkey, &vesting_owner_pubkey).unwrap();
+    let return_data = simulate_return_data(&rpc_client, &payer.pubkey(), instruction, "GetVoterWeight");
+    let info: VoterWeightInfo = try_from_slice_unchecked(&return_data)
+        .expect("Can't decode GetVoterWeight return data");
+
+    msg!("Voter weight: {}", info.voter_weight);
+    if let Some(voter_weight_expiry) = info.voter_weight_expiry {
+        msg!("Voter weight expiry: slot {}", voter_weight_expiry);
+    }
+    msg!("Total amount: {}", info.total_amount);
+    msg!("Vote percentage: {}.{:02}%", info.vote_percentage / 100, info.vote_percentage % 100);
+    if let Some(delegate) = info.delegate {
+        msg!("Delegated out: {} to {}", info.delegated_out, delegate);
     }
+    msg!("Delegated in: {}", info.delegated_in);
 }
 
 fn command_info(
@@ -631,37 +2040,174 @@ fn command_info(
     msg!("Vesting Account Pubkey: {:?}", &vesting_pubkey);
 
     let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
-    let vesting_record: VestingRecord = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+    let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
     msg!("Vesting Token Account Pubkey: {:?}", &vesting_token_pubkey);
-    report_vesting_record_info(&vesting_record);
+    report_vesting_record_info(&rpc_client, &vesting_record);
 }
 
-fn report_vesting_record_info(vesting_record: &VestingRecord) {
+fn report_vesting_record_info(rpc_client: &RpcClient, vesting_record: &VestingRecordV2) {
     msg!("Vesting Owner Address: {:?}", &vesting_record.owner);
     msg!("Vesting Mint Address:  {:?}", &vesting_record.mint);
     msg!("Vesting Token Address: {:?}", &vesting_record.token);
     msg!("Vesting Realm: {:?}", &vesting_record.realm);
+    msg!("Vesting Revoke Authority: {:?}", &vesting_record.revoke_authority);
+    if let Some(proposed_owner) = &vesting_record.proposed_owner {
+        msg!("Vesting Proposed Owner: {:?}", proposed_owner);
+    }
+    if vesting_record.account_type == VestingAccountType::VestingRecordV2 {
+        msg!("Vesting Creator: {:?}", &vesting_record.creator);
+        msg!("Vesting Created At: {} ({})",
+            vesting_record.created_at,
+            NaiveDateTime::from_timestamp(vesting_record.created_at.try_into().unwrap(), 0u32),
+        );
+        msg!("Vesting Original Total: {}", vesting_record.original_total);
+        msg!("Vesting Time Base: {:?}", vesting_record.time_base);
+        if vesting_record.frozen {
+            msg!("Vesting is FROZEN by the freeze authority of the Realm");
+        }
+        if let Some(manager) = &vesting_record.manager {
+            msg!("Vesting Manager: {:?}", manager);
+        }
+        if vesting_record.allow_crank_withdraw {
+            msg!("Vesting allows crank withdraw to the owner's associated token account");
+        }
+    } else {
+        msg!("Vesting Record is not migrated (legacy layout)");
+    }
+
+    let format_time = release_time_formatter(rpc_client, vesting_record.time_base);
+    report_schedules(&vesting_record.schedule, &*format_time);
+    if let Some(linear) = &vesting_record.linear {
+        report_linear_schedule(&linear.schedule, linear.withdrawn_amount, &*format_time);
+    }
+}
 
-    report_schedules(&vesting_record.schedule);
+/// Returns formatter of the release times expressed in the time base.
+/// Slots and epochs are shown with the wall-clock time estimated from the current cluster clock
+fn release_time_formatter(rpc_client: &RpcClient, time_base: VestingTimeBase) -> Box<dyn Fn(u64) -> String> {
+    let format_timestamp = |timestamp: i64| NaiveDateTime::from_timestamp(timestamp, 0u32);
+    if time_base == VestingTimeBase::UnixTimestamp {
+        return Box::new(move |time| format!("timestamp {} ({})", time, format_timestamp(time.try_into().unwrap())));
+    }
+
+    let clock_account = rpc_client.get_account(&sysvar::clock::id()).unwrap();
+    let clock: Clock = from_account(&clock_account).expect("Can't decode Clock sysvar");
+    let epoch_schedule = rpc_client.get_epoch_schedule().unwrap();
+    Box::new(move |time| {
+        let (units, slot) = match time_base {
+            VestingTimeBase::Slot => ("slot", time),
+            _ => ("epoch", epoch_schedule.get_first_slot_in_epoch(time)),
+        };
+        let estimated_timestamp = clock.unix_timestamp +
+                (slot as i64 - clock.slot as i64) * DEFAULT_MS_PER_SLOT as i64 / 1000;
+        format!("{} {} (~{})", units, time, format_timestamp(estimated_timestamp))
+    })
 }
 
-fn report_schedules(schedules: &[VestingSchedule]) {
+fn report_schedules(schedules: &[VestingSchedule], format_time: &dyn Fn(u64) -> String) {
     msg!("Schedule:");
     let total_amount: u64 = schedules.iter()
         .enumerate()
         .map(|(i, item)| {
-            msg!("  {:2}: amount {}, timestamp {} ({})",
+            msg!("  {:2}: amount {}, {}",
                 i,
                 &item.amount,
-                &item.release_time,
-                NaiveDateTime::from_timestamp(item.release_time.try_into().unwrap(), 0u32),
+                format_time(item.release_time),
             );
             item.amount
         }).sum();
     msg!("Total amount: {}", total_amount);
 }
 
+fn report_linear_schedule(schedule: &LinearVestingSchedule, withdrawn_amount: u64, format_time: &dyn Fn(u64) -> String) {
+    msg!("Linear schedule:");
+    msg!("  start: {}", format_time(schedule.start_time));
+    msg!("  cliff: {}", format_time(schedule.cliff_time));
+    msg!("  end:   {}", format_time(schedule.end_time));
+    msg!("  amount {}, withdrawn {}", schedule.amount, withdrawn_amount);
+}
+
+fn parse_date_time(arg_matches: &ArgMatches, name: &str) -> Option<u64> {
+    value_of::<String>(arg_matches, name).map(|value|
+        DateTime::parse_from_rfc3339(&value)
+            .unwrap()
+            .timestamp()
+            .try_into()
+            .unwrap()
+    )
+}
+
+fn parse_time_base(arg_matches: &ArgMatches) -> VestingTimeBase {
+    let time_base = match arg_matches.value_of("time_base") {
+        Some("slot") => VestingTimeBase::Slot,
+        Some("epoch") => VestingTimeBase::Epoch,
+        _ => VestingTimeBase::UnixTimestamp,
+    };
+    // Date-time arguments are converted to unix timestamps
+    if time_base != VestingTimeBase::UnixTimestamp &&
+       (arg_matches.is_present("continuous") || arg_matches.is_present("release-frequency")) {
+        eprintln!("error: Slot and epoch based vestings require `release-times`");
+        exit(1);
+    }
+    time_base
+}
+
+fn parse_linear_schedule(arg_matches: &ArgMatches) -> Option<LinearVestingSchedule> {
+    if !arg_matches.is_present("continuous") {
+        return None;
+    }
+    let schedule_amounts: Vec<u64> = values_of(arg_matches, "amounts").unwrap();
+    if schedule_amounts.len() != 1 {
+        panic!("Continuous vesting must have one amount which will be released linearly")
+    }
+    let start_time = parse_date_time(arg_matches, "start-date-time")
+        .expect("Continuous vesting requires `start-date-time`");
+    let end_time = parse_date_time(arg_matches, "end-date-time")
+        .expect("Continuous vesting requires `end-date-time`");
+    let cliff_time = parse_date_time(arg_matches, "cliff-date-time").unwrap_or(start_time);
+    Some(LinearVestingSchedule {
+        start_time,
+        cliff_time,
+        end_time,
+        amount: schedule_amounts[0],
+    })
+}
+
+/// Parses deposits for `deposit-batch` command.
+/// Every line of the file is `OWNER,AMOUNT,RELEASE_DATE_TIME[,AMOUNT,RELEASE_DATE_TIME...]`
+/// (empty lines and lines started with '#' are skipped)
+fn parse_batch_deposits(file_path: &str) -> Vec<(Pubkey, Vec<VestingSchedule>)> {
+    fs::read_to_string(file_path)
+        .unwrap()
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields = line.split(',').map(|field| field.trim()).collect::<Vec<_>>();
+            if fields.len() < 3 || fields.len() % 2 != 1 {
+                panic!("Invalid deposit line: {}", line);
+            }
+            let vesting_owner_pubkey = Pubkey::from_str(fields[0]).unwrap();
+            let schedules = fields[1..].chunks(2)
+                .map(|schedule| VestingSchedule {
+                    amount: schedule[0].parse().unwrap(),
+                    release_time: DateTime::parse_from_rfc3339(schedule[1])
+                        .unwrap()
+                        .timestamp()
+                        .try_into()
+                        .unwrap(),
+                })
+                .collect();
+            (vesting_owner_pubkey, schedules)
+        })
+        .collect()
+}
+
 fn parse_schedules(arg_matches: &ArgMatches) -> Vec<VestingSchedule> {
+    if arg_matches.is_present("continuous") {
+        return Vec::new();
+    }
+
     let mut schedule_amounts: Vec<u64> = values_of(arg_matches, "amounts").unwrap();
     let release_frequency: Option<String> = value_of(arg_matches, "release-frequency");
     let schedule_times = if let Some(release_frequency_some) = release_frequency {
@@ -758,6 +2304,7 @@ trait ArgsHelper {
     fn arg_realm_address(self, required: bool) -> Self;
     fn arg_mint_address(self, required: bool) -> Self;
     fn arg_schedules(self) -> Self;
+    fn arg_revoke_authority_signer(self) -> Self;
 }
 
 impl ArgsHelper for App<'_, '_> {
@@ -848,6 +2395,21 @@ impl ArgsHelper for App<'_, '_> {
         )
     }
 
+    fn arg_revoke_authority_signer(self) -> Self {
+        self.arg(
+            Arg::with_name("revoke_authority")
+                .long("revoke_authority")
+                .value_name("KEYPAIR")
+                .required(true)
+                .validator(is_valid_signer)
+                .takes_value(true)
+                .help(
+                    "Specify the revoke authority of the vesting. \
+                        This may be a keypair file, the ASK keyword.",
+                ),
+        )
+    }
+
     fn arg_schedules(self) -> Self {
         self
             // scheduled vesting
@@ -872,7 +2434,7 @@ impl ArgsHelper for App<'_, '_> {
             .arg(
             Arg::with_name("release-times")
                 .long("release-times")
-                .conflicts_with("release-frequency")
+                .conflicts_with_all(&["release-frequency", "continuous"])
                 .value_name("SLOT")
                 .validator(is_slot)
                 .takes_value(true)
@@ -893,7 +2455,7 @@ impl ArgsHelper for App<'_, '_> {
                     .long("release-frequency")
                     .value_name("RELEASE_FREQUENCY")
                     .takes_value(true)
-                    .conflicts_with("release-times")
+                    .conflicts_with_all(&["release-times", "continuous"])
                     .help(
                         "Frequency of release amount. \
                         You start on 1sth of Nov and end on 5th of Nov. \
@@ -927,6 +2489,29 @@ impl ArgsHelper for App<'_, '_> {
                         Example, 2022-17-06T20:11:18Z",
                     ),
             )
+            // continuous vesting
+            .arg(
+                Arg::with_name("continuous")
+                    .long("continuous")
+                    .takes_value(false)
+                    .help(
+                        "Release amount continuously (per second) between \
+                        `start-date-time` and `end-date-time`. \
+                        Nothing is released before `cliff-date-time`.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("cliff-date-time")
+                    .long("cliff-date-time")
+                    .value_name("CLIFF_DATE_TIME")
+                    .takes_value(true)
+                    .requires("continuous")
+                    .help(
+                        "Cliff time of continuous vesting (defaults to `start-date-time`). \
+                        Must be RFC 3339 and ISO 8601 sortable date time. \
+                        Example, 2022-01-06T20:11:18Z",
+                    ),
+            )
     }
 }
 
@@ -971,80 +2556,340 @@ fn main() {
                 .global(true)
                 .help("Specify the address (public key) of the governance program."),
         )
-        .arg(
-            Arg::with_name("vesting_program_id")
-                .long("vesting_program_id")
-                .value_name("ADDRESS")
-                .default_value("Hu548Kzvfo9C9zATuXVpnmxYRUCJxrsXLdiKjxuTczim")
-                .validator(is_pubkey)
-                .takes_value(true)
-                .global(true)
-                .help("Specify the address (public key) of the vesting addin program."),
+        .arg(
+            Arg::with_name("vesting_program_id")
+                .long("vesting_program_id")
+                .value_name("ADDRESS")
+                .default_value("Hu548Kzvfo9C9zATuXVpnmxYRUCJxrsXLdiKjxuTczim")
+                .validator(is_pubkey)
+                .takes_value(true)
+                .global(true)
+                .help("Specify the address (public key) of the vesting addin program."),
+        )
+        .subcommand(
+            SubCommand::with_name("deposit")
+                .about("Create a new vesting contract with an optional release schedule")
+                .arg(
+                    Arg::with_name("source_owner")
+                        .long("source_owner")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the source account owner. \
+                            This may be a keypair file, the ASK keyword. \
+                            Defaults to the client keypair.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("source_token_address")
+                        .long("source_token_address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the source token account address."),
+                )
+                .arg_vesting_owner_address(true)
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+                .arg_schedules()
+                .arg(
+                    Arg::with_name("revoke_authority")
+                        .long("revoke_authority")
+                        .value_name("ADDRESS")
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the authority (publickey) which can revoke unreleased tokens."),
+                )
+                .arg(
+                    Arg::with_name("time_base")
+                        .long("time_base")
+                        .value_name("TIME_BASE")
+                        .possible_values(&["unix", "slot", "epoch"])
+                        .default_value("unix")
+                        .takes_value(true)
+                        .help("Specify the units of the release times. \
+                               Slot and epoch based vestings accept only `release-times`."),
+                )
+                .arg_optional_payer()
+                .arg(
+                    Arg::with_name("confirm")
+                        .long("confirm")
+                        .value_name("CONFIRM")
+                        .takes_value(true)
+                        .default_value("true")
+                        .help("Specify whether to wait transaction confirmation"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("deposit-batch")
+                .about("Create vesting contracts for many owners funded from one source account")
+                .arg(
+                    Arg::with_name("source_owner")
+                        .long("source_owner")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the source account owner. \
+                            This may be a keypair file, the ASK keyword.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("source_token_address")
+                        .long("source_token_address")
+                        .value_name("ADDRESS")
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the source token account address. Defaults to the associated token account of the source owner."),
+                )
+                .arg_mint_address(true)
+                .arg_realm_address(false)
+                .arg(
+                    Arg::with_name("deposits_file")
+                        .long("deposits_file")
+                        .value_name("PATH")
+                        .required(true)
+                        .takes_value(true)
+                        .help(
+                            "Specify the file with deposits. Every line of the file is \
+                            `OWNER,AMOUNT,RELEASE_DATE_TIME[,AMOUNT,RELEASE_DATE_TIME...]` \
+                            with release times in RFC3339 format.",
+                        ),
+                )
+                .arg_optional_payer()
+        )
+        .subcommand(
+            SubCommand::with_name("add-schedules")
+                .about("Add schedules (and their tokens) to an existing vesting contract")
+                .arg(
+                    Arg::with_name("source_owner")
+                        .long("source_owner")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the source account owner. \
+                            This may be a keypair file, the ASK keyword.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("source_token_address")
+                        .long("source_token_address")
+                        .value_name("ADDRESS")
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the source token account address. \
+                               Defaults to the associated token account of the source owner."),
+                )
+                .arg_vesting_address()
+                .arg_schedules()
+                .arg_optional_payer()
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw")
+                .about("Unlock & Withdraw a vesting contract. This will only release \
+                        the schedules that have reached maturity.")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
+                .arg(
+                    Arg::with_name("destination_address")
+                        .long("destination_address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the destination token address (publickey)."),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("AMOUNT")
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .help("Withdraw only the specified amount of released tokens \
+                               (all released tokens are withdrawn if omitted)."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("revoke")
+                .about("Revoke all unreleased tokens of a revocable vesting contract. \
+                        Schedules that have reached maturity stay withdrawable by the owner.")
+                .arg_optional_payer()
+                .arg_revoke_authority_signer()
+                .arg_vesting_address()
+                .arg(
+                    Arg::with_name("destination_address")
+                        .long("destination_address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the destination token address (publickey)."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-revoke-authority")
+                .about("Change or remove the revoke authority of a vesting contract")
+                .arg_optional_payer()
+                .arg_revoke_authority_signer()
+                .arg_vesting_address()
+                .arg(
+                    Arg::with_name("new_revoke_authority")
+                        .long("new_revoke_authority")
+                        .value_name("ADDRESS")
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the new revoke authority address (publickey). \
+                               The vesting becomes irrevocable if omitted."),
+                )
         )
         .subcommand(
-            SubCommand::with_name("deposit")
-                .about("Create a new vesting contract with an optional release schedule")
+            SubCommand::with_name("set-crank-withdraw")
+                .about("Allow anyone to withdraw matured tokens of a vesting contract to the owner's associated token account")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
                 .arg(
-                    Arg::with_name("source_owner")
-                        .long("source_owner")
+                    Arg::with_name("disable")
+                        .long("disable")
+                        .takes_value(false)
+                        .help("Disallow crank withdraw."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-manager")
+                .about("Set the manager which can split or change owner of a vesting contract on behalf of the owner")
+                .arg_optional_payer()
+                .arg(
+                    Arg::with_name("authority")
+                        .long("authority")
                         .value_name("KEYPAIR")
                         .required(true)
                         .validator(is_valid_signer)
                         .takes_value(true)
                         .help(
-                            "Specify the source account owner. \
-                            This may be a keypair file, the ASK keyword. \
-                            Defaults to the client keypair.",
+                            "Specify the vesting owner (or the current vesting manager with --clear). \
+                            This may be a keypair file, the ASK keyword.",
                         ),
                 )
+                .arg_vesting_address()
                 .arg(
-                    Arg::with_name("source_token_address")
-                        .long("source_token_address")
+                    Arg::with_name("manager")
+                        .long("manager")
                         .value_name("ADDRESS")
-                        .required(true)
                         .validator(is_pubkey)
                         .takes_value(true)
-                        .help("Specify the source token account address."),
+                        .required_unless("clear")
+                        .help("Specify the new vesting manager address (publickey)."),
                 )
-                .arg_vesting_owner_address(true)
-                .arg_mint_address(true)
+                .arg(
+                    Arg::with_name("clear")
+                        .long("clear")
+                        .takes_value(false)
+                        .conflicts_with("manager")
+                        .help("Remove the manager of the vesting."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("crank")
+                .about("Withdraw matured tokens of all vesting contracts allowing crank withdraw to the owners' associated token accounts")
+                .arg_payer()
+        )
+        .subcommand(
+            SubCommand::with_name("change-owner")
+                .about("Change the owner of a vesting contract")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
+                .arg_new_vesting_owner()
+        )
+        .subcommand(
+            SubCommand::with_name("attach-realm")
+                .about("Attach a vesting contract to the Realm (tokens are added to the voter weight of the owner)")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
                 .arg_realm_address(true)
-                .arg_schedules()
+        )
+        .subcommand(
+            SubCommand::with_name("detach-realm")
+                .about("Detach a vesting contract from its Realm (tokens are removed from the voter weight of the owner)")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
+        )
+        .subcommand(
+            SubCommand::with_name("move-realm")
+                .about("Move a vesting contract to another Realm")
                 .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
+                .arg_realm_address(true)
                 .arg(
-                    Arg::with_name("confirm")
-                        .long("confirm")
-                        .value_name("CONFIRM")
+                    Arg::with_name("new_governance_program_id")
+                        .long("new_governance_program_id")
+                        .value_name("ADDRESS")
+                        .validator(is_pubkey)
                         .takes_value(true)
-                        .default_value("true")
-                        .help("Specify whether to wait transaction confirmation"),
+                        .help("Specify the address (public key) of the governance program of the new Realm. \
+                            Defaults to the governance program of the current Realm."),
                 )
         )
         .subcommand(
-            SubCommand::with_name("withdraw")
-                .about("Unlock & Withdraw a vesting contract. This will only release \
-                        the schedules that have reached maturity.")
+            SubCommand::with_name("compact")
+                .about("Drop withdrawn schedules of a vesting contract and reclaim released rent")
                 .arg_optional_payer()
                 .arg_vesting_owner_signer()
                 .arg_vesting_address()
                 .arg(
-                    Arg::with_name("destination_address")
-                        .long("destination_address")
+                    Arg::with_name("rent_receiver")
+                        .long("rent_receiver")
                         .value_name("ADDRESS")
-                        .required(true)
                         .validator(is_pubkey)
                         .takes_value(true)
-                        .help("Specify the destination token address (publickey)."),
+                        .help("Specify the account which receives released rent. Defaults to the vesting owner."),
                 )
         )
         .subcommand(
-            SubCommand::with_name("change-owner")
-                .about("Change the owner of a vesting contract")
+            SubCommand::with_name("propose-owner")
+                .about("Propose the new owner of a vesting contract. \
+                        The ownership is transferred when the proposed owner accepts it.")
                 .arg_optional_payer()
                 .arg_vesting_owner_signer()
                 .arg_vesting_address()
                 .arg_new_vesting_owner()
         )
+        .subcommand(
+            SubCommand::with_name("accept-owner")
+                .about("Accept the ownership of a vesting contract")
+                .arg_optional_payer()
+                .arg(
+                    Arg::with_name("new_vesting_owner")
+                        .long("new_vesting_owner")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the proposed vesting owner account address. \
+                            This may be a keypair file, the ASK keyword.",
+                        ),
+                )
+                .arg_vesting_address()
+        )
+        .subcommand(
+            SubCommand::with_name("cancel-owner-proposal")
+                .about("Cancel the ownership proposal of a vesting contract")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
+        )
         .subcommand(
             SubCommand::with_name("create-voter-weight-record")
                 .about("Create Voter Weight Record")
@@ -1091,6 +2936,58 @@ fn main() {
                         .help("Deposited tokens percentage of voting."),
                 )
         )
+        .subcommand(
+            SubCommand::with_name("delegate")
+                .about("Delegate voting weight of the owner's vested tokens to another wallet")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg(
+                    Arg::with_name("delegate")
+                        .long("delegate")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the delegate address (publickey)."),
+                )
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("AMOUNT")
+                        .required(true)
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .help("Number of tokens to delegate."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("undelegate")
+                .about("Return delegated voting weight from the delegate")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg(
+                    Arg::with_name("delegate")
+                        .long("delegate")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the delegate address (publickey)."),
+                )
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("AMOUNT")
+                        .required(true)
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .help("Number of tokens to undelegate."),
+                )
+        )
         .subcommand(
             SubCommand::with_name("split")
                 .about("Move remaining vesting to another account using a new release schedule")
@@ -1100,6 +2997,175 @@ fn main() {
                 .arg_new_vesting_owner()
                 .arg_schedules()
         )
+        .subcommand(
+            SubCommand::with_name("merge")
+                .about("Merge the source vesting contract into another one with the same owner. \
+                        The source vesting contract is closed.")
+                .arg_optional_payer()
+                .arg_vesting_owner_signer()
+                .arg_vesting_address()
+                .arg(
+                    Arg::with_name("source_vesting_address")
+                        .long("source_vesting_address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Specify the source vesting token address (publickey)."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-lockup-config")
+                .about("Set lockup-duration voting multiplier for a Realm")
+                .arg_optional_payer()
+                .arg(
+                    Arg::with_name("realm_authority")
+                        .long("realm_authority")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the realm authority. \
+                            This may be a keypair file, the ASK keyword.",
+                        ),
+                )
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+                .arg(
+                    Arg::with_name("baseline_factor")
+                        .long("baseline_factor")
+                        .value_name("FACTOR")
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .required_unless("disable")
+                        .help("Voting factor of unlocked tokens."),
+                )
+                .arg(
+                    Arg::with_name("max_extra_factor")
+                        .long("max_extra_factor")
+                        .value_name("FACTOR")
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .required_unless("disable")
+                        .help("Extra voting factor of tokens locked for saturation period or longer."),
+                )
+                .arg(
+                    Arg::with_name("saturation_secs")
+                        .long("saturation_secs")
+                        .value_name("SECONDS")
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .required_unless("disable")
+                        .help("Lockup duration (in seconds) giving maximum extra voting factor."),
+                )
+                .arg(
+                    Arg::with_name("disable")
+                        .long("disable")
+                        .takes_value(false)
+                        .conflicts_with_all(&["baseline_factor", "max_extra_factor", "saturation_secs"])
+                        .help("Remove lockup-duration voting multiplier of the Realm."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-vote-percentage-policy")
+                .about("Set limits of the vote percentage for a Realm")
+                .arg_optional_payer()
+                .arg(
+                    Arg::with_name("realm_authority")
+                        .long("realm_authority")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the realm authority. \
+                            This may be a keypair file, the ASK keyword.",
+                        ),
+                )
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+                .arg(
+                    Arg::with_name("default_percentage")
+                        .long("default_percentage")
+                        .value_name("PERCENTAGE")
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .required_unless("disable")
+                        .help("Vote percentage of new voter weight records (in hundredths of a percent)."),
+                )
+                .arg(
+                    Arg::with_name("max_percentage")
+                        .long("max_percentage")
+                        .value_name("PERCENTAGE")
+                        .validator(is_amount)
+                        .takes_value(true)
+                        .required_unless("disable")
+                        .help("Maximum vote percentage (in hundredths of a percent)."),
+                )
+                .arg(
+                    Arg::with_name("disable_partial_voting")
+                        .long("disable_partial_voting")
+                        .takes_value(false)
+                        .help("Allow only the maximum vote percentage."),
+                )
+                .arg(
+                    Arg::with_name("disable")
+                        .long("disable")
+                        .takes_value(false)
+                        .conflicts_with_all(&["default_percentage", "max_percentage", "disable_partial_voting"])
+                        .help("Remove the vote percentage policy of the Realm."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-freeze-authority")
+                .about("Set the Governance which can freeze vesting contracts of a Realm")
+                .arg_optional_payer()
+                .arg(
+                    Arg::with_name("realm_authority")
+                        .long("realm_authority")
+                        .value_name("KEYPAIR")
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the realm authority. \
+                            This may be a keypair file, the ASK keyword.",
+                        ),
+                )
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+                .arg(
+                    Arg::with_name("freeze_authority")
+                        .long("freeze_authority")
+                        .value_name("ADDRESS")
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .required_unless("disable")
+                        .help("Specify the Governance account (publickey) of the Realm."),
+                )
+                .arg(
+                    Arg::with_name("disable")
+                        .long("disable")
+                        .takes_value(false)
+                        .conflicts_with("freeze_authority")
+                        .help("Remove the freeze authority of the Realm."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("recalculate-voter-weight")
+                .about("Recalculate voter weight of a vesting owner using lockup-duration voting multiplier of the Realm")
+                .arg_payer()
+                .arg_vesting_owner_address(true)
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+        )
+        .subcommand(
+            SubCommand::with_name("migrate-vesting-record")
+                .about("Migrate a vesting contract to the record layout with creation metadata")
+                .arg_payer()
+                .arg_vesting_address()
+        )
         .subcommand(
             SubCommand::with_name("info")
                 .about("Print information about a vesting contract")
@@ -1111,8 +3177,36 @@ fn main() {
                 .arg_vesting_owner_address(true)
         )
         .subcommand(
-            SubCommand::with_name("list")
-                .about("Print the list of locked tokens")
+            SubCommand::with_name("list")
+                .about("Print the list of locked tokens")
+        )
+        .subcommand(
+            SubCommand::with_name("get-withdrawable-amount")
+                .about("Get the number of tokens which can be withdrawn from a vesting contract now")
+                .arg_payer()
+                .arg_vesting_address()
+        )
+        .subcommand(
+            SubCommand::with_name("get-vesting-summary")
+                .about("Get totals of the vesting contracts of an owner")
+                .arg_payer()
+                .arg_vesting_owner_address(true)
+                .arg_mint_address(true)
+        )
+        .subcommand(
+            SubCommand::with_name("get-voter-weight")
+                .about("Get voter weight of a vesting owner in a Realm")
+                .arg_payer()
+                .arg_vesting_owner_address(true)
+                .arg_mint_address(true)
+                .arg_realm_address(true)
+        )
+        .subcommand(
+            SubCommand::with_name("audit")
+                .about("Check that the MaxVoterWeightRecord of a Realm matches the weighted amounts of its voter weight records")
+                .arg_payer()
+                .arg_realm_address(true)
+                .arg_mint_address(true)
         )
         .get_matches();
 
@@ -1142,6 +3236,9 @@ fn main() {
 
             let confirm: bool = value_of(arg_matches, "confirm").unwrap();
             let schedules = parse_schedules(arg_matches);
+            let linear_schedule = parse_linear_schedule(arg_matches);
+            let revoke_authority = pubkey_of(arg_matches, "revoke_authority");
+            let time_base = parse_time_base(arg_matches);
 
             if let Some(realm_pubkey) = realm_opt {
                 command_deposit_with_realm_svc(
@@ -1155,6 +3252,9 @@ fn main() {
                     mint_pubkey,
                     realm_pubkey,
                     schedules,
+                    linear_schedule,
+                    revoke_authority,
+                    time_base,
                     compute_unit_price,
                     confirm,
                 )
@@ -1168,17 +3268,77 @@ fn main() {
                     vesting_owner_pubkey,
                     mint_pubkey,
                     schedules,
+                    linear_schedule,
+                    revoke_authority,
+                    time_base,
                     compute_unit_price,
                     confirm,
                 )
             }
         }
+        ("deposit-batch", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let source_signer = get_signer(arg_matches, "source_owner", &mut wallet_manager)
+                .expect("Need to specify `source_owner`");
+            let source_token_pubkey = pubkey_of(arg_matches, "source_token_address");
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_opt: Option<Pubkey> = pubkey_of(arg_matches, "realm_address");
+            let deposits = parse_batch_deposits(arg_matches.value_of("deposits_file").unwrap());
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*source_signer);
+
+            command_deposit_batch(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*source_signer,
+                source_token_pubkey,
+                mint_pubkey,
+                realm_opt,
+                deposits,
+                compute_unit_price,
+            )
+        }
+        ("add-schedules", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let source_signer = get_signer(arg_matches, "source_owner", &mut wallet_manager)
+                .expect("Need to specify `source_owner`");
+            let source_token_pubkey = pubkey_of(arg_matches, "source_token_address");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*source_signer);
+
+            if arg_matches.is_present("continuous") {
+                panic!("Continuous schedule can't be added to an existing vesting")
+            }
+            let schedules = parse_schedules(arg_matches);
+
+            command_add_schedules(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*source_signer,
+                source_token_pubkey,
+                vesting_token_pubkey,
+                schedules,
+                compute_unit_price,
+            )
+        }
         ("withdraw", Some(arg_matches)) => {
             let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
             let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager).expect("Need to specify `vesting_owner`");
             let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
 
             let destination_token_pubkey = pubkey_of(arg_matches, "destination_address").unwrap();
+            let amount: Option<u64> = value_of(arg_matches, "amount");
 
             let payer_keypair = keypair_of(arg_matches, "payer");
             let payer = payer_keypair
@@ -1189,7 +3349,7 @@ fn main() {
             let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
 
             let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
-            let vesting_record: VestingRecord = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+            let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
 
             if let Some(realm_pubkey) = vesting_record.realm {
                 let mint_pubkey: Pubkey = vesting_record.mint;
@@ -1204,6 +3364,7 @@ fn main() {
                     mint_pubkey,
                     realm_pubkey,
                     destination_token_pubkey,
+                    amount,
                     compute_unit_price,
                 )
             } else {
@@ -1213,56 +3374,343 @@ fn main() {
                     payer,
                     &*vesting_owner_signer,
                     vesting_token_pubkey,
+                    vesting_record.mint,
                     destination_token_pubkey,
+                    amount,
                     compute_unit_price,
                 )
             };
         }
-        ("change-owner", Some(arg_matches)) => {
+        ("revoke", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let revoke_authority_signer = get_signer(arg_matches, "revoke_authority", &mut wallet_manager)
+                .expect("Need to specify `revoke_authority`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let destination_token_pubkey = pubkey_of(arg_matches, "destination_address").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*revoke_authority_signer);
+
+            command_revoke(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*revoke_authority_signer,
+                vesting_token_pubkey,
+                destination_token_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("set-revoke-authority", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let revoke_authority_signer = get_signer(arg_matches, "revoke_authority", &mut wallet_manager)
+                .expect("Need to specify `revoke_authority`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let new_revoke_authority_pubkey = pubkey_of(arg_matches, "new_revoke_authority");
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*revoke_authority_signer);
+
+            command_set_revoke_authority(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*revoke_authority_signer,
+                vesting_token_pubkey,
+                new_revoke_authority_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("set-crank-withdraw", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager).expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let allow = !arg_matches.is_present("disable");
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            command_set_crank_withdraw(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                allow,
+                compute_unit_price,
+            )
+        }
+        ("set-manager", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let authority = get_signer(arg_matches, "authority", &mut wallet_manager)
+                .expect("Need to specify `authority`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let manager_pubkey = pubkey_of(arg_matches, "manager");
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*authority);
+
+            command_set_manager(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*authority,
+                vesting_token_pubkey,
+                manager_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("crank", Some(arg_matches)) => {
+            let payer_keypair = keypair_of(arg_matches, "payer").unwrap();
+
+            command_crank(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                &payer_keypair,
+                compute_unit_price,
+            )
+        }
+        ("change-owner", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+
+            let new_vesting_owner_pubkey = pubkey_of(arg_matches, "new_vesting_owner").unwrap();
+            
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+
+            let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+            let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+
+            if let Some(realm_pubkey) = vesting_record.realm {
+                let mint_pubkey: Pubkey = vesting_record.mint;
+
+                command_change_owner_with_realm(
+                    rpc_client,
+                    governance_program_id,
+                    vesting_addin_program_id,
+                    payer,
+                    &*vesting_owner_signer,
+                    vesting_token_pubkey,
+                    mint_pubkey,
+                    realm_pubkey,
+                    new_vesting_owner_pubkey,
+                    compute_unit_price,
+                )
+            } else {
+                command_change_owner(
+                    rpc_client,
+                    vesting_addin_program_id,
+                    payer,
+                    &*vesting_owner_signer,
+                    vesting_token_pubkey,
+                    new_vesting_owner_pubkey,
+                    compute_unit_price,
+                )
+            }
+        }
+        ("attach-realm", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+            let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+            let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+
+            command_attach_realm(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                vesting_record.mint,
+                realm_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("detach-realm", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+            let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+            let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+            let realm_pubkey = vesting_record.realm.expect("Vesting is not under realm");
+
+            command_detach_realm(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                vesting_record.mint,
+                realm_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("move-realm", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let new_realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+            let new_governance_program_id = pubkey_of(arg_matches, "new_governance_program_id")
+                .unwrap_or(governance_program_id);
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+            let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
+            let vesting_record: VestingRecordV2 = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+            let realm_pubkey = vesting_record.realm.expect("Vesting is not under realm");
+
+            command_move_realm(
+                rpc_client,
+                governance_program_id,
+                new_governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                vesting_record.mint,
+                realm_pubkey,
+                new_realm_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("compact", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let rent_receiver_pubkey = pubkey_of(arg_matches, "rent_receiver")
+                .unwrap_or_else(|| vesting_owner_signer.pubkey());
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            command_compact(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                rent_receiver_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("propose-owner", Some(arg_matches)) => {
             let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
             let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
                 .expect("Need to specify `vesting_owner`");
             let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let proposed_vesting_owner_pubkey = pubkey_of(arg_matches, "new_vesting_owner").unwrap();
 
-            let new_vesting_owner_pubkey = pubkey_of(arg_matches, "new_vesting_owner").unwrap();
-            
             let payer_keypair = keypair_of(arg_matches, "payer");
             let payer = payer_keypair
                 .as_ref()
                 .map(|v| v as &dyn Signer)
                 .unwrap_or(&*vesting_owner_signer);
 
-            let (vesting_pubkey,_) = Pubkey::find_program_address(&[vesting_token_pubkey.as_ref()], &vesting_addin_program_id);
+            command_propose_owner(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                proposed_vesting_owner_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("accept-owner", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let new_vesting_owner_signer = get_signer(arg_matches, "new_vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `new_vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
 
-            let vesting_record_account_data = rpc_client.get_account_data(&vesting_pubkey).unwrap();
-            let vesting_record: VestingRecord = try_from_slice_unchecked(&vesting_record_account_data).unwrap();
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*new_vesting_owner_signer);
 
-            if let Some(realm_pubkey) = vesting_record.realm {
-                let mint_pubkey: Pubkey = vesting_record.mint;
+            command_accept_owner(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*new_vesting_owner_signer,
+                vesting_token_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("cancel-owner-proposal", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
 
-                command_change_owner_with_realm(
-                    rpc_client,
-                    governance_program_id,
-                    vesting_addin_program_id,
-                    payer,
-                    &*vesting_owner_signer,
-                    vesting_token_pubkey,
-                    mint_pubkey,
-                    realm_pubkey,
-                    new_vesting_owner_pubkey,
-                    compute_unit_price,
-                )
-            } else {
-                command_change_owner(
-                    rpc_client,
-                    vesting_addin_program_id,
-                    payer,
-                    &*vesting_owner_signer,
-                    vesting_token_pubkey,
-                    new_vesting_owner_pubkey,
-                    compute_unit_price,
-                )
-            }
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            command_cancel_owner_proposal(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                compute_unit_price,
+            )
         }
         ("create-voter-weight-record", Some(arg_matches)) => {
             let record_owner_pubkey = pubkey_of(arg_matches, "record_owner").unwrap();
@@ -1312,6 +3760,60 @@ fn main() {
                 compute_unit_price,
             )
         }
+        ("delegate", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager).expect("Need to specify `vesting_owner`");
+            let delegate_pubkey = pubkey_of(arg_matches, "delegate").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+            let amount: u64 = value_of(arg_matches, "amount").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            command_delegate(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                delegate_pubkey,
+                mint_pubkey,
+                realm_pubkey,
+                amount,
+                compute_unit_price,
+            )
+        }
+        ("undelegate", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager).expect("Need to specify `vesting_owner`");
+            let delegate_pubkey = pubkey_of(arg_matches, "delegate").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+            let amount: u64 = value_of(arg_matches, "amount").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            command_undelegate(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                delegate_pubkey,
+                mint_pubkey,
+                realm_pubkey,
+                amount,
+                compute_unit_price,
+            )
+        }
         ("split", Some(arg_matches)) => {
             let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
             let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
@@ -1326,6 +3828,7 @@ fn main() {
             let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
             let new_vesting_owner_pubkey = pubkey_of(arg_matches, "new_vesting_owner").unwrap();
             let schedules = parse_schedules(arg_matches);
+            let linear_schedule = parse_linear_schedule(arg_matches);
 
             command_split(
                 rpc_client,
@@ -1336,6 +3839,156 @@ fn main() {
                 vesting_token_pubkey,
                 new_vesting_owner_pubkey,
                 schedules,
+                linear_schedule,
+                compute_unit_price,
+            )
+        }
+        ("merge", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let vesting_owner_signer = get_signer(arg_matches, "vesting_owner", &mut wallet_manager)
+                .expect("Need to specify `vesting_owner`");
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*vesting_owner_signer);
+
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let source_vesting_token_pubkey = pubkey_of(arg_matches, "source_vesting_address").unwrap();
+
+            command_merge(
+                rpc_client,
+                vesting_addin_program_id,
+                payer,
+                &*vesting_owner_signer,
+                vesting_token_pubkey,
+                source_vesting_token_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("set-lockup-config", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let realm_authority = get_signer(arg_matches, "realm_authority", &mut wallet_manager)
+                .expect("Need to specify `realm_authority`");
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+
+            let lockup = if arg_matches.is_present("disable") {
+                None
+            } else {
+                Some(LockupConfig {
+                    baseline_factor: value_of(arg_matches, "baseline_factor").unwrap(),
+                    max_extra_factor: value_of(arg_matches, "max_extra_factor").unwrap(),
+                    saturation_secs: value_of(arg_matches, "saturation_secs").unwrap(),
+                })
+            };
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*realm_authority);
+
+            command_set_lockup_config(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*realm_authority,
+                mint_pubkey,
+                realm_pubkey,
+                lockup,
+                compute_unit_price,
+            )
+        }
+        ("set-vote-percentage-policy", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let realm_authority = get_signer(arg_matches, "realm_authority", &mut wallet_manager)
+                .expect("Need to specify `realm_authority`");
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+
+            let policy = if arg_matches.is_present("disable") {
+                None
+            } else {
+                Some(VotePercentagePolicy {
+                    default_vote_percentage: value_of(arg_matches, "default_percentage").unwrap(),
+                    max_vote_percentage: value_of(arg_matches, "max_percentage").unwrap(),
+                    allow_partial_voting: !arg_matches.is_present("disable_partial_voting"),
+                })
+            };
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*realm_authority);
+
+            command_set_vote_percentage_policy(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*realm_authority,
+                mint_pubkey,
+                realm_pubkey,
+                policy,
+                compute_unit_price,
+            )
+        }
+        ("set-freeze-authority", Some(arg_matches)) => {
+            let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+            let realm_authority = get_signer(arg_matches, "realm_authority", &mut wallet_manager)
+                .expect("Need to specify `realm_authority`");
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+            let freeze_authority = pubkey_of(arg_matches, "freeze_authority");
+
+            let payer_keypair = keypair_of(arg_matches, "payer");
+            let payer = payer_keypair
+                .as_ref()
+                .map(|v| v as &dyn Signer)
+                .unwrap_or(&*realm_authority);
+
+            command_set_freeze_authority(
+                rpc_client,
+                governance_program_id,
+                vesting_addin_program_id,
+                payer,
+                &*realm_authority,
+                mint_pubkey,
+                realm_pubkey,
+                freeze_authority,
+                compute_unit_price,
+            )
+        }
+        ("recalculate-voter-weight", Some(arg_matches)) => {
+            let vesting_owner_pubkey = pubkey_of(arg_matches, "vesting_owner").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+
+            let payer_keypair = keypair_of(arg_matches, "payer").unwrap();
+
+            command_recalculate_voter_weight(
+                rpc_client,
+                vesting_addin_program_id,
+                &payer_keypair,
+                vesting_owner_pubkey,
+                mint_pubkey,
+                realm_pubkey,
+                compute_unit_price,
+            )
+        }
+        ("migrate-vesting-record", Some(arg_matches)) => {
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+            let payer_keypair = keypair_of(arg_matches, "payer").unwrap();
+
+            command_migrate_vesting_record(
+                rpc_client,
+                vesting_addin_program_id,
+                &payer_keypair,
+                vesting_token_pubkey,
                 compute_unit_price,
             )
         }
@@ -1346,43 +3999,67 @@ fn main() {
         ("info-owner", Some(arg_matches)) => {
             let vesting_owner_pubkey = pubkey_of(arg_matches, "vesting_owner").unwrap();
 
-            let records: Vec<(Pubkey,Account)> =
-                rpc_client.get_program_accounts_with_config(
-                    &vesting_addin_program_id,
-                    RpcProgramAccountsConfig {
-                        filters: Some(vec![
-                            rpc_filter::RpcFilterType::Memcmp(
-                                #[allow(deprecated)]
-                                rpc_filter::Memcmp {
-                                    offset: 0,
-                                    bytes: rpc_filter::MemcmpEncodedBytes::Bytes({
-                                        let mut fd: Vec<u8> = vec![1];
-                                        fd.append(&mut vesting_owner_pubkey.to_bytes().to_vec());
-                                        fd
-                                    }),
-                                    encoding: None,
-                                },
-                            )
-                        ]),
-                        account_config: RpcAccountInfoConfig {
-                            encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
-                            data_slice: None,
-                            commitment: None,
-                            min_context_slot: None,
-                        },
-                        with_context: Some(false),
-                    }
-                ).unwrap();
-            
-            for (vesting_account_pubkey, vesting_account) in records {
-                let vesting_record: VestingRecord = try_from_slice_unchecked(&vesting_account.data).unwrap();
+            let records = get_vesting_records_by_owner(&rpc_client, &vesting_addin_program_id, &vesting_owner_pubkey);
+            for (vesting_account_pubkey, vesting_record) in records {
                 msg!("\nVesting Account Pubkey: {:?}", &vesting_account_pubkey);
-                report_vesting_record_info(&vesting_record);
+                report_vesting_record_info(&rpc_client, &vesting_record);
             }
         }
         ("list", Some(_)) => {
             command_list(rpc_client, vesting_addin_program_id)
         }
+        ("audit", Some(arg_matches)) => {
+            let payer = keypair_of(arg_matches, "payer").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+
+            command_audit(
+                rpc_client,
+                vesting_addin_program_id,
+                &payer,
+                realm_pubkey,
+                mint_pubkey,
+            )
+        }
+        ("get-withdrawable-amount", Some(arg_matches)) => {
+            let payer = keypair_of(arg_matches, "payer").unwrap();
+            let vesting_token_pubkey = pubkey_of(arg_matches, "vesting_address").unwrap();
+
+            command_get_withdrawable_amount(
+                rpc_client,
+                vesting_addin_program_id,
+                &payer,
+                vesting_token_pubkey,
+            )
+        }
+        ("get-vesting-summary", Some(arg_matches)) => {
+            let payer = keypair_of(arg_matches, "payer").unwrap();
+            let vesting_owner_pubkey = pubkey_of(arg_matches, "vesting_owner").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+
+            command_get_vesting_summary(
+                rpc_client,
+                vesting_addin_program_id,
+                &payer,
+                vesting_owner_pubkey,
+                mint_pubkey,
+            )
+        }
+        ("get-voter-weight", Some(arg_matches)) => {
+            let payer = keypair_of(arg_matches, "payer").unwrap();
+            let vesting_owner_pubkey = pubkey_of(arg_matches, "vesting_owner").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_address").unwrap();
+            let realm_pubkey = pubkey_of(arg_matches, "realm_address").unwrap();
+
+            command_get_voter_weight(
+                rpc_client,
+                vesting_addin_program_id,
+                &payer,
+                vesting_owner_pubkey,
+                mint_pubkey,
+                realm_pubkey,
+            )
+        }
         _ => unreachable!(),
     };
 }
@@ -71,6 +71,78 @@ pub enum VestingError {
 
     #[error("Invalid schedule")]
     InvalidSchedule,
+
+    #[error("Vesting is not revocable")]
+    VestingIsNotRevocable,
+
+    #[error("Invalid revoke authority")]
+    InvalidRevokeAuthority,
+
+    #[error("Vestings can't be merged")]
+    IncompatibleVestings,
+
+    #[error("Invalid proposed owner")]
+    InvalidProposedOwner,
+
+    #[error("Invalid lockup config")]
+    InvalidLockupConfig,
+
+    #[error("Invalid VestingRealmConfig account address")]
+    InvalidRealmConfigAccountAddress,
+
+    #[error("Invalid realm authority")]
+    InvalidRealmAuthority,
+
+    #[error("Vesting accounts don't match VoterWeightRecord")]
+    VestingAccountsMismatch,
+
+    #[error("Invalid mint account")]
+    InvalidMintAccount,
+
+    #[error("Mint extension is not supported")]
+    UnsupportedMintExtension,
+
+    #[error("Vesting record is already migrated")]
+    VestingRecordAlreadyMigrated,
+
+    #[error("Vesting is already under realm")]
+    VestingIsUnderRealm,
+
+    #[error("Vesting has nothing to compact")]
+    NothingToCompact,
+
+    #[error("Vesting is frozen")]
+    VestingFrozen,
+
+    #[error("Invalid freeze authority")]
+    InvalidFreezeAuthority,
+
+    #[error("Vesting record is not migrated")]
+    VestingRecordNotMigrated,
+
+    #[error("Amount is delegated")]
+    AmountIsDelegated,
+
+    #[error("Invalid delegate")]
+    InvalidDelegate,
+
+    #[error("Crank withdraw is not allowed")]
+    CrankWithdrawNotAllowed,
+
+    #[error("Invalid destination token account")]
+    InvalidDestinationAccount,
+
+    #[error("Vote percentage is not allowed by the Realm policy")]
+    VotePercentageNotAllowed,
+
+    #[error("Voter Weight Record is not upgraded")]
+    VoterWeightRecordNotUpgraded,
+
+    #[error("Voter Weight Records are not sorted")]
+    VoterWeightRecordsNotSorted,
+
+    #[error("Token account extension is not supported")]
+    UnsupportedTokenAccountExtension,
 }
 
 impl From<VestingError> for ProgramError {
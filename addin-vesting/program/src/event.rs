@@ -0,0 +1,306 @@
+use crate::{
+    realm_config::VotePercentagePolicy,
+    state::LockupConfig,
+};
+use solana_program::{
+    log::sol_log_data,
+    pubkey::Pubkey,
+};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use std::str::FromStr;
+
+/// Tag logged before every event to distinguish it from other data logged by the program
+pub const VESTING_EVENT_TAG: &[u8] = b"vesting-event";
+
+/// Event emitted on every state change of the vesting, the VoterWeightRecord or the VestingRealmConfig.
+/// The event is logged with `sol_log_data` as [VESTING_EVENT_TAG, borsh-serialized event]
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub enum VestingEvent {
+    /// Tokens were deposited to the vesting (Deposit and AddSchedules instructions)
+    Deposited {
+        vesting: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weight of the owner (None for vesting without Realm or if the weight is not changed)
+        voter_weight: Option<u64>,
+    },
+
+    /// Tokens were withdrawn from the vesting
+    Withdrawn {
+        vesting: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weight of the owner (None for vesting without Realm)
+        voter_weight: Option<u64>,
+    },
+
+    /// Vesting was transferred to the new owner
+    OwnerChanged {
+        vesting: Pubkey,
+        old_owner: Pubkey,
+        new_owner: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weights of the old and new owners (None for vesting without Realm)
+        old_owner_voter_weight: Option<u64>,
+        new_owner_voter_weight: Option<u64>,
+    },
+
+    /// Part of the vesting was moved to the new vesting
+    Split {
+        vesting: Pubkey,
+        new_vesting: Pubkey,
+        owner: Pubkey,
+        new_owner: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weights of the owners (None for vesting without Realm)
+        owner_voter_weight: Option<u64>,
+        new_owner_voter_weight: Option<u64>,
+    },
+
+    /// Vesting account was closed
+    Closed {
+        vesting: Pubkey,
+        owner: Pubkey,
+    },
+
+    /// Vote percentage of the VoterWeightRecord was changed
+    VotePercentageSet {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+        vote_percentage: u16,
+        voter_weight: u64,
+    },
+
+    /// Voter weight of the VoterWeightRecord was recalculated
+    VoterWeightChanged {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+        voter_weight: u64,
+    },
+
+    /// Vesting was frozen or unfrozen by the freeze authority of the Realm
+    FrozenSet {
+        vesting: Pubkey,
+        owner: Pubkey,
+        frozen: bool,
+    },
+
+    /// Manager of the vesting was set or cleared
+    ManagerSet {
+        vesting: Pubkey,
+        owner: Pubkey,
+        manager: Option<Pubkey>,
+    },
+
+    /// Voting weight of the owner's tokens was delegated
+    Delegated {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+        delegate: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weights of the owner and the delegate
+        owner_voter_weight: u64,
+        delegate_voter_weight: u64,
+    },
+
+    /// Delegated voting weight was returned to the owner
+    Undelegated {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+        delegate: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weights of the owner and the delegate
+        owner_voter_weight: u64,
+        delegate_voter_weight: u64,
+    },
+
+    /// Release times of the vesting later than `from_time` were moved earlier by `shift`
+    ScheduleAccelerated {
+        vesting: Pubkey,
+        owner: Pubkey,
+        from_time: u64,
+        shift: u64,
+
+        /// Resulting voter weight of the owner (None if the Realm has no lockup multiplier)
+        voter_weight: Option<u64>,
+    },
+
+    /// Unreleased tokens were revoked from the vesting by the revoke authority
+    Revoked {
+        vesting: Pubkey,
+        owner: Pubkey,
+        revoke_authority: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+
+        /// Resulting voter weight of the owner (None for vesting without Realm)
+        voter_weight: Option<u64>,
+    },
+
+    /// New owner of the vesting was proposed (or the proposal was cancelled)
+    OwnerProposed {
+        vesting: Pubkey,
+        owner: Pubkey,
+        proposed_owner: Option<Pubkey>,
+    },
+
+    /// Legacy vesting record was migrated to the current layout
+    Migrated {
+        vesting: Pubkey,
+        owner: Pubkey,
+    },
+
+    /// Released and withdrawn schedule entries were removed from the vesting record
+    Compacted {
+        vesting: Pubkey,
+        owner: Pubkey,
+    },
+
+    /// Revoke authority of the vesting was changed (or removed)
+    RevokeAuthoritySet {
+        vesting: Pubkey,
+        owner: Pubkey,
+        revoke_authority: Option<Pubkey>,
+    },
+
+    /// Withdrawals of released tokens by anyone were allowed or disallowed
+    CrankWithdrawSet {
+        vesting: Pubkey,
+        owner: Pubkey,
+        allow: bool,
+    },
+
+    /// VoterWeightRecord was created (explicitly or by the first deposit of the owner)
+    VoterWeightRecordCreated {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+        vote_percentage: u16,
+    },
+
+    /// Legacy VoterWeightRecord was upgraded to the layout with delegation and lockup fields
+    VoterWeightRecordUpgraded {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+    },
+
+    /// VoterWeightRecord was closed
+    VoterWeightRecordClosed {
+        realm: Pubkey,
+        mint: Pubkey,
+        owner: Pubkey,
+    },
+
+    /// VestingRealmConfig was created or changed (SetLockupConfig, SetVotePercentagePolicy
+    /// and SetFreezeAuthority instructions). Contains the resulting settings
+    RealmConfigSet {
+        realm: Pubkey,
+        mint: Pubkey,
+        lockup: Option<LockupConfig>,
+        freeze_authority: Option<Pubkey>,
+        vote_percentage_policy: Option<VotePercentagePolicy>,
+    },
+
+    /// Source vesting was merged into the target vesting (the source vesting is closed)
+    Merged {
+        source: Pubkey,
+        target: Pubkey,
+        amount: u64,
+    },
+}
+
+impl VestingEvent {
+    /// Logs the event
+    pub fn emit(&self) {
+        sol_log_data(&[VESTING_EVENT_TAG, &self.try_to_vec().unwrap()]);
+    }
+
+    /// Decodes the event from fields logged with `sol_log_data`
+    pub fn decode(fields: &[&[u8]]) -> Option<Self> {
+        match fields {
+            [tag, data] if *tag == VESTING_EVENT_TAG => Self::try_from_slice(data).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Parses events emitted by the vesting program from the transaction logs.
+/// Data logged by other programs (including ones invoked by the vesting program) is skipped
+pub fn parse_vesting_events(program_id: &Pubkey, logs: &[String]) -> Vec<VestingEvent> {
+    let mut invoke_stack: Vec<Option<Pubkey>> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let words = log.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["Program", program, "invoke", _] => invoke_stack.push(Pubkey::from_str(program).ok()),
+            ["Program", _, "success"] => { invoke_stack.pop(); }
+            ["Program", _, "failed:", ..] => { invoke_stack.pop(); }
+            ["Program", "data:", data @ ..] if invoke_stack.last() == Some(&Some(*program_id)) => {
+                let fields = data.iter()
+                        .map(|field| base64::decode(field))
+                        .collect::<Result<Vec<_>, _>>();
+                if let Ok(fields) = fields {
+                    let fields = fields.iter().map(|field| field.as_slice()).collect::<Vec<_>>();
+                    events.extend(VestingEvent::decode(&fields));
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vesting_events() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let event = VestingEvent::Withdrawn {
+            vesting: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 1000,
+            voter_weight: Some(500),
+        };
+        let data_log = format!("Program data: {} {}",
+                base64::encode(VESTING_EVENT_TAG),
+                base64::encode(event.try_to_vec().unwrap()));
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("Program {} invoke [2]", other_program_id),
+            data_log.clone(),
+            format!("Program {} success", other_program_id),
+            data_log,
+            "Program data: AQID".to_string(),
+            format!("Program {} success", program_id),
+        ];
+        assert_eq!(parse_vesting_events(&program_id, &logs), vec![event]);
+    }
+
+    #[test]
+    fn test_decode_merged_event() {
+        let event = VestingEvent::Merged {
+            source: Pubkey::new_unique(),
+            target: Pubkey::new_unique(),
+            amount: 1000,
+        };
+        let data = event.try_to_vec().unwrap();
+        assert_eq!(VestingEvent::decode(&[VESTING_EVENT_TAG, &data]), Some(event));
+        assert_eq!(VestingEvent::decode(&[&b"other-tag"[..], &data]), None);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    state::VestingSchedule,
-    voter_weight::get_voter_weight_record_address,
+    state::{LinearVestingSchedule, LockupConfig, VestingSchedule, VestingTimeBase},
+    voter_weight::{get_voter_weight_record_address, VoterWeightAction},
     max_voter_weight::get_max_voter_weight_record_address,
+    realm_config::{get_realm_config_address, VotePercentagePolicy},
 };
 
 use solana_program::{
@@ -13,6 +14,7 @@ use solana_program::{
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use spl_governance::state::token_owner_record::get_token_owner_record_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
@@ -24,7 +26,7 @@ pub enum VestingInstruction {
     ///
     ///   * Single owner
     ///   0. `[]` The system program account
-    ///   1. `[]` The spl-token program account
+    ///   1. `[]` The spl-token (or spl-token-2022) program account
     ///   2. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
     ///   3. `[writable]` The vesting spl-token account
     ///   4. `[signer]` The source spl-token account owner
@@ -36,6 +38,10 @@ pub enum VestingInstruction {
     ///   8. `[]` The Realm account
     ///   9. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
     ///  10. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///  11. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
     ///
     Deposit {
         #[allow(dead_code)]
@@ -47,7 +53,7 @@ pub enum VestingInstruction {
     /// Accounts expected by this instruction:
     ///
     ///   * Single owner
-    ///   0. `[]` The spl-token program account
+    ///   0. `[]` The spl-token (or spl-token-2022) program account
     ///   1. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
     ///   2. `[writable]` The vesting spl-token account
     ///   3. `[writable]` The destination spl-token account
@@ -59,6 +65,11 @@ pub enum VestingInstruction {
     ///   7. `[]` Governing Owner Record. PDA seeds (governance program): ['governance', realm, token_mint, vesting_owner]
     ///   8. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, vesting_owner]
     ///   9. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///  10. `[]` The VestingRealmConfig (optional, without it removed tokens are weighted with the ratio of the VoterWeightRecord).
+    ///       PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
     ///
     Withdraw,
 
@@ -75,6 +86,7 @@ pub enum VestingInstruction {
     ///   4. `[]` The Realm account
     ///   5. `[]` Governing Owner Record. PDA seeds (governance program): ['governance', realm, token_mint, vesting_owner]
     ///   6. `[writable]` The VoterWeight Record. PDA seeds: ['voter_weight', realm, token_mint, vesting_owner]
+    ///   7. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
     SetVotePercentage {
         #[allow(dead_code)]
         vote_percentage: u16,
@@ -83,12 +95,13 @@ pub enum VestingInstruction {
 
     /// Change the destination account of a given simple vesting contract (SVC)
     /// - can only be invoked by the present destination address of the contract.
+    /// The vesting manager and the crank withdraw permission of the previous owner are removed.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   * Single owner
     ///   0. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
-    ///   1. `[signer]` The Current Vesting Owner account
+    ///   1. `[signer]` The Current Vesting Owner account (or the Vesting Manager account)
     ///   2. `[]` The New Vesting Owner account
     ///
     ///  Optional part (vesting for Realm)
@@ -97,10 +110,18 @@ pub enum VestingInstruction {
     ///   5. `[]` Governing Owner Record. PDA seeds (governance program): ['governance', realm, token_mint, current_vesting_owner]
     ///   6. `[writable]` The from VoterWeight Record. PDA seeds: ['voter_weight', realm, token_mint, current_vesting_owner]
     ///   7. `[writable]` The to VoterWeight Record. PDA seeds: ['voter_weight', realm, token_mint, new_vesting_owner]
+    ///   8. `[]` The VestingRealmConfig (optional, without it moved tokens are weighted with the ratio of the VoterWeightRecord)
+    ///       (required with the delegate's accounts). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   9. `[writable]` The VoterWeightRecord of the delegate (optional, required if delegated tokens are removed from the owner).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///  10. `[]` The TokenOwnerRecord of the delegate (optional, required with the delegate's VoterWeightRecord,
+    ///       the delegate must not have active votes).
+    ///       PDA seeds (governance program): ['governance', realm, token_mint, delegate]
     ChangeOwner,
 
 
-    /// Create VoterWeightRecord for account
+    /// Create VoterWeightRecord for account.
+    /// Existing record with the legacy layout is upgraded to the current layout (required for lockup weighting)
     ///
     /// Accounts expected by this instruction:
     ///
@@ -111,17 +132,27 @@ pub enum VestingInstruction {
     ///   3. `[]` The Realm account
     ///   4. `[]` The Mint account
     ///   5. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   6. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
     CreateVoterWeightRecord,
 
 
     /// Close vesting account
+    /// With the optional realm accounts the owner's VoterWeightRecord is closed too when it becomes empty
+    /// (no tokens are delegated to or by the owner) and the owner has no outstanding votes in the Realm
+    /// (otherwise it's left untouched)
     ///
     /// Accounts expected by this instruction:
-    ///   0. `[]` The spl-token program account
+    ///   0. `[]` The spl-token (or spl-token-2022) program account
     ///   1. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
     ///   2. `[writable]` The vesting spl-token account
     ///   3. `[signer]` The vesting Owner account
     ///   4. `[writable]` Spill account
+    ///
+    ///  Optional part (vesting for Realm)
+    ///   5. `[]` The Governance program account
+    ///   6. `[]` The Realm account
+    ///   7. `[]` Governing Owner Record. PDA seeds (governance program): ['governance', realm, token_mint, vesting_owner]
+    ///   8. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, vesting_owner]
     Close,
 
 
@@ -143,10 +174,10 @@ pub enum VestingInstruction {
     ///
     ///   * Single owner
     ///   0. `[]` The system program account
-    ///   1. `[]` The spl-token program account
+    ///   1. `[]` The spl-token (or spl-token-2022) program account
     ///   2. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
     ///   3. `[writable]` The vesting spl-token account
-    ///   4. `[signer]` The vesting Owner account
+    ///   4. `[signer]` The vesting Owner account (or the Vesting Manager account)
     ///   5. `[writable]` The new vesting account. PDA seeds: [new vesting spl-token account]
     ///   6. `[]` The new vesting spl-token account
     ///   7. `[]` The new Vesting Owner account
@@ -158,107 +189,2062 @@ pub enum VestingInstruction {
     ///  11. `[]` Governing Owner Record. PDA seed (governance program): ['governance', realm, token_mint, vesting_owner]
     ///  12. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
     ///  13. `[writable]` The new VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, new_token_owner]
+    ///  14. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///  15. `[writable]` The VoterWeightRecord of the delegate (optional, required if delegated tokens are removed from the owner).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///  16. `[]` The TokenOwnerRecord of the delegate (optional, required with the delegate's VoterWeightRecord,
+    ///       the delegate must not have active votes).
+    ///       PDA seeds (governance program): ['governance', realm, token_mint, delegate]
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
     ///
     Split {
         #[allow(dead_code)]
         schedules: Vec<VestingSchedule>,
     },
 
-}
 
-/// Creates a `Deposit` instruction to create and initialize the vesting token account
-#[allow(clippy::too_many_arguments)]
-pub fn deposit(
-    program_id: &Pubkey,
-    token_program_id: &Pubkey,
-    vesting_token_account: &Pubkey,
-    source_token_owner: &Pubkey,
-    source_token_account: &Pubkey,
-    vesting_owner: &Pubkey,
-    payer: &Pubkey,
-    schedules: Vec<VestingSchedule>,
-) -> Result<Instruction, ProgramError> {
-    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
-    let accounts = vec![
-        AccountMeta::new_readonly(system_program::id(), false),
-        AccountMeta::new_readonly(*token_program_id, false),
-        AccountMeta::new(vesting_account, false),
-        AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new_readonly(*source_token_owner, true),
-        AccountMeta::new(*source_token_account, false),
-        AccountMeta::new_readonly(*vesting_owner, false),
-        AccountMeta::new_readonly(*payer, true),
-    ];
+    /// Creates a new vesting contract with continuous (linear) release schedule
+    ///
+    /// Accounts expected by this instruction are the same as for `Deposit` instruction
+    DepositLinear {
+        #[allow(dead_code)]
+        schedule: LinearVestingSchedule,
+    },
 
-    let instruction = VestingInstruction::Deposit { schedules };
 
-    Ok(Instruction {
-        program_id: *program_id,
-        accounts,
-        data: instruction.try_to_vec().unwrap(),
-    })
-}
+    /// Split linear Vesting into two parts
+    /// The new vesting can't start, reach the cliff or end earlier then the source vesting.
+    /// `schedule.amount` tokens of the source schedule are moved together with the same share of already withdrawn
+    /// amount, so only their not withdrawn part is transferred to the new vesting.
+    ///
+    /// Accounts expected by this instruction are the same as for `Split` instruction
+    SplitLinear {
+        #[allow(dead_code)]
+        schedule: LinearVestingSchedule,
+    },
 
-/// Creates a `Deposit` instruction to create and initialize the vesting token account
-/// inside the Realm
-#[allow(clippy::too_many_arguments)]
-pub fn deposit_with_realm(
-    program_id: &Pubkey,
-    token_program_id: &Pubkey,
-    vesting_token_account: &Pubkey,
-    source_token_owner: &Pubkey,
-    source_token_account: &Pubkey,
-    vesting_owner: &Pubkey,
-    payer: &Pubkey,
-    schedules: Vec<VestingSchedule>,
-    realm: &Pubkey,
-    mint: &Pubkey,
-) -> Result<Instruction, ProgramError> {
-    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
-    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
-    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
-    let accounts = vec![
-        AccountMeta::new_readonly(system_program::id(), false),
-        AccountMeta::new_readonly(*token_program_id, false),
-        AccountMeta::new(vesting_account, false),
-        AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new_readonly(*source_token_owner, true),
-        AccountMeta::new(*source_token_account, false),
-        AccountMeta::new_readonly(*vesting_owner, false),
-        AccountMeta::new(*payer, true),
 
-        AccountMeta::new_readonly(*realm, false),
-        AccountMeta::new(voting_weight_record_account, false),
-        AccountMeta::new(max_voting_weight_record_account, false),
-    ];
+    /// Creates a new vesting contract which unreleased tokens can be revoked by the revoke authority
+    ///
+    /// Accounts expected by this instruction are the same as for `Deposit` instruction
+    DepositRevocable {
+        #[allow(dead_code)]
+        schedules: Vec<VestingSchedule>,
+        #[allow(dead_code)]
+        linear_schedule: Option<LinearVestingSchedule>,
+        #[allow(dead_code)]
+        revoke_authority: Pubkey,
+    },
 
-    let instruction = VestingInstruction::Deposit { schedules };
 
-    Ok(Instruction {
-        program_id: *program_id,
-        accounts,
-        data: instruction.try_to_vec().unwrap(),
-    })
-}
+    /// Revoke all unreleased tokens from the vesting to the destination account.
+    /// Released tokens stay in the vesting and can be withdrawn by the vesting owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[]` The spl-token (or spl-token-2022) program account
+    ///   1. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
+    ///   2. `[writable]` The vesting spl-token account
+    ///   3. `[writable]` The destination spl-token account
+    ///   4. `[signer]` The Revoke Authority account
+    ///
+    ///  Optional part (vesting for Realm)
+    ///   5. `[]` The Governance program account
+    ///   6. `[]` The Realm account
+    ///   7. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, vesting_owner]
+    ///   8. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   9. `[]` The VestingRealmConfig (the Realm has no lockup config if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///  10. `[writable]` The VoterWeightRecord of the delegate (optional, required if delegated tokens are removed from the owner).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
+    ///
+    Revoke,
 
-/// Creates a `Withdraw` instruction
-pub fn withdraw(
-    program_id: &Pubkey,
-    token_program_id: &Pubkey,
-    vesting_token_account: &Pubkey,
-    destination_token_account: &Pubkey,
-    vesting_owner: &Pubkey,
-) -> Result<Instruction, ProgramError> {
-    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
-    let accounts = vec![
-        AccountMeta::new_readonly(*token_program_id, false),
-        AccountMeta::new(vesting_account, false),
-        AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new(*destination_token_account, false),
-        AccountMeta::new_readonly(*vesting_owner, true),
+
+    /// Change or remove the revoke authority of the vesting
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[signer]` The current Revoke Authority account
+    SetRevokeAuthority {
+        #[allow(dead_code)]
+        revoke_authority: Option<Pubkey>,
+    },
+
+
+    /// Add schedules to the existing vesting and transfer their tokens to the vesting spl-token account.
+    /// New schedules are merged with the existing ones by the release time.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[]` The system program account
+    ///   1. `[]` The spl-token (or spl-token-2022) program account
+    ///   2. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[signer]` The source spl-token account owner
+    ///   5. `[writable]` The source spl-token account
+    ///   6. `[writable,signer]` Payer
+    ///
+    ///  Optional part (vesting for Realm)
+    ///   7. `[]` The Realm account
+    ///   8. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, vesting_owner]
+    ///   9. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///  10. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
+    ///
+    AddSchedules {
+        #[allow(dead_code)]
+        schedules: Vec<VestingSchedule>,
+    },
+
+
+    /// Withdraw the specified amount of tokens released by the vesting.
+    /// Tokens are taken from the earliest matured schedules first.
+    ///
+    /// Accounts expected by this instruction are the same as for `Withdraw` instruction
+    WithdrawAmount {
+        #[allow(dead_code)]
+        amount: u64,
+    },
+
+
+    /// Merge the source vesting into the vesting with the same owner, mint and realm.
+    /// Tokens are moved to the vesting spl-token account, the source vesting account
+    /// and the source vesting spl-token account are closed to the spill account.
+    /// Legacy vesting record must be migrated with MigrateVestingRecord instruction first if the source has linear schedule.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The spl-token (or spl-token-2022) program account
+    ///   2. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[writable]` The source vesting account. PDA seeds: [source vesting spl-token account]
+    ///   5. `[writable]` The source vesting spl-token account
+    ///   6. `[signer]` The Vesting Owner account
+    ///   7. `[writable]` Spill account
+    ///   8. `[writable,signer]` Payer
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
+    Merge,
+
+
+    /// Propose the new owner of the vesting. The ownership (and voting weight) is transferred
+    /// only when the proposed owner accepts it with `AcceptOwner` instruction.
+    /// Legacy vesting records must be migrated with MigrateVestingRecord instruction first.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   2. `[signer]` The Vesting Owner account
+    ///   3. `[]` The Proposed Vesting Owner account
+    ///   4. `[writable,signer]` Payer
+    ProposeOwner,
+
+
+    /// Accept the ownership of the vesting proposed with `ProposeOwner` instruction
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[]` The Current Vesting Owner account
+    ///   2. `[signer]` The Proposed Vesting Owner account
+    ///
+    ///  Optional part (vesting for Realm)
+    ///   3. `[]` The Governance program account
+    ///   4. `[]` The Realm account
+    ///   5. `[]` Governing Owner Record. PDA seeds (governance program): ['governance', realm, token_mint, current_vesting_owner]
+    ///   6. `[writable]` The from VoterWeight Record. PDA seeds: ['voter_weight', realm, token_mint, current_vesting_owner]
+    ///   7. `[writable]` The to VoterWeight Record. PDA seeds: ['voter_weight', realm, token_mint, proposed_vesting_owner]
+    ///   8. `[]` The VestingRealmConfig (optional, without it moved tokens are weighted with the ratio of the VoterWeightRecord)
+    ///       (required with the delegate's accounts). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   9. `[writable]` The VoterWeightRecord of the delegate (optional, required if delegated tokens are removed from the owner).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///  10. `[]` The TokenOwnerRecord of the delegate (optional, required with the delegate's VoterWeightRecord,
+    ///       the delegate must not have active votes).
+    ///       PDA seeds (governance program): ['governance', realm, token_mint, delegate]
+    AcceptOwner,
+
+
+    /// Cancel the ownership proposal of the vesting
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[signer]` The Vesting Owner account
+    CancelOwnerProposal,
+
+
+    /// Set (or remove) lockup-duration voting multiplier for the Realm.
+    /// Creates VestingRealmConfig account if it doesn't exist.
+    /// Existing VoterWeightRecords keep their weight until they are recalculated
+    /// (governance must refresh them with `UpdateVoterWeightRecord` before voting).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The Governance program account
+    ///   2. `[]` The Realm account
+    ///   3. `[]` The Mint account
+    ///   4. `[signer]` The Realm authority
+    ///   5. `[writable]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   6. `[writable,signer]` Payer
+    SetLockupConfig {
+        #[allow(dead_code)]
+        lockup: Option<LockupConfig>,
+    },
+
+
+    /// Recalculate voter weight of the VoterWeightRecord using lockup-duration multiplier of the Realm.
+    /// Other instructions add or remove tokens with the weight of their lockup at the time of the instruction,
+    /// but the weight of the tokens left in the record is not changed until it's recalculated.
+    /// Weight of the locked tokens decreases over time, so the stored weight goes stale as lockups age:
+    /// governance must refresh the record before voting (see `UpdateVoterWeightRecord`).
+    /// MaxVoterWeightRecord is updated by the change of the weighted amount of the record.
+    /// The weight delegated by the owner is set to the recalculated share of delegated tokens.
+    /// Legacy VoterWeightRecords must be upgraded with `CreateVoterWeightRecord` instruction to be weighted.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   1. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   2. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   3. `[writable]` The VoterWeightRecord of the delegate (only if the owner delegated tokens).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///   3.. (4.. with the delegate) `[]` All vesting accounts of the token owner in the Realm
+    RecalculateVoterWeight,
+
+
+    /// Recalculate voter weight of the VoterWeightRecord (like `RecalculateVoterWeight`)
+    /// and make it valid for the specified action in the current slot only.
+    /// The instruction must be executed in the same transaction before the governance action,
+    /// so the action always uses the weight of the current lockups.
+    /// Unlike `RecalculateVoterWeight` it must be signed by the token owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   1. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   2. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   3. `[signer]` The token owner account
+    ///   4. `[writable]` The VoterWeightRecord of the delegate (only if the owner delegated tokens).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///   4.. (5.. with the delegate) `[]` All vesting accounts of the token owner in the Realm
+    UpdateVoterWeightRecord {
+        #[allow(dead_code)]
+        action: VoterWeightAction,
+
+        #[allow(dead_code)]
+        target: Option<Pubkey>,
+    },
+
+
+    /// Migrate the vesting account to VestingRecordV2 layout (with creation metadata).
+    /// The account is reallocated and rent is topped up from the payer. Can be executed by anyone.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   2. `[writable,signer]` Payer
+    MigrateVestingRecord,
+
+
+    /// Attach the vesting to the Realm: locked tokens are added to the voter weight of the owner
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   2. `[signer]` The Vesting Owner account
+    ///   3. `[]` The Governance program account
+    ///   4. `[]` The Realm account
+    ///   5. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   6. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   7. `[writable,signer]` Payer
+    ///   8. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    AttachRealm,
+
+
+    /// Detach the vesting from the Realm: locked tokens are removed from the voter weight of the owner.
+    /// The owner should not have active votes in the Realm
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[signer]` The Vesting Owner account
+    ///   2. `[]` The Governance program account
+    ///   3. `[]` The Realm account
+    ///   4. `[]` Governing Owner Record. PDA seeds (governance program): ['governance', realm, token_mint, vesting_owner]
+    ///   5. `[writable]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   6. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   7. `[]` The VestingRealmConfig (the Realm has no lockup config if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    DetachRealm,
+
+
+    /// Move the vesting to another Realm: locked tokens are moved from the voter weight of the owner
+    /// in the current Realm to the voter weight in the new Realm.
+    /// The owner should not have active votes in the current Realm
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   2. `[signer]` The Vesting Owner account
+    ///   3. `[]` The Governance program account of the current Realm
+    ///   4. `[]` The current Realm account
+    ///   5. `[]` Governing Owner Record in the current Realm. PDA seeds (governance program): ['governance', realm, token_mint, vesting_owner]
+    ///   6. `[writable]` The VoterWeightRecord in the current Realm. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   7. `[writable]` The MaxVoterWeightRecord of the current Realm. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   8. `[]` The Governance program account of the new Realm
+    ///   9. `[]` The new Realm account
+    ///  10. `[writable]` The VoterWeightRecord in the new Realm. PDA seeds: ['voter_weight', new_realm, token_mint, token_owner]
+    ///  11. `[writable]` The MaxVoterWeightRecord of the new Realm. PDA seeds: ['max_voter_weight', new_realm, token_mint]
+    ///  12. `[writable,signer]` Payer
+    ///  13. `[]` The VestingRealmConfig of the new Realm (the new Realm has no vote percentage policy if the account doesn't exist).
+    ///       PDA seeds: ['vesting-realm-config', new_realm, token_mint]
+    ///  14. `[]` The VestingRealmConfig of the current Realm (the current Realm has no lockup config if the account doesn't exist).
+    ///       PDA seeds: ['vesting-realm-config', realm, token_mint]
+    MoveRealm,
+
+
+    /// Drop fully withdrawn schedules from the vesting, shrink the account
+    /// and return the released rent lamports to the receiver
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[signer]` The Vesting Owner account
+    ///   2. `[writable]` The rent receiver account
+    Compact,
+
+
+    /// Creates several vesting contracts funded from one source spl-token account.
+    /// Each entry of `schedules` corresponds to one vesting
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The spl-token (or spl-token-2022) program account
+    ///   2. `[signer]` The source spl-token account owner
+    ///   3. `[writable]` The source spl-token account
+    ///   4. `[writable,signer]` Payer
+    ///   5.. For every vesting (N is the number of vestings):
+    ///       `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
+    ///       `[writable]` The vesting spl-token account
+    ///       `[]` The Vesting Owner account
+    ///
+    ///  Optional part (vestings for Realm)
+    ///   5+3N. `[]` The Realm account
+    ///   6+3N. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   7+3N.. `[writable]` The VoterWeightRecord for every vesting (in the same order).
+    ///       PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   7+4N. `[]` The VestingRealmConfig (the Realm has no vote percentage policy if the account doesn't exist). PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///
+    ///  Trailing account
+    ///   *. `[]` The Mint account (optional for spl-token program, required for spl-token-2022 program)
+    BatchDeposit {
+        #[allow(dead_code)]
+        schedules: Vec<Vec<VestingSchedule>>,
+    },
+
+
+    /// Sums the weighted amounts of the passed VoterWeightRecords and compares the sum with the MaxVoterWeightRecord
+    /// (which keeps the sum of weighted amounts of all VoterWeightRecords of the Realm).
+    /// Result is returned as borsh-serialized `MaxVoterWeightReconciliation` in the return data,
+    /// mismatch is also reported in the program logs.
+    /// The instruction is permissionless and doesn't change any account. Records must be passed in ascending
+    /// order of their addresses greater than `after`. When the Realm has more records than fit into one transaction,
+    /// every next call continues after `last_record` of the previous one, so no record is counted twice,
+    /// and the sums of the calls are added up by the caller
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   1.. `[]` The VoterWeightRecords of the Realm. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ReconcileMaxVoterWeight {
+        #[allow(dead_code)]
+        after: Option<Pubkey>,
+    },
+
+
+    /// Creates a new vesting contract which release times are expressed in the specified time base
+    /// (unix timestamp, slot or epoch). The time base can't be changed after the creation.
+    ///
+    /// Accounts expected by this instruction are the same as for `Deposit` instruction
+    DepositWithTimeBase {
+        #[allow(dead_code)]
+        schedules: Vec<VestingSchedule>,
+        #[allow(dead_code)]
+        linear_schedule: Option<LinearVestingSchedule>,
+        #[allow(dead_code)]
+        revoke_authority: Option<Pubkey>,
+        #[allow(dead_code)]
+        time_base: VestingTimeBase,
+    },
+
+
+    /// Set (or remove) the freeze authority of the Realm. The freeze authority must be a Governance account of the Realm.
+    /// It freezes vestings and accelerates their schedules.
+    /// Creates VestingRealmConfig account if it doesn't exist.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The Governance program account
+    ///   2. `[]` The Realm account
+    ///   3. `[]` The Mint account
+    ///   4. `[signer]` The Realm authority
+    ///   5. `[writable]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   6. `[writable,signer]` Payer
+    ///   7. `[]` The Governance account of the new freeze authority (only when it is set)
+    SetFreezeAuthority {
+        #[allow(dead_code)]
+        freeze_authority: Option<Pubkey>,
+    },
+
+
+    /// Freeze (or unfreeze) vestings of the Realm. Withdraw, split and ownership change of frozen vestings fail.
+    /// Voter weight of frozen vestings is not changed.
+    /// Legacy vesting records must be migrated with MigrateVestingRecord instruction first.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   1. `[signer]` The freeze authority (Governance account of the Realm)
+    ///   2.. `[writable]` The Vesting accounts. PDA seeds: [vesting spl-token account]
+    SetFrozen {
+        #[allow(dead_code)]
+        frozen: bool,
+    },
+
+
+    /// Freeze (or unfreeze) every vesting of the owner in the Realm.
+    /// All vestings of the owner must be passed: their total amount is checked against the VoterWeightRecord.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   1. `[signer]` The freeze authority (Governance account of the Realm)
+    ///   2. `[]` The VoterWeightRecord of the Vesting Owner. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   3.. `[writable]` All Vesting accounts of the owner in the Realm. PDA seeds: [vesting spl-token account]
+    SetOwnerFrozen {
+        #[allow(dead_code)]
+        frozen: bool,
+    },
+
+
+    /// Delegate voting weight of `amount` of owner's vested tokens to the VoterWeightRecord of another owner.
+    /// The delegate receives the share of the owner's weighted amount corresponding to the delegated tokens.
+    /// Tokens stay in the vestings; delegated tokens can't be withdrawn or transferred until they are undelegated.
+    /// All tokens of the owner can be delegated to a single delegate only.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The Governance program account
+    ///   2. `[]` The Realm account
+    ///   3. `[signer]` The Vesting Owner account
+    ///   4. `[]` The Owner's TokenOwnerRecord of the Governance program.
+    ///        PDA seeds: ['governance', realm, token_mint, token_owner]
+    ///   5. `[writable]` The Owner's VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   6. `[writable]` The Delegate's VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    ///   7. `[writable,signer]` Payer
+    Delegate {
+        #[allow(dead_code)]
+        amount: u64,
+    },
+
+
+    /// Return delegated voting weight of `amount` of tokens to the owner.
+    /// The delegate must not have active votes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The Governance program account
+    ///   1. `[]` The Realm account
+    ///   2. `[signer]` The Vesting Owner account
+    ///   3. `[writable]` The Owner's VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    ///   4. `[]` The Delegate's TokenOwnerRecord of the Governance program.
+    ///        PDA seeds: ['governance', realm, token_mint, delegate]
+    ///   5. `[writable]` The Delegate's VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, delegate]
+    Undelegate {
+        #[allow(dead_code)]
+        amount: u64,
+    },
+
+
+    /// Returns the number of tokens which can be withdrawn from the vesting at the current time
+    /// as borsh-serialized u64 in the return data. The instruction doesn't change any account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The Vesting account. PDA seeds: [vesting spl-token account]
+    GetWithdrawableAmount,
+
+
+    /// Returns totals of the passed vesting accounts of one owner and mint
+    /// as borsh-serialized `VestingSummary` in the return data. The instruction doesn't change any account.
+    /// When the owner has more vestings than fit into one transaction, the results of several calls
+    /// should be added up by the caller
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0.. `[]` The Vesting accounts of the owner. PDA seeds: [vesting spl-token account]
+    GetVestingSummary,
+
+
+    /// Returns the state of the VoterWeightRecord as borsh-serialized `VoterWeightInfo` in the return data.
+    /// The instruction doesn't change any account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The VoterWeightRecord. PDA seeds: ['voter_weight', realm, token_mint, token_owner]
+    GetVoterWeight,
+
+
+    /// Allow or disallow anyone to withdraw matured tokens of the vesting to the owner's associated token account.
+    /// The permission is removed when the owner of the vesting changes
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[signer]` The Vesting Owner account
+    SetCrankWithdraw {
+        #[allow(dead_code)]
+        allow: bool,
+    },
+
+
+    /// Withdraw all matured tokens of the vesting to the owner's associated token account.
+    /// The instruction is permissionless and can be used only when the owner allowed it with `SetCrankWithdraw`
+    ///
+    /// Accounts expected by this instruction are the same as for `Withdraw` instruction, but:
+    ///   4. `[writable]` The owner's associated spl-token account for the vesting mint
+    ///   5. `[]` The Vesting Owner account (doesn't sign the transaction)
+    CrankWithdraw,
+
+
+    /// Set the manager of the vesting. The manager can split the vesting or change its owner
+    /// (`Split` and `ChangeOwner` instructions) on behalf of the owner, but can't withdraw tokens.
+    /// The instruction is signed by the owner. The manager is removed when the owner of the vesting changes
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   2. `[signer]` The Vesting Owner account
+    ///   3. `[]` The new Vesting Manager account
+    ///   4. `[writable,signer]` Payer
+    SetManager,
+
+
+    /// Remove the manager of the vesting. The instruction is signed by the owner or the manager
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///   1. `[signer]` The Vesting Owner account (or the Vesting Manager account)
+    ClearManager,
+
+
+    /// Set (or remove) the vote percentage policy for the Realm: the vote percentage of new VoterWeightRecords,
+    /// its maximum and whether partial voting is allowed. The policy is checked by `CreateVoterWeightRecord` and
+    /// `SetVotePercentage` instructions and applied to existing records by `RecalculateVoterWeight` instruction.
+    /// Creates VestingRealmConfig account if it doesn't exist.
+    ///
+    /// Accounts expected by this instruction are the same as for `SetLockupConfig` instruction
+    SetVotePercentagePolicy {
+        #[allow(dead_code)]
+        policy: Option<VotePercentagePolicy>,
+    },
+
+
+    /// Accelerate unlocking of the vestings of the Realm (e.g. by a governance proposal of the freeze authority).
+    /// Release times later than `from_time` are moved earlier by `shift`, but not earlier than `from_time`,
+    /// so tokens are never released later than before. Times are expressed in the time base of each vesting.
+    /// Voter weights of the owners and the max voter weight are decreased by the lost lockup weight
+    /// (legacy voter weight records are not weighted and are not changed). The weight delegated by the owner
+    /// is decreased when the owner's record is recalculated. Frozen vestings can't be accelerated.
+    /// Legacy vesting records must be migrated with MigrateVestingRecord instruction first.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The VestingRealmConfig. PDA seeds: ['vesting-realm-config', realm, token_mint]
+    ///   1. `[signer]` The freeze authority (Governance account of the Realm)
+    ///   2. `[writable]` The MaxVoterWeightRecord. PDA seeds: ['max_voter_weight', realm, token_mint]
+    ///   3.. Pairs of accounts for every vesting:
+    ///       `[writable]` The Vesting account. PDA seeds: [vesting spl-token account]
+    ///       `[writable]` The VoterWeightRecord of the vesting owner. PDA seeds: ['voter_weight', realm, token_mint, vesting_owner]
+    AccelerateSchedule {
+        #[allow(dead_code)]
+        from_time: u64,
+
+        #[allow(dead_code)]
+        shift: u64,
+    },
+
+}
+
+/// Creates a `Deposit` instruction to create and initialize the vesting token account
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new_readonly(*source_token_owner, true),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, false),
+        AccountMeta::new_readonly(*payer, true),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Deposit { schedules };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `Deposit` instruction to create and initialize the vesting token account
+/// inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new_readonly(*source_token_owner, true),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, false),
+        AccountMeta::new(*payer, true),
+
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Deposit { schedules };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `Withdraw` instruction
+pub fn withdraw(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Withdraw;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `Withdraw` instruction with realm
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(owner_record_account, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Withdraw;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `ChangeOwner` instruction
+pub fn change_owner(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    new_vesting_owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new_readonly(*new_vesting_owner, false),
+    ];
+
+    let instruction = VestingInstruction::ChangeOwner;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `ChangeOwner` instruction with realm
+pub fn change_owner_with_realm(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    new_vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let current_owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
+    let current_voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let new_voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, new_vesting_owner);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new_readonly(*new_vesting_owner, false),
+
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(current_owner_record_account, false),
+        AccountMeta::new(current_voter_weight_record_account, false),
+        AccountMeta::new(new_voter_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+    ];
+
+    let instruction = VestingInstruction::ChangeOwner;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `CreateVoterWeightRecord` instruction to create and initialize the VoterWeightRecord
+#[allow(clippy::too_many_arguments)]
+pub fn create_voter_weight_record(
+    program_id: &Pubkey,
+    record_owner: &Pubkey,
+    payer: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, record_owner);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*record_owner, false),
+        AccountMeta::new_readonly(*payer, true),
+
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+    ];
+
+    let instruction = VestingInstruction::CreateVoterWeightRecord;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `ChangeVotePercentage` instruction with realm
+#[allow(clippy::too_many_arguments)]
+pub fn set_vote_percentage_with_realm(
+    program_id: &Pubkey,
+    vesting_owner: &Pubkey,
+    vesting_authority: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    vote_percentage: u16,
+) -> Result<Instruction, ProgramError> {
+    let token_owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
+    let voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let accounts = vec![
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*vesting_owner, false),
+        AccountMeta::new_readonly(*vesting_authority, true),
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(token_owner_record_account, false),
+        AccountMeta::new(voter_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+    ];
+
+    let instruction = VestingInstruction::SetVotePercentage { vote_percentage };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+
+/// Creates a `Close` instruction
+pub fn close(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    spill: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new(*spill, false),
+    ];
+
+    let instruction = VestingInstruction::Close;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `Close` instruction with realm
+#[allow(clippy::too_many_arguments)]
+pub fn close_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    spill: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = close(program_id, token_program_id, vesting_token_account, vesting_owner, spill)?;
+    instruction.accounts.push(AccountMeta::new_readonly(*governance_id, false));
+    instruction.accounts.push(AccountMeta::new_readonly(*realm, false));
+    instruction.accounts.push(AccountMeta::new_readonly(get_token_owner_record_address(governance_id, realm, mint, vesting_owner), false));
+    instruction.accounts.push(AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, vesting_owner), false));
+    Ok(instruction)
+}
+
+/// Creates a `CloseVoterWeightRecord` account
+pub fn close_voter_weight_record(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    spill: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let voter_weight_record = get_voter_weight_record_address(program_id, realm, mint, owner);
+    let accounts = vec![
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(voter_weight_record, false),
+        AccountMeta::new(*spill, false),
+    ];
+
+    let instruction = VestingInstruction::CloseVoterWeightRecord;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+
+/// Creates a `Split` instruction to split and initialize the new vesting token account
+#[allow(clippy::too_many_arguments)]
+pub fn split(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    new_vesting_token_account: &Pubkey,
+    new_vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let (new_vesting_account, _) = Pubkey::find_program_address(&[new_vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new(new_vesting_account, false),
+        AccountMeta::new(*new_vesting_token_account, false),
+        AccountMeta::new_readonly(*new_vesting_owner, false),
+        AccountMeta::new_readonly(*payer, true),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Split { schedules };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `Split` instruction to split and initialize the new vesting token account
+/// inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn split_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    new_vesting_token_account: &Pubkey,
+    new_vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+
+    let (new_vesting_account, _) = Pubkey::find_program_address(&[new_vesting_token_account.as_ref()], program_id);
+    let new_voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, new_vesting_owner);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new(new_vesting_account, false),
+        AccountMeta::new(*new_vesting_token_account, false),
+        AccountMeta::new_readonly(*new_vesting_owner, false),
+        AccountMeta::new(*payer, true),
+
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(owner_record_account, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(new_voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Split { schedules };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `DepositLinear` instruction to create and initialize the vesting token account
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_linear(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedule: LinearVestingSchedule,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = deposit(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        vesting_owner,
+        payer,
+        Vec::new(),
+    )?;
+    instruction.data = VestingInstruction::DepositLinear { schedule }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `DepositLinear` instruction to create and initialize the vesting token account
+/// inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_linear_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedule: LinearVestingSchedule,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = deposit_with_realm(
+        program_id,
+        token_program_id,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        vesting_owner,
+        payer,
+        Vec::new(),
+        realm,
+        mint,
+    )?;
+    instruction.data = VestingInstruction::DepositLinear { schedule }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `SplitLinear` instruction to split and initialize the new vesting token account
+#[allow(clippy::too_many_arguments)]
+pub fn split_linear(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    new_vesting_token_account: &Pubkey,
+    new_vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedule: LinearVestingSchedule,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = split(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        vesting_owner,
+        new_vesting_token_account,
+        new_vesting_owner,
+        payer,
+        Vec::new(),
+    )?;
+    instruction.data = VestingInstruction::SplitLinear { schedule }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `SplitLinear` instruction to split and initialize the new vesting token account
+/// inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn split_linear_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    new_vesting_token_account: &Pubkey,
+    new_vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedule: LinearVestingSchedule,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = split_with_realm(
+        program_id,
+        token_program_id,
+        vesting_token_account,
+        vesting_owner,
+        new_vesting_token_account,
+        new_vesting_owner,
+        payer,
+        Vec::new(),
+        governance_id,
+        realm,
+        mint,
+    )?;
+    instruction.data = VestingInstruction::SplitLinear { schedule }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+/// Creates a `DepositRevocable` instruction to create and initialize the vesting token account
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_revocable(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    revoke_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = deposit(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        vesting_owner,
+        payer,
+        Vec::new(),
+    )?;
+    instruction.data = VestingInstruction::DepositRevocable {
+        schedules,
+        linear_schedule,
+        revoke_authority: *revoke_authority,
+    }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `DepositRevocable` instruction to create and initialize the vesting token account
+/// inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_revocable_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    revoke_authority: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = deposit_with_realm(
+        program_id,
+        token_program_id,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        vesting_owner,
+        payer,
+        Vec::new(),
+        realm,
+        mint,
+    )?;
+    instruction.data = VestingInstruction::DepositRevocable {
+        schedules,
+        linear_schedule,
+        revoke_authority: *revoke_authority,
+    }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `DepositWithTimeBase` instruction to create and initialize the vesting token account
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_with_time_base(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    revoke_authority: Option<Pubkey>,
+    time_base: VestingTimeBase,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = deposit(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        vesting_owner,
+        payer,
+        Vec::new(),
+    )?;
+    instruction.data = VestingInstruction::DepositWithTimeBase {
+        schedules,
+        linear_schedule,
+        revoke_authority,
+        time_base,
+    }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `DepositWithTimeBase` instruction to create and initialize the vesting token account
+/// inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_with_time_base_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    linear_schedule: Option<LinearVestingSchedule>,
+    revoke_authority: Option<Pubkey>,
+    time_base: VestingTimeBase,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = deposit_with_realm(
+        program_id,
+        token_program_id,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        vesting_owner,
+        payer,
+        Vec::new(),
+        realm,
+        mint,
+    )?;
+    instruction.data = VestingInstruction::DepositWithTimeBase {
+        schedules,
+        linear_schedule,
+        revoke_authority,
+        time_base,
+    }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `Revoke` instruction
+pub fn revoke(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    revoke_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*revoke_authority, true),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Revoke;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `Revoke` instruction with realm
+#[allow(clippy::too_many_arguments)]
+pub fn revoke_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    revoke_authority: &Pubkey,
+    vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*revoke_authority, true),
+
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Revoke;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `WithdrawAmount` instruction
+pub fn withdraw_amount(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = withdraw(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        destination_token_account,
+        vesting_owner,
+    )?;
+    instruction.data = VestingInstruction::WithdrawAmount { amount }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `WithdrawAmount` instruction with realm
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_amount_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = withdraw_with_realm(
+        program_id,
+        token_program_id,
+        vesting_token_account,
+        destination_token_account,
+        vesting_owner,
+        governance_id,
+        realm,
+        mint,
+    )?;
+    instruction.data = VestingInstruction::WithdrawAmount { amount }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `SetCrankWithdraw` instruction
+pub fn set_crank_withdraw(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    allow: bool,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+    ];
+
+    let instruction = VestingInstruction::SetCrankWithdraw { allow };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `CrankWithdraw` instruction
+pub fn crank_withdraw(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let destination_token_account = get_associated_token_address_with_program_id(vesting_owner, mint, token_program_id);
+    let mut instruction = withdraw(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        &destination_token_account,
+        vesting_owner,
+    )?;
+    instruction.accounts[4].is_signer = false;
+    instruction.data = VestingInstruction::CrankWithdraw.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `CrankWithdraw` instruction with realm
+pub fn crank_withdraw_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let destination_token_account = get_associated_token_address_with_program_id(vesting_owner, mint, token_program_id);
+    let mut instruction = withdraw_with_realm(
+        program_id,
+        token_program_id,
+        vesting_token_account,
+        &destination_token_account,
+        vesting_owner,
+        governance_id,
+        realm,
+        mint,
+    )?;
+    instruction.accounts[4].is_signer = false;
+    instruction.data = VestingInstruction::CrankWithdraw.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `Merge` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn merge(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    spill: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let (source_vesting_account, _) = Pubkey::find_program_address(&[source_vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*vesting_token_account, false),
+        AccountMeta::new(source_vesting_account, false),
+        AccountMeta::new(*source_vesting_token_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new(*spill, false),
+        AccountMeta::new(*payer, true),
+
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let instruction = VestingInstruction::Merge;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `ProposeOwner` instruction
+pub fn propose_owner(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    proposed_vesting_owner: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new_readonly(*proposed_vesting_owner, false),
+        AccountMeta::new(*payer, true),
+    ];
+
+    let instruction = VestingInstruction::ProposeOwner;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `SetManager` instruction
+pub fn set_manager(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    manager: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new_readonly(*manager, false),
+        AccountMeta::new(*payer, true),
+    ];
+
+    let instruction = VestingInstruction::SetManager;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `ClearManager` instruction
+pub fn clear_manager(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*authority, true),
+    ];
+
+    let instruction = VestingInstruction::ClearManager;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Makes `Split` or `ChangeOwner` instruction (created by any of their builders)
+/// signed by the vesting manager instead of the vesting owner
+pub fn signed_by_manager(
+    mut instruction: Instruction,
+    vesting_owner: &Pubkey,
+    manager: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let owner_meta = instruction.accounts.iter_mut()
+        .find(|meta| meta.pubkey == *vesting_owner && meta.is_signer)
+        .ok_or(ProgramError::InvalidArgument)?;
+    owner_meta.pubkey = *manager;
+    Ok(instruction)
+}
+
+/// Appends the VoterWeightRecord of the delegate to `Revoke` instruction
+/// (created by any of its builders with realm), so the delegated tokens can be revoked from the vesting
+pub fn with_delegate_voter_weight_record(
+    mut instruction: Instruction,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    let delegate_voter_weight_record_account = get_voter_weight_record_address(&instruction.program_id, realm, mint, delegate);
+    append_accounts(&mut instruction, mint, vec![AccountMeta::new(delegate_voter_weight_record_account, false)]);
+    instruction
+}
+
+/// Appends the VoterWeightRecord and the TokenOwnerRecord of the delegate to `ChangeOwner`, `AcceptOwner`
+/// or `Split` instruction (created by any of their builders with realm), so the delegated tokens can be moved
+/// from the vesting
+pub fn with_delegate_records(
+    instruction: Instruction,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    let mut instruction = with_delegate_voter_weight_record(instruction, realm, mint, delegate);
+    append_accounts(&mut instruction, mint, vec![AccountMeta::new_readonly(get_token_owner_record_address(governance_id, realm, mint, delegate), false)]);
+    instruction
+}
+
+/// Appends optional accounts to the instruction keeping the Mint account (if any) the last one
+fn append_accounts(instruction: &mut Instruction, mint: &Pubkey, accounts: Vec<AccountMeta>) {
+    let index = match instruction.accounts.last() {
+        Some(meta) if meta.pubkey == *mint => instruction.accounts.len() - 1,
+        _ => instruction.accounts.len(),
+    };
+    let trailing_accounts = instruction.accounts.split_off(index);
+    instruction.accounts.extend(accounts);
+    instruction.accounts.extend(trailing_accounts);
+}
+
+/// Creates an `AcceptOwner` instruction
+pub fn accept_owner(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    proposed_vesting_owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, false),
+        AccountMeta::new_readonly(*proposed_vesting_owner, true),
+    ];
+
+    let instruction = VestingInstruction::AcceptOwner;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates an `AcceptOwner` instruction with realm
+pub fn accept_owner_with_realm(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    proposed_vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let current_owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
+    let current_voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let new_voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, proposed_vesting_owner);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, false),
+        AccountMeta::new_readonly(*proposed_vesting_owner, true),
+
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(current_owner_record_account, false),
+        AccountMeta::new(current_voter_weight_record_account, false),
+        AccountMeta::new(new_voter_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+    ];
+
+    let instruction = VestingInstruction::AcceptOwner;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `CancelOwnerProposal` instruction
+pub fn cancel_owner_proposal(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+    ];
+
+    let instruction = VestingInstruction::CancelOwnerProposal;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `SetLockupConfig` instruction
+pub fn set_lockup_config(
+    program_id: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    realm_authority: &Pubkey,
+    payer: &Pubkey,
+    lockup: Option<LockupConfig>,
+) -> Result<Instruction, ProgramError> {
+    let realm_config_account = get_realm_config_address(program_id, realm, mint);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*realm_authority, true),
+        AccountMeta::new(realm_config_account, false),
+        AccountMeta::new(*payer, true),
+    ];
+
+    let instruction = VestingInstruction::SetLockupConfig { lockup };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `SetVotePercentagePolicy` instruction
+pub fn set_vote_percentage_policy(
+    program_id: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    realm_authority: &Pubkey,
+    payer: &Pubkey,
+    policy: Option<VotePercentagePolicy>,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = set_lockup_config(program_id, governance_id, realm, mint, realm_authority, payer, None)?;
+    instruction.data = VestingInstruction::SetVotePercentagePolicy { policy }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates an `AccelerateSchedule` instruction for the vesting spl-token accounts
+/// (given together with the owners of the vestings)
+pub fn accelerate_schedule(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+    vestings: &[(Pubkey, Pubkey)],
+    from_time: u64,
+    shift: u64,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+        AccountMeta::new_readonly(*freeze_authority, true),
+        AccountMeta::new(get_max_voter_weight_record_address(program_id, realm, mint), false),
+    ];
+    for (vesting_token_account, vesting_owner) in vestings {
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+        accounts.push(AccountMeta::new(vesting_account, false));
+        accounts.push(AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, vesting_owner), false));
+    }
+
+    let instruction = VestingInstruction::AccelerateSchedule { from_time, shift };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `SetFreezeAuthority` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn set_freeze_authority(
+    program_id: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    realm_authority: &Pubkey,
+    payer: &Pubkey,
+    freeze_authority: Option<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let realm_config_account = get_realm_config_address(program_id, realm, mint);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*realm_authority, true),
+        AccountMeta::new(realm_config_account, false),
+        AccountMeta::new(*payer, true),
+    ];
+    if let Some(freeze_authority) = freeze_authority {
+        accounts.push(AccountMeta::new_readonly(freeze_authority, false));
+    }
+
+    let instruction = VestingInstruction::SetFreezeAuthority { freeze_authority };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `SetFrozen` instruction for the vesting spl-token accounts
+pub fn set_frozen(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+    vesting_token_accounts: &[Pubkey],
+    frozen: bool,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+        AccountMeta::new_readonly(*freeze_authority, true),
+    ];
+    for vesting_token_account in vesting_token_accounts {
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+        accounts.push(AccountMeta::new(vesting_account, false));
+    }
+
+    let instruction = VestingInstruction::SetFrozen { frozen };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `SetOwnerFrozen` instruction for all vesting spl-token accounts of the owner
+pub fn set_owner_frozen(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+    vesting_owner: &Pubkey,
+    vesting_token_accounts: &[Pubkey],
+    frozen: bool,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = set_frozen(program_id, realm, mint, freeze_authority, vesting_token_accounts, frozen)?;
+    instruction.accounts.insert(2, AccountMeta::new_readonly(get_voter_weight_record_address(program_id, realm, mint, vesting_owner), false));
+    instruction.data = VestingInstruction::SetOwnerFrozen { frozen }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `Delegate` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn delegate(
+    program_id: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(get_token_owner_record_address(governance_id, realm, mint, owner), false),
+        AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, owner), false),
+        AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, delegate), false),
+        AccountMeta::new(*payer, true),
+    ];
+
+    let instruction = VestingInstruction::Delegate { amount };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates an `Undelegate` instruction
+pub fn undelegate(
+    program_id: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, owner), false),
+        AccountMeta::new_readonly(get_token_owner_record_address(governance_id, realm, mint, delegate), false),
+        AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, delegate), false),
+    ];
+
+    let instruction = VestingInstruction::Undelegate { amount };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `RecalculateVoterWeight` instruction
+pub fn recalculate_voter_weight(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    delegate: Option<&Pubkey>,
+    vesting_token_accounts: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let realm_config_account = get_realm_config_address(program_id, realm, mint);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(realm_config_account, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+    ];
+    if let Some(delegate) = delegate {
+        accounts.push(AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, delegate), false));
+    }
+    for vesting_token_account in vesting_token_accounts {
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+        accounts.push(AccountMeta::new_readonly(vesting_account, false));
+    }
+
+    let instruction = VestingInstruction::RecalculateVoterWeight;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `UpdateVoterWeightRecord` instruction
+pub fn update_voter_weight_record(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    delegate: Option<&Pubkey>,
+    vesting_token_accounts: &[Pubkey],
+    action: VoterWeightAction,
+    target: Option<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = recalculate_voter_weight(program_id, realm, mint, owner, delegate, vesting_token_accounts)?;
+    instruction.accounts.insert(3, AccountMeta::new_readonly(*owner, true));
+    instruction.data = VestingInstruction::UpdateVoterWeightRecord { action, target }.try_to_vec().unwrap();
+    Ok(instruction)
+}
+
+/// Creates a `MigrateVestingRecord` instruction
+pub fn migrate_vesting_record(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new(*payer, true),
+    ];
+
+    let instruction = VestingInstruction::MigrateVestingRecord;
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `AttachRealm` instruction
+pub fn attach_realm(
+    program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(vesting_account, false),
+        AccountMeta::new_readonly(*vesting_owner, true),
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
     ];
 
-    let instruction = VestingInstruction::Withdraw;
+    let instruction = VestingInstruction::AttachRealm;
 
     Ok(Instruction {
         program_id: *program_id,
@@ -267,13 +2253,10 @@ pub fn withdraw(
     })
 }
 
-/// Creates a `Withdraw` instruction with realm
-#[allow(clippy::too_many_arguments)]
-pub fn withdraw_with_realm(
+/// Creates a `DetachRealm` instruction
+pub fn detach_realm(
     program_id: &Pubkey,
-    token_program_id: &Pubkey,
     vesting_token_account: &Pubkey,
-    destination_token_account: &Pubkey,
     vesting_owner: &Pubkey,
     governance_id: &Pubkey,
     realm: &Pubkey,
@@ -284,20 +2267,17 @@ pub fn withdraw_with_realm(
     let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
     let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
     let accounts = vec![
-        AccountMeta::new_readonly(*token_program_id, false),
         AccountMeta::new(vesting_account, false),
-        AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new(*destination_token_account, false),
         AccountMeta::new_readonly(*vesting_owner, true),
-
         AccountMeta::new_readonly(*governance_id, false),
         AccountMeta::new_readonly(*realm, false),
         AccountMeta::new_readonly(owner_record_account, false),
         AccountMeta::new(voting_weight_record_account, false),
         AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
     ];
 
-    let instruction = VestingInstruction::Withdraw;
+    let instruction = VestingInstruction::DetachRealm;
 
     Ok(Instruction {
         program_id: *program_id,
@@ -306,21 +2286,44 @@ pub fn withdraw_with_realm(
     })
 }
 
-/// Creates a `ChangeOwner` instruction
-pub fn change_owner(
+/// Creates a `MoveRealm` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn move_realm(
     program_id: &Pubkey,
     vesting_token_account: &Pubkey,
     vesting_owner: &Pubkey,
-    new_vesting_owner: &Pubkey,
+    governance_id: &Pubkey,
+    realm: &Pubkey,
+    new_governance_id: &Pubkey,
+    new_realm: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+    let owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+    let new_voting_weight_record_account = get_voter_weight_record_address(program_id, new_realm, mint, vesting_owner);
+    let new_max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, new_realm, mint);
     let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new(vesting_account, false),
         AccountMeta::new_readonly(*vesting_owner, true),
-        AccountMeta::new_readonly(*new_vesting_owner, false),
+        AccountMeta::new_readonly(*governance_id, false),
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new_readonly(owner_record_account, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new_readonly(*new_governance_id, false),
+        AccountMeta::new_readonly(*new_realm, false),
+        AccountMeta::new(new_voting_weight_record_account, false),
+        AccountMeta::new(new_max_voting_weight_record_account, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, new_realm, mint), false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
     ];
 
-    let instruction = VestingInstruction::ChangeOwner;
+    let instruction = VestingInstruction::MoveRealm;
 
     Ok(Instruction {
         program_id: *program_id,
@@ -329,33 +2332,21 @@ pub fn change_owner(
     })
 }
 
-/// Creates a `ChangeOwner` instruction with realm
-pub fn change_owner_with_realm(
+/// Creates a `Compact` instruction
+pub fn compact(
     program_id: &Pubkey,
     vesting_token_account: &Pubkey,
     vesting_owner: &Pubkey,
-    new_vesting_owner: &Pubkey,
-    governance_id: &Pubkey,
-    realm: &Pubkey,
-    mint: &Pubkey,
+    rent_receiver: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
-    let current_owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
-    let current_voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
-    let new_voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, new_vesting_owner);
     let accounts = vec![
         AccountMeta::new(vesting_account, false),
         AccountMeta::new_readonly(*vesting_owner, true),
-        AccountMeta::new_readonly(*new_vesting_owner, false),
-
-        AccountMeta::new_readonly(*governance_id, false),
-        AccountMeta::new_readonly(*realm, false),
-        AccountMeta::new_readonly(current_owner_record_account, false),
-        AccountMeta::new(current_voter_weight_record_account, false),
-        AccountMeta::new(new_voter_weight_record_account, false),
+        AccountMeta::new(*rent_receiver, false),
     ];
 
-    let instruction = VestingInstruction::ChangeOwner;
+    let instruction = VestingInstruction::Compact;
 
     Ok(Instruction {
         program_id: *program_id,
@@ -364,27 +2355,35 @@ pub fn change_owner_with_realm(
     })
 }
 
-/// Creates a `CreateVoterWeightRecord` instruction to create and initialize the VoterWeightRecord
-#[allow(clippy::too_many_arguments)]
-pub fn create_voter_weight_record(
+/// Creates a `BatchDeposit` instruction. Every deposit is (vesting spl-token account, vesting owner, schedules)
+pub fn batch_deposit(
     program_id: &Pubkey,
-    record_owner: &Pubkey,
-    payer: &Pubkey,
-    realm: &Pubkey,
+    token_program_id: &Pubkey,
     mint: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    payer: &Pubkey,
+    deposits: &[(Pubkey, Pubkey, Vec<VestingSchedule>)],
 ) -> Result<Instruction, ProgramError> {
-    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, record_owner);
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new_readonly(system_program::id(), false),
-        AccountMeta::new_readonly(*record_owner, false),
-        AccountMeta::new_readonly(*payer, true),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*source_token_owner, true),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*payer, true),
 
-        AccountMeta::new_readonly(*realm, false),
         AccountMeta::new_readonly(*mint, false),
-        AccountMeta::new(voting_weight_record_account, false),
     ];
+    for (vesting_token_account, vesting_owner, _) in deposits {
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
+        accounts.push(AccountMeta::new(vesting_account, false));
+        accounts.push(AccountMeta::new(*vesting_token_account, false));
+        accounts.push(AccountMeta::new_readonly(*vesting_owner, false));
+    }
 
-    let instruction = VestingInstruction::CreateVoterWeightRecord;
+    let instruction = VestingInstruction::BatchDeposit {
+        schedules: deposits.iter().map(|(_, _, schedules)| schedules.clone()).collect(),
+    };
 
     Ok(Instruction {
         program_id: *program_id,
@@ -393,30 +2392,45 @@ pub fn create_voter_weight_record(
     })
 }
 
-/// Creates a `ChangeVotePercentage` instruction with realm
+/// Creates a `BatchDeposit` instruction to create several vestings inside the Realm
 #[allow(clippy::too_many_arguments)]
-pub fn set_vote_percentage_with_realm(
+pub fn batch_deposit_with_realm(
     program_id: &Pubkey,
-    vesting_owner: &Pubkey,
-    vesting_authority: &Pubkey,
-    governance_id: &Pubkey,
-    realm: &Pubkey,
+    token_program_id: &Pubkey,
     mint: &Pubkey,
-    vote_percentage: u16,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    payer: &Pubkey,
+    deposits: &[(Pubkey, Pubkey, Vec<VestingSchedule>)],
+    realm: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
-    let token_owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
-    let voter_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
-    let accounts = vec![
-        AccountMeta::new_readonly(*mint, false),
-        AccountMeta::new_readonly(*vesting_owner, false),
-        AccountMeta::new_readonly(*vesting_authority, true),
-        AccountMeta::new_readonly(*governance_id, false),
+    let mut instruction = batch_deposit(program_id, token_program_id, mint, source_token_owner, source_token_account, payer, deposits)?;
+    let mut accounts = vec![
         AccountMeta::new_readonly(*realm, false),
-        AccountMeta::new_readonly(token_owner_record_account, false),
-        AccountMeta::new(voter_weight_record_account, false),
+        AccountMeta::new(get_max_voter_weight_record_address(program_id, realm, mint), false),
     ];
+    for (_, vesting_owner, _) in deposits {
+        accounts.push(AccountMeta::new(get_voter_weight_record_address(program_id, realm, mint, vesting_owner), false));
+    }
+    accounts.push(AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false));
+    append_accounts(&mut instruction, mint, accounts);
+    Ok(instruction)
+}
 
-    let instruction = VestingInstruction::SetVotePercentage { vote_percentage };
+/// Creates a `ReconcileMaxVoterWeight` instruction for the VoterWeightRecords sorted by their addresses
+pub fn reconcile_max_voter_weight(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    after: Option<Pubkey>,
+    voter_weight_records: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(get_max_voter_weight_record_address(program_id, realm, mint), false),
+    ];
+    accounts.extend(voter_weight_records.iter().map(|record| AccountMeta::new_readonly(*record, false)));
+
+    let instruction = VestingInstruction::ReconcileMaxVoterWeight { after };
 
     Ok(Instruction {
         program_id: *program_id,
@@ -425,25 +2439,17 @@ pub fn set_vote_percentage_with_realm(
     })
 }
 
-
-/// Creates a `Close` instruction
-pub fn close(
+/// Creates a `GetWithdrawableAmount` instruction
+pub fn get_withdrawable_amount(
     program_id: &Pubkey,
-    token_program_id: &Pubkey,
     vesting_token_account: &Pubkey,
-    vesting_owner: &Pubkey,
-    spill: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
     let accounts = vec![
-        AccountMeta::new_readonly(*token_program_id, false),
-        AccountMeta::new(vesting_account, false),
-        AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new_readonly(*vesting_owner, true),
-        AccountMeta::new(*spill, false),
+        AccountMeta::new_readonly(vesting_account, false),
     ];
 
-    let instruction = VestingInstruction::Close;
+    let instruction = VestingInstruction::GetWithdrawableAmount;
 
     Ok(Instruction {
         program_id: *program_id,
@@ -452,25 +2458,36 @@ pub fn close(
     })
 }
 
+/// Creates a `GetVestingSummary` instruction
+pub fn get_vesting_summary(
+    program_id: &Pubkey,
+    vesting_accounts: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let accounts = vesting_accounts.iter()
+        .map(|vesting_account| AccountMeta::new_readonly(*vesting_account, false))
+        .collect();
+
+    let instruction = VestingInstruction::GetVestingSummary;
 
-/// Creates a `CloseVoterWeightRecord` account
-pub fn close_voter_weight_record(
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.try_to_vec().unwrap(),
+    })
+}
+
+/// Creates a `GetVoterWeight` instruction
+pub fn get_voter_weight(
     program_id: &Pubkey,
-    owner: &Pubkey,
     realm: &Pubkey,
     mint: &Pubkey,
-    spill: &Pubkey,
+    vesting_owner: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
-    let voter_weight_record = get_voter_weight_record_address(program_id, realm, mint, owner);
     let accounts = vec![
-        AccountMeta::new_readonly(*owner, true),
-        AccountMeta::new_readonly(*realm, false),
-        AccountMeta::new_readonly(*mint, false),
-        AccountMeta::new(voter_weight_record, false),
-        AccountMeta::new(*spill, false),
+        AccountMeta::new_readonly(get_voter_weight_record_address(program_id, realm, mint, vesting_owner), false),
     ];
 
-    let instruction = VestingInstruction::CloseVoterWeightRecord;
+    let instruction = VestingInstruction::GetVoterWeight;
 
     Ok(Instruction {
         program_id: *program_id,
@@ -479,34 +2496,20 @@ pub fn close_voter_weight_record(
     })
 }
 
-
-/// Creates a `Split` instruction to split and initialize the new vesting token account
-#[allow(clippy::too_many_arguments)]
-pub fn split(
+/// Creates a `SetRevokeAuthority` instruction
+pub fn set_revoke_authority(
     program_id: &Pubkey,
-    token_program_id: &Pubkey,
     vesting_token_account: &Pubkey,
-    vesting_owner: &Pubkey,
-    new_vesting_token_account: &Pubkey,
-    new_vesting_owner: &Pubkey,
-    payer: &Pubkey,
-    schedules: Vec<VestingSchedule>,
+    revoke_authority: &Pubkey,
+    new_revoke_authority: Option<Pubkey>,
 ) -> Result<Instruction, ProgramError> {
     let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
-    let (new_vesting_account, _) = Pubkey::find_program_address(&[new_vesting_token_account.as_ref()], program_id);
     let accounts = vec![
-        AccountMeta::new_readonly(system_program::id(), false),
-        AccountMeta::new_readonly(*token_program_id, false),
         AccountMeta::new(vesting_account, false),
-        AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new_readonly(*vesting_owner, true),
-        AccountMeta::new(new_vesting_account, false),
-        AccountMeta::new(*new_vesting_token_account, false),
-        AccountMeta::new_readonly(*new_vesting_owner, false),
-        AccountMeta::new_readonly(*payer, true),
+        AccountMeta::new_readonly(*revoke_authority, true),
     ];
 
-    let instruction = VestingInstruction::Split { schedules };
+    let instruction = VestingInstruction::SetRevokeAuthority { revoke_authority: new_revoke_authority };
 
     Ok(Instruction {
         program_id: *program_id,
@@ -515,47 +2518,32 @@ pub fn split(
     })
 }
 
-/// Creates a `Split` instruction to split and initialize the new vesting token account
-/// inside the Realm
+/// Creates an `AddSchedules` instruction
 #[allow(clippy::too_many_arguments)]
-pub fn split_with_realm(
+pub fn add_schedules(
     program_id: &Pubkey,
     token_program_id: &Pubkey,
+    mint: &Pubkey,
     vesting_token_account: &Pubkey,
-    vesting_owner: &Pubkey,
-    new_vesting_token_account: &Pubkey,
-    new_vesting_owner: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
     payer: &Pubkey,
     schedules: Vec<VestingSchedule>,
-    governance_id: &Pubkey,
-    realm: &Pubkey,
-    mint: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], program_id);
-    let owner_record_account = get_token_owner_record_address(governance_id, realm, mint, vesting_owner);
-    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
-
-    let (new_vesting_account, _) = Pubkey::find_program_address(&[new_vesting_token_account.as_ref()], program_id);
-    let new_voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, new_vesting_owner);
     let accounts = vec![
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(*token_program_id, false),
         AccountMeta::new(vesting_account, false),
         AccountMeta::new(*vesting_token_account, false),
-        AccountMeta::new_readonly(*vesting_owner, true),
-        AccountMeta::new(new_vesting_account, false),
-        AccountMeta::new(*new_vesting_token_account, false),
-        AccountMeta::new_readonly(*new_vesting_owner, false),
+        AccountMeta::new_readonly(*source_token_owner, true),
+        AccountMeta::new(*source_token_account, false),
         AccountMeta::new(*payer, true),
 
-        AccountMeta::new_readonly(*governance_id, false),
-        AccountMeta::new_readonly(*realm, false),
-        AccountMeta::new_readonly(owner_record_account, false),
-        AccountMeta::new(voting_weight_record_account, false),
-        AccountMeta::new(new_voting_weight_record_account, false),
+        AccountMeta::new_readonly(*mint, false),
     ];
 
-    let instruction = VestingInstruction::Split { schedules };
+    let instruction = VestingInstruction::AddSchedules { schedules };
 
     Ok(Instruction {
         program_id: *program_id,
@@ -564,6 +2552,41 @@ pub fn split_with_realm(
     })
 }
 
+/// Creates an `AddSchedules` instruction for the vesting inside the Realm
+#[allow(clippy::too_many_arguments)]
+pub fn add_schedules_with_realm(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_token_account: &Pubkey,
+    source_token_owner: &Pubkey,
+    source_token_account: &Pubkey,
+    vesting_owner: &Pubkey,
+    payer: &Pubkey,
+    schedules: Vec<VestingSchedule>,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let voting_weight_record_account = get_voter_weight_record_address(program_id, realm, mint, vesting_owner);
+    let max_voting_weight_record_account = get_max_voter_weight_record_address(program_id, realm, mint);
+
+    let mut instruction = add_schedules(
+        program_id,
+        token_program_id,
+        mint,
+        vesting_token_account,
+        source_token_owner,
+        source_token_account,
+        payer,
+        schedules,
+    )?;
+    append_accounts(&mut instruction, mint, vec![
+        AccountMeta::new_readonly(*realm, false),
+        AccountMeta::new(voting_weight_record_account, false),
+        AccountMeta::new(max_voting_weight_record_account, false),
+        AccountMeta::new_readonly(get_realm_config_address(program_id, realm, mint), false),
+    ]);
+    Ok(instruction)
+}
 
 
 #[cfg(test)]
@@ -602,5 +2625,128 @@ mod test {
             original_set_vote_percentage,
             VestingInstruction::try_from_slice(&original_set_vote_percentage.try_to_vec().unwrap()).unwrap()
         );
+
+        let original_deposit_linear = VestingInstruction::DepositLinear {
+            schedule: LinearVestingSchedule {
+                start_time: 100,
+                cliff_time: 200,
+                end_time: 1000,
+                amount: 42,
+            },
+        };
+        assert_eq!(
+            original_deposit_linear,
+            VestingInstruction::try_from_slice(&original_deposit_linear.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_set_revoke_authority = VestingInstruction::SetRevokeAuthority { revoke_authority: Some(Pubkey::new_unique()) };
+        assert_eq!(
+            original_set_revoke_authority,
+            VestingInstruction::try_from_slice(&original_set_revoke_authority.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_withdraw_amount = VestingInstruction::WithdrawAmount { amount: 1_000_000 };
+        assert_eq!(
+            original_withdraw_amount,
+            VestingInstruction::try_from_slice(&original_withdraw_amount.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_set_lockup_config = VestingInstruction::SetLockupConfig {
+            lockup: Some(LockupConfig {
+                baseline_factor: 10000,
+                max_extra_factor: 20000,
+                saturation_secs: 4 * 365 * 86400,
+            }),
+        };
+        assert_eq!(
+            original_set_lockup_config,
+            VestingInstruction::try_from_slice(&original_set_lockup_config.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_update_voter_weight_record = VestingInstruction::UpdateVoterWeightRecord {
+            action: VoterWeightAction::CastVote,
+            target: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(
+            original_update_voter_weight_record,
+            VestingInstruction::try_from_slice(&original_update_voter_weight_record.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_batch_deposit = VestingInstruction::BatchDeposit {
+            schedules: vec![
+                vec![VestingSchedule { amount: 42, release_time: 250 }],
+                vec![
+                    VestingSchedule { amount: 7, release_time: 500 },
+                    VestingSchedule { amount: 9, release_time: 900 },
+                ],
+            ],
+        };
+        assert_eq!(
+            original_batch_deposit,
+            VestingInstruction::try_from_slice(&original_batch_deposit.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_deposit_with_time_base = VestingInstruction::DepositWithTimeBase {
+            schedules: vec![VestingSchedule { amount: 42, release_time: 250_000_000 }],
+            linear_schedule: None,
+            revoke_authority: Some(Pubkey::new_unique()),
+            time_base: VestingTimeBase::Slot,
+        };
+        assert_eq!(
+            original_deposit_with_time_base,
+            VestingInstruction::try_from_slice(&original_deposit_with_time_base.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_set_freeze_authority = VestingInstruction::SetFreezeAuthority {
+            freeze_authority: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(
+            original_set_freeze_authority,
+            VestingInstruction::try_from_slice(&original_set_freeze_authority.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_set_vote_percentage_policy = VestingInstruction::SetVotePercentagePolicy {
+            policy: Some(VotePercentagePolicy {
+                default_vote_percentage: 5000,
+                max_vote_percentage: 8000,
+                allow_partial_voting: true,
+            }),
+        };
+        assert_eq!(
+            original_set_vote_percentage_policy,
+            VestingInstruction::try_from_slice(&original_set_vote_percentage_policy.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_accelerate_schedule = VestingInstruction::AccelerateSchedule {
+            from_time: 1_700_000_000,
+            shift: 86_400 * 30,
+        };
+        assert_eq!(
+            original_accelerate_schedule,
+            VestingInstruction::try_from_slice(&original_accelerate_schedule.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_set_crank_withdraw = VestingInstruction::SetCrankWithdraw { allow: true };
+        assert_eq!(
+            original_set_crank_withdraw,
+            VestingInstruction::try_from_slice(&original_set_crank_withdraw.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_delegate = VestingInstruction::Delegate { amount: 1_000_000 };
+        assert_eq!(
+            original_delegate,
+            VestingInstruction::try_from_slice(&original_delegate.try_to_vec().unwrap()).unwrap()
+        );
+
+        let original_add_schedules = VestingInstruction::AddSchedules {
+            schedules: vec![VestingSchedule {
+                amount: 7,
+                release_time: 500,
+            }],
+        };
+        assert_eq!(
+            original_add_schedules,
+            VestingInstruction::try_from_slice(&original_add_schedules.try_to_vec().unwrap()).unwrap()
+        );
     }
 }
@@ -6,6 +6,8 @@ pub mod instruction;
 pub mod state;
 pub mod voter_weight;
 pub mod max_voter_weight;
+pub mod realm_config;
 pub mod token_owner_record;
+pub mod event;
 
 pub mod processor;
@@ -6,11 +6,25 @@ use solana_program::{
     rent::Rent,
     sysvar::Sysvar,
 };
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use spl_governance::addins::max_voter_weight::get_max_voter_weight_record_data;
 use spl_governance_tools::account::create_and_serialize_account_signed;
 
 pub use spl_governance_addin_api::max_voter_weight::MaxVoterWeightRecord;
 
+/// Return data of ReconcileMaxVoterWeight instruction
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct MaxVoterWeightReconciliation {
+    /// Current value of the MaxVoterWeightRecord
+    pub max_voter_weight: u64,
+
+    /// Sum of weighted amounts of the passed VoterWeightRecords
+    pub weighted_amount: u64,
+
+    /// Address of the last passed VoterWeightRecord (the next call continues after it)
+    pub last_record: Option<Pubkey>,
+}
+
 /// Returns MaxVoterWeightRecord PDA seeds
 pub fn get_max_voter_weight_record_seeds<'a>(
     realm: &'a Pubkey,
@@ -40,6 +54,20 @@ pub fn get_max_voter_weight_record_data_for_seeds(
     get_max_voter_weight_record_data(program_id, max_voter_weight_record_info)
 }
 
+/// Deserializes MaxVoterWeightRecord account and checks owner program and that the address
+/// is derived from the realm and mint stored in the record
+pub fn get_max_voter_weight_record_data_checked_address(
+    program_id: &Pubkey,
+    record_info: &AccountInfo,
+) -> Result<MaxVoterWeightRecord, ProgramError> {
+    let record = get_max_voter_weight_record_data(program_id, record_info)?;
+    let record_address = get_max_voter_weight_record_address(program_id, &record.realm, &record.governing_token_mint);
+    if record_address != *record_info.key {
+        return Err(VestingError::InvalidMaxVoterWeightRecordAccountAddress.into());
+    }
+    Ok(record)
+}
+
 /// Deserializes MaxVoterWeightRecord account and checks owner program and linkage
 pub fn get_max_voter_weight_record_data_checked(
     program_id: &Pubkey,
@@ -3,29 +3,33 @@ use solana_program::{
     borsh::try_from_slice_unchecked,
     entrypoint::ProgramResult,
     msg,
-    program::invoke_signed,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
+    system_instruction,
     sysvar::{clock::Clock, Sysvar},
 };
 
 use borsh::BorshSerialize;
-use spl_token::{
+use spl_token_2022::{
+    check_spl_token_program_account,
+    extension::{ExtensionType, StateWithExtensions},
     instruction::{
         close_account,
         set_authority,
         AuthorityType,
     },
-    state::Account,
+    state::{Account, Mint},
 };
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_governance_tools::account::{
     get_account_data,
     create_and_serialize_account_signed,
     dispose_account,
 };
 use spl_governance::state::{
+    governance::get_governance_data_for_realm,
     realm::get_realm_data,
     token_owner_record::{
         get_token_owner_record_address_seeds,
@@ -35,15 +39,32 @@ use spl_governance::state::{
 
 use crate::{
     error::VestingError,
+    event::VestingEvent,
     instruction::VestingInstruction,
-    state::{VestingAccountType, VestingRecord, VestingSchedule},
+    state::{LinearVesting, LinearVestingSchedule, LockupConfig, VestingAccountType, VestingRecordV1, VestingRecordV2, VestingSchedule, VestingSummary, VestingTimeBase, VESTING_RECORD_RESERVED_SIZE},
     voter_weight::{
+        ExtendedVoterWeightRecord,
+        VoterWeightAction,
+        VoterWeightInfo,
         create_voter_weight_record,
+        get_voter_weight_record_address,
+        get_voter_weight_record_data,
         get_voter_weight_record_data_checked,
+        get_voter_weight_record_data_checked_address,
+    },
+    realm_config::{
+        VestingRealmConfig,
+        VotePercentagePolicy,
+        VESTING_REALM_CONFIG_RESERVED_SIZE,
+        get_realm_config_address,
+        get_realm_config_seeds,
+        get_realm_config_data_if_exists,
     },
     max_voter_weight::{
+        MaxVoterWeightReconciliation,
         create_max_voter_weight_record,
         get_max_voter_weight_record_data_checked,
+        get_max_voter_weight_record_data_checked_address,
     },
     token_owner_record::{
         get_token_owner_record_data_if_exists,
@@ -58,6 +79,9 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         schedules: Vec<VestingSchedule>,
+        linear_schedule: Option<LinearVestingSchedule>,
+        revoke_authority: Option<Pubkey>,
+        time_base: VestingTimeBase,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
 
@@ -70,10 +94,15 @@ impl Processor {
         let vesting_owner_account = next_account_info(accounts_iter)?;
         let payer_account = next_account_info(accounts_iter)?;
 
+        let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+        let (accounts, mint_account) = split_mint_account(accounts_iter.as_slice(), &vesting_token_account_data.mint);
+        let accounts_iter = &mut accounts.iter();
+
         let realm_info = if let Some(realm) = accounts_iter.next() {
             let voter_weight = next_account_info(accounts_iter)?;
             let max_voter_weight = next_account_info(accounts_iter)?;
-            Some((realm, voter_weight, max_voter_weight,))
+            let realm_config = next_account_info(accounts_iter)?;
+            Some((realm, voter_weight, max_voter_weight, realm_config,))
         } else {
             None
         };
@@ -83,23 +112,36 @@ impl Processor {
         }
 
         verify_schedule(&schedules)?;
+        if let Some(linear_schedule) = &linear_schedule {
+            linear_schedule.verify()?;
+        }
 
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
         verify_token_account_owned_by_vesting(vesting_account, vesting_token_account_data)?;
+        let mint = get_transfer_mint(spl_token_account, mint_account, &vesting_token_account_data.mint)?;
 
-        let total_amount = schedules.iter()
-                .try_fold(0u64, |acc, item| acc.checked_add(item.amount))
-                .ok_or(VestingError::OverflowAmount)?;
-        
-        let vesting_record = VestingRecord {
-            account_type: VestingAccountType::VestingRecord,
+        let mut vesting_record = VestingRecordV2 {
+            account_type: VestingAccountType::VestingRecordV2,
             owner: *vesting_owner_account.key,
             mint: vesting_token_account_data.mint,
             token: *vesting_token_account.key,
             realm: realm_info.map(|v| *v.0.key),
-            schedule: schedules
+            schedule: schedules,
+            linear: linear_schedule.map(|schedule| LinearVesting {schedule, withdrawn_amount: 0}),
+            revoke_authority,
+            proposed_owner: None,
+            creator: *source_token_account_owner.key,
+            created_at: Clock::get()?.unix_timestamp as u64,
+            original_total: 0,
+            time_base,
+            frozen: false,
+            allow_crank_withdraw: false,
+            manager: None,
+            reserved: [0; VESTING_RECORD_RESERVED_SIZE],
         };
-        create_and_serialize_account_signed::<VestingRecord>(
+        let total_amount = vesting_record.total_amount()?;
+        vesting_record.original_total = total_amount;
+        
+        create_and_serialize_account_signed::<VestingRecordV2>(
             payer_account,
             vesting_account,
             &vesting_record,
@@ -109,26 +151,36 @@ impl Processor {
             &Rent::get()?,
         )?;
 
-        if Account::unpack(&source_token_account.data.borrow())?.amount < total_amount {
+        if get_token_account_data(source_token_account)?.amount < total_amount {
             return Err(VestingError::InsufficientFunds.into());
         };
 
         invoke_transfer_signed(
             spl_token_account,
             source_token_account,
+            mint,
             vesting_token_account,
             source_token_account_owner,
             total_amount,
             &[]
         )?;
 
-        if let Some((realm_account, voter_weight_record_account, max_voter_weight_record_account)) = realm_info {
-            create_or_increase_voter_weight_record(
+        let voter_weight = if let Some((realm_account, voter_weight_record_account, max_voter_weight_record_account, realm_config_account)) = realm_info {
+            let (lockup, vote_percentage_policy) = get_realm_voting_config(
+                program_id,
+                realm_config_account,
+                realm_account.key,
+                &vesting_token_account_data.mint,
+            )?;
+            let weighted_amount = get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &Clock::get()?)?;
+            let (voter_weight, weighted_amount) = create_or_increase_voter_weight_record(
                 &realm_account.key,
                 &vesting_token_account_data.mint,
                 vesting_owner_account.key,
                 voter_weight_record_account,
                 total_amount,
+                Some(weighted_amount),
+                vote_percentage_policy.as_ref(),
                 program_id,
                 system_program_account,
                 payer_account
@@ -138,7 +190,180 @@ impl Processor {
                 &realm_account.key,
                 &vesting_token_account_data.mint,
                 max_voter_weight_record_account,
+                weighted_amount,
+                program_id,
+                system_program_account,
+                payer_account
+            )?;
+            Some(voter_weight)
+        } else {
+            None
+        };
+
+        VestingEvent::Deposited {
+            vesting: *vesting_account.key,
+            owner: *vesting_owner_account.key,
+            amount: total_amount,
+            voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_batch_deposit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        schedules: Vec<Vec<VestingSchedule>>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let source_token_account_owner = next_account_info(accounts_iter)?;
+        let source_token_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let mut deposit_accounts = Vec::with_capacity(schedules.len());
+        for _ in 0..schedules.len() {
+            let vesting_account = next_account_info(accounts_iter)?;
+            let vesting_token_account = next_account_info(accounts_iter)?;
+            let vesting_owner_account = next_account_info(accounts_iter)?;
+            deposit_accounts.push((vesting_account, vesting_token_account, vesting_owner_account));
+        }
+
+        let source_token_account_data = get_token_account_data(source_token_account)?;
+        let (accounts, mint_account) = split_mint_account(accounts_iter.as_slice(), &source_token_account_data.mint);
+        let accounts_iter = &mut accounts.iter();
+
+        let realm_info = if let Some(realm) = accounts_iter.next() {
+            let max_voter_weight = next_account_info(accounts_iter)?;
+            let voter_weights = (0..schedules.len())
+                    .map(|_| next_account_info(accounts_iter))
+                    .collect::<Result<Vec<_>, _>>()?;
+            let realm_config = next_account_info(accounts_iter)?;
+            Some((realm, max_voter_weight, voter_weights, realm_config,))
+        } else {
+            None
+        };
+
+        if !source_token_account_owner.is_signer {
+            return Err(VestingError::MissingRequiredSigner.into());
+        }
+
+        let mint = get_transfer_mint(spl_token_account, mint_account, &source_token_account_data.mint)?;
+        let clock = Clock::get()?;
+        let created_at = clock.unix_timestamp as u64;
+
+        let (lockup, vote_percentage_policy) = match &realm_info {
+            Some((realm_account, _, _, realm_config_account)) => get_realm_voting_config(
+                program_id,
+                realm_config_account,
+                realm_account.key,
+                &source_token_account_data.mint,
+            )?,
+            None => (None, None),
+        };
+
+        let mut total_amounts = Vec::with_capacity(schedules.len());
+        let mut weighted_amounts = Vec::with_capacity(schedules.len());
+        for ((vesting_account, vesting_token_account, vesting_owner_account), schedules) in deposit_accounts.iter().zip(schedules) {
+            verify_schedule(&schedules)?;
+
+            let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+            verify_token_account_owned_by_vesting(vesting_account, vesting_token_account_data)?;
+            if vesting_token_account_data.mint != source_token_account_data.mint {
+                return Err(VestingError::InvalidVestingTokenAccount.into());
+            }
+
+            let mut vesting_record = VestingRecordV2 {
+                account_type: VestingAccountType::VestingRecordV2,
+                owner: *vesting_owner_account.key,
+                mint: vesting_token_account_data.mint,
+                token: *vesting_token_account.key,
+                realm: realm_info.as_ref().map(|v| *v.0.key),
+                schedule: schedules,
+                linear: None,
+                revoke_authority: None,
+                proposed_owner: None,
+                creator: *source_token_account_owner.key,
+                created_at,
+                original_total: 0,
+                time_base: VestingTimeBase::UnixTimestamp,
+                frozen: false,
+                allow_crank_withdraw: false,
+                manager: None,
+                reserved: [0; VESTING_RECORD_RESERVED_SIZE],
+            };
+            let total_amount = vesting_record.total_amount()?;
+            vesting_record.original_total = total_amount;
+
+            create_and_serialize_account_signed::<VestingRecordV2>(
+                payer_account,
+                vesting_account,
+                &vesting_record,
+                &[vesting_token_account.key.as_ref()],
+                program_id,
+                system_program_account,
+                &Rent::get()?,
+            )?;
+            total_amounts.push(total_amount);
+            weighted_amounts.push(get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &clock)?);
+        }
+
+        let batch_total_amount = total_amounts.iter()
+                .try_fold(0u64, |acc, amount| acc.checked_add(*amount))
+                .ok_or(VestingError::OverflowAmount)?;
+        if source_token_account_data.amount < batch_total_amount {
+            return Err(VestingError::InsufficientFunds.into());
+        };
+
+        let mut batch_weighted_amount = 0u64;
+        for (index, (vesting_account, vesting_token_account, vesting_owner_account)) in deposit_accounts.iter().enumerate() {
+            let total_amount = total_amounts[index];
+            invoke_transfer_signed(
+                spl_token_account,
+                source_token_account,
+                mint,
+                vesting_token_account,
+                source_token_account_owner,
                 total_amount,
+                &[]
+            )?;
+
+            let voter_weight = if let Some((realm_account, _, voter_weight_record_accounts, _)) = &realm_info {
+                let (voter_weight, weighted_amount) = create_or_increase_voter_weight_record(
+                    realm_account.key,
+                    &source_token_account_data.mint,
+                    vesting_owner_account.key,
+                    voter_weight_record_accounts[index],
+                    total_amount,
+                    Some(weighted_amounts[index]),
+                    vote_percentage_policy.as_ref(),
+                    program_id,
+                    system_program_account,
+                    payer_account
+                )?;
+                batch_weighted_amount = batch_weighted_amount.checked_add(weighted_amount).ok_or(VestingError::OverflowAmount)?;
+                Some(voter_weight)
+            } else {
+                None
+            };
+
+            VestingEvent::Deposited {
+                vesting: *vesting_account.key,
+                owner: *vesting_owner_account.key,
+                amount: total_amount,
+                voter_weight,
+            }.emit();
+        }
+
+        // MaxVoterWeightRecord is updated once for the whole batch
+        if let Some((realm_account, max_voter_weight_record_account, _, _)) = realm_info {
+            create_or_increase_max_voter_weight_record(
+                realm_account.key,
+                &source_token_account_data.mint,
+                max_voter_weight_record_account,
+                batch_weighted_amount,
                 program_id,
                 system_program_account,
                 payer_account
@@ -151,6 +376,8 @@ impl Processor {
     pub fn process_withdraw(
         program_id: &Pubkey,
         _accounts: &[AccountInfo],
+        amount: Option<u64>,
+        crank: bool,
     ) -> ProgramResult {
         let accounts_iter = &mut _accounts.iter();
 
@@ -160,12 +387,17 @@ impl Processor {
         let destination_token_account = next_account_info(accounts_iter)?;
         let vesting_owner_account = next_account_info(accounts_iter)?;
 
+        let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+        let (accounts, mint_account) = split_mint_account(accounts_iter.as_slice(), &vesting_token_account_data.mint);
+        let accounts_iter = &mut accounts.iter();
+
         let realm_info = if let Some(governance) = accounts_iter.next() {
             let realm = next_account_info(accounts_iter)?;
             let owner_record = next_account_info(accounts_iter)?;
             let voter_weight = next_account_info(accounts_iter)?;
             let max_voter_weight = next_account_info(accounts_iter)?;
-            Some((governance, realm, owner_record, voter_weight, max_voter_weight,))
+            let realm_config = accounts_iter.next();
+            Some((governance, realm, owner_record, voter_weight, max_voter_weight, realm_config,))
         } else {
             None
         };
@@ -175,21 +407,26 @@ impl Processor {
             return Err(VestingError::InvalidVestingAccount.into());
         }
 
-        let mut vesting_record = get_account_data::<VestingRecord>(program_id, vesting_account)?;
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
-        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        if crank {
+            verify_crank_withdraw(&vesting_record, vesting_owner_account, spl_token_account, destination_token_account)?;
+        } else {
+            verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        }
+        verify_vesting_not_frozen(&vesting_record)?;
         verify_vesting_token_account(&vesting_record, vesting_token_account, vesting_token_account_data, vesting_account_key)?;
+        let mint = get_transfer_mint(spl_token_account, mint_account, &vesting_record.mint)?;
 
         // Unlock the schedules that have reached maturity
+        let previous_vesting_record = vesting_record.clone();
         let clock = Clock::get()?;
-        let mut total_amount_to_transfer = 0u64;
-        for s in vesting_record.schedule.iter_mut() {
-            if clock.unix_timestamp as u64 >= s.release_time {
-                total_amount_to_transfer = total_amount_to_transfer.checked_add(s.amount)
-                        .ok_or(VestingError::OverflowAmount)?;
-                s.amount = 0;
-            }
-        }
+        let time = vesting_record.time_base.current_time(&clock);
+        let total_amount_to_transfer = if let Some(amount) = amount {
+            vesting_record.release_amount(time, amount)?;
+            amount
+        } else {
+            vesting_record.release_matured(time)?
+        };
         if total_amount_to_transfer == 0 {
             return Err(VestingError::NotReachedReleaseTime.into());
         }
@@ -197,6 +434,7 @@ impl Processor {
         invoke_transfer_signed(
             spl_token_account,
             vesting_token_account,
+            mint,
             destination_token_account,
             vesting_account,
             total_amount_to_transfer,
@@ -206,12 +444,13 @@ impl Processor {
         // Reset released amounts to 0. This makes the simple unlock safe with complex scheduling contracts
         vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
 
-        if let Some(expected_realm_account) = vesting_record.realm {
+        let voter_weight = if let Some(expected_realm_account) = vesting_record.realm {
             let (governance_account,
                  realm_account,
                  owner_record_account,
                  voter_weight_record_account,
-                 max_voter_weight_record_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
+                 max_voter_weight_record_account,
+                 realm_config_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
 
             if *realm_account.key != expected_realm_account {
                 return Err(VestingError::InvalidRealmAccount.into())
@@ -233,67 +472,104 @@ impl Processor {
                 owner_record_data.assert_can_withdraw_governing_tokens()?;
             }
 
-            let mut voter_weight_record = get_voter_weight_record_data_checked(
-                    program_id,
-                    voter_weight_record_account,
-                    realm_account.key,
-                    &vesting_record.mint,
-                    vesting_owner_account.key)?;
-
-            voter_weight_record.decrease_total_amount(total_amount_to_transfer)?;
-            voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+            let weighted_amount = get_optional_realm_lockup(program_id, realm_config_account, realm_account.key, &vesting_record.mint)?
+                    .map(|lockup| get_vesting_weight_decrease(&previous_vesting_record, &vesting_record, lockup.as_ref(), &clock))
+                    .transpose()?;
+            let (voter_weight, weighted_amount) = decrease_voter_weight_record(
+                realm_account.key,
+                &vesting_record.mint,
+                vesting_owner_account.key,
+                voter_weight_record_account,
+                total_amount_to_transfer,
+                weighted_amount,
+                program_id,
+            )?;
 
-            let mut max_voter_weight_record = get_max_voter_weight_record_data_checked(
-                    program_id,
-                    max_voter_weight_record_account,
-                    realm_account.key,
-                    &vesting_record.mint)?;
+            decrease_max_voter_weight_record(
+                realm_account.key,
+                &vesting_record.mint,
+                max_voter_weight_record_account,
+                weighted_amount,
+                program_id,
+            )?;
+            Some(voter_weight)
+        } else {
+            None
+        };
 
-            let max_voter_weight = &mut max_voter_weight_record.max_voter_weight;
-            *max_voter_weight = max_voter_weight.checked_sub(total_amount_to_transfer).ok_or(VestingError::UnderflowAmount)?;
-            max_voter_weight_record.serialize(&mut *max_voter_weight_record_account.data.borrow_mut())?;
-        }
+        VestingEvent::Withdrawn {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            amount: total_amount_to_transfer,
+            voter_weight,
+        }.emit();
 
         Ok(())
     }
 
-    pub fn process_change_owner(
+    pub fn process_revoke(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
 
+        let spl_token_account = next_account_info(accounts_iter)?;
         let vesting_account = next_account_info(accounts_iter)?;
-        let vesting_owner_account = next_account_info(accounts_iter)?;
-        let new_vesting_owner_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let revoke_authority_account = next_account_info(accounts_iter)?;
+
+        let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+        let (accounts, mint_account) = split_mint_account(accounts_iter.as_slice(), &vesting_token_account_data.mint);
+        let accounts_iter = &mut accounts.iter();
+
         let realm_info = if let Some(governance) = accounts_iter.next() {
             let realm = next_account_info(accounts_iter)?;
-            let current_owner_record = next_account_info(accounts_iter)?;
-            let current_voter_weight = next_account_info(accounts_iter)?;
-            let new_voter_weight = next_account_info(accounts_iter)?;
-            Some((governance, realm, current_owner_record, current_voter_weight, new_voter_weight,))
+            let voter_weight = next_account_info(accounts_iter)?;
+            let max_voter_weight = next_account_info(accounts_iter)?;
+            let realm_config = next_account_info(accounts_iter)?;
+            let delegate_voter_weight = accounts_iter.next();
+            Some((governance, realm, voter_weight, max_voter_weight, realm_config, delegate_voter_weight,))
         } else {
             None
         };
 
-        msg!("Change owner {} -> {}", vesting_owner_account.key, new_vesting_owner_account.key);
+        let (vesting_account_key, vesting_account_seed) = Pubkey::find_program_address(&[vesting_token_account.key.as_ref()], program_id);
+        if vesting_account_key != *vesting_account.key {
+            return Err(VestingError::InvalidVestingAccount.into());
+        }
 
-        let mut vesting_record = get_account_data::<VestingRecord>(program_id, vesting_account)?;
-        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_revoke_authority(&vesting_record, revoke_authority_account)?;
+        verify_vesting_token_account(&vesting_record, vesting_token_account, vesting_token_account_data, vesting_account_key)?;
+        let mint = get_transfer_mint(spl_token_account, mint_account, &vesting_record.mint)?;
 
-        let total_amount = vesting_record.schedule.iter()
-                .try_fold(0u64, |acc, item| acc.checked_add(item.amount))
-                .ok_or(VestingError::OverflowAmount)?;
+        let previous_vesting_record = vesting_record.clone();
+        let clock = Clock::get()?;
+        let time = vesting_record.time_base.current_time(&clock);
+        let total_amount_to_transfer = vesting_record.revoke_unreleased(time)?;
+
+        invoke_transfer_signed(
+            spl_token_account,
+            vesting_token_account,
+            mint,
+            destination_token_account,
+            vesting_account,
+            total_amount_to_transfer,
+            &[&[vesting_token_account.key.as_ref(), &[vesting_account_seed]]],
+        )?;
 
-        vesting_record.owner = *new_vesting_owner_account.key;
         vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
 
-        if let Some(expected_realm_account) = vesting_record.realm {
+        // Votes of the vesting owner are not checked and revoked tokens are undelegated:
+        // the owner can't block revocation
+        let voter_weight = if let Some(expected_realm_account) = vesting_record.realm {
             let (governance_account,
                  realm_account,
-                 owner_record_account,
                  voter_weight_record_account,
-                 new_voter_weight_record_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
+                 max_voter_weight_record_account,
+                 realm_config_account,
+                 delegate_voter_weight_record_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
 
             if *realm_account.key != expected_realm_account {
                 return Err(VestingError::InvalidRealmAccount.into())
@@ -302,99 +578,1403 @@ impl Processor {
             let realm_data = get_realm_data(governance_account.key, realm_account)?;
             realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
 
-            let owner_record_optional_data = get_token_owner_record_data_if_exists(
-                governance_account.key,
-                owner_record_account,
-                &get_token_owner_record_address_seeds(
-                    realm_account.key,
-                    &vesting_record.mint,
-                    vesting_owner_account.key,
-                ),
-            )?;
-            if let Some(owner_record_data) = owner_record_optional_data {
-                owner_record_data.assert_can_withdraw_governing_tokens()?;
-            }
-
             let mut voter_weight_record = get_voter_weight_record_data_checked(
                     program_id,
                     voter_weight_record_account,
                     realm_account.key,
                     &vesting_record.mint,
-                    vesting_owner_account.key)?;
-
-            voter_weight_record.decrease_total_amount(total_amount)?;
+                    &vesting_record.owner)?;
+            undelegate_removed_amount(
+                program_id,
+                &mut voter_weight_record,
+                total_amount_to_transfer,
+                delegate_voter_weight_record_account,
+                None,
+            )?;
+            let (lockup, _) = get_realm_voting_config(program_id, realm_config_account, realm_account.key, &vesting_record.mint)?;
+            let weighted_amount = get_vesting_weight_decrease(&previous_vesting_record, &vesting_record, lockup.as_ref(), &clock)?;
+            let weighted_amount = voter_weight_record.decrease_total_amount(total_amount_to_transfer, Some(weighted_amount))?;
             voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+            let voter_weight = voter_weight_record.base.voter_weight;
 
-            let mut new_voter_weight_record = get_voter_weight_record_data_checked(
-                    program_id,
-                    new_voter_weight_record_account,
-                    realm_account.key,
-                    &vesting_record.mint,
-                    new_vesting_owner_account.key)?;
-
-            new_voter_weight_record.increase_total_amount(total_amount)?;
-            new_voter_weight_record.serialize(&mut *new_voter_weight_record_account.data.borrow_mut())?;
+            decrease_max_voter_weight_record(
+                realm_account.key,
+                &vesting_record.mint,
+                max_voter_weight_record_account,
+                weighted_amount,
+                program_id,
+            )?;
+            Some(voter_weight)
+        } else {
+            None
+        };
 
-        }
+        VestingEvent::Revoked {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            revoke_authority: *revoke_authority_account.key,
+            destination: *destination_token_account.key,
+            amount: total_amount_to_transfer,
+            voter_weight,
+        }.emit();
 
         Ok(())
     }
 
-    pub fn process_create_voter_weight_record(
+    pub fn process_add_schedules(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
+        schedules: Vec<VestingSchedule>,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
 
         let system_program_account = next_account_info(accounts_iter)?;
-        let record_owner_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let source_token_account_owner = next_account_info(accounts_iter)?;
+        let source_token_account = next_account_info(accounts_iter)?;
         let payer_account = next_account_info(accounts_iter)?;
-        let realm_account = next_account_info(accounts_iter)?;
-        let mint_account = next_account_info(accounts_iter)?;
-        let voter_weight_record_account = next_account_info(accounts_iter)?;
 
-        create_voter_weight_record(
-            program_id,
-            realm_account.key,
-            mint_account.key,
-            record_owner_account.key,
-            payer_account,
-            voter_weight_record_account,
-            system_program_account,
-            |_| {Ok(())},
-        )?;
+        let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+        let (accounts, mint_account) = split_mint_account(accounts_iter.as_slice(), &vesting_token_account_data.mint);
+        let accounts_iter = &mut accounts.iter();
 
-        Ok(())
-    }
+        let realm_info = if let Some(realm) = accounts_iter.next() {
+            let voter_weight = next_account_info(accounts_iter)?;
+            let max_voter_weight = next_account_info(accounts_iter)?;
+            let realm_config = next_account_info(accounts_iter)?;
+            Some((realm, voter_weight, max_voter_weight, realm_config,))
+        } else {
+            None
+        };
 
-    pub fn process_set_vote_percentage(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        vote_percentage: u16,
-    ) -> ProgramResult {
-        let accounts_iter = &mut accounts.iter();
+        if !source_token_account_owner.is_signer {
+            return Err(VestingError::MissingRequiredSigner.into());
+        }
 
-        let vesting_mint_account = next_account_info(accounts_iter)?;
-        let vesting_owner_account = next_account_info(accounts_iter)?;
-        let vesting_authority_account = next_account_info(accounts_iter)?;
-        let governance_account = next_account_info(accounts_iter)?;
-        let realm_account = next_account_info(accounts_iter)?;
-        let owner_record_account = next_account_info(accounts_iter)?;
-        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        verify_schedule(&schedules)?;
 
-        let realm_data = get_realm_data(governance_account.key, realm_account)?;
-        realm_data.assert_is_valid_governing_token_mint(vesting_mint_account.key)?;
+        let (vesting_account_key, _) = Pubkey::find_program_address(&[vesting_token_account.key.as_ref()], program_id);
+        if vesting_account_key != *vesting_account.key {
+            return Err(VestingError::InvalidVestingAccount.into());
+        }
 
-        let owner_record_data = get_token_owner_record_data_for_seeds(
-            governance_account.key,
-            owner_record_account,
-            &get_token_owner_record_address_seeds(
-                realm_account.key,
-                vesting_mint_account.key,
-                vesting_owner_account.key,
-            ),
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_token_account(&vesting_record, vesting_token_account, vesting_token_account_data, vesting_account_key)?;
+        let mint = get_transfer_mint(spl_token_account, mint_account, &vesting_record.mint)?;
+
+        let previous_vesting_record = vesting_record.clone();
+        let total_amount = vesting_record.add_schedules(&schedules)?;
+        vesting_record.original_total = vesting_record.original_total.checked_add(total_amount)
+                .ok_or(VestingError::OverflowAmount)?;
+
+        serialize_account_with_realloc(
+            &vesting_record,
+            vesting_account,
+            payer_account,
+            system_program_account,
         )?;
-        owner_record_data.assert_token_owner_or_delegate_is_signer(vesting_authority_account)?;
+
+        if get_token_account_data(source_token_account)?.amount < total_amount {
+            return Err(VestingError::InsufficientFunds.into());
+        };
+
+        invoke_transfer_signed(
+            spl_token_account,
+            source_token_account,
+            mint,
+            vesting_token_account,
+            source_token_account_owner,
+            total_amount,
+            &[]
+        )?;
+
+        let voter_weight = if let Some(expected_realm_account) = vesting_record.realm {
+            let (realm_account,
+                 voter_weight_record_account,
+                 max_voter_weight_record_account,
+                 realm_config_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
+
+            if *realm_account.key != expected_realm_account {
+                return Err(VestingError::InvalidRealmAccount.into())
+            };
+
+            let (lockup, vote_percentage_policy) = get_realm_voting_config(
+                program_id,
+                realm_config_account,
+                realm_account.key,
+                &vesting_record.mint,
+            )?;
+            let clock = Clock::get()?;
+            let weighted_amount = get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &clock)?
+                    .saturating_sub(get_vesting_weighted_amount(&previous_vesting_record, lockup.as_ref(), &clock)?);
+            let (voter_weight, weighted_amount) = create_or_increase_voter_weight_record(
+                realm_account.key,
+                &vesting_record.mint,
+                &vesting_record.owner,
+                voter_weight_record_account,
+                total_amount,
+                Some(weighted_amount),
+                vote_percentage_policy.as_ref(),
+                program_id,
+                system_program_account,
+                payer_account
+            )?;
+
+            create_or_increase_max_voter_weight_record(
+                realm_account.key,
+                &vesting_record.mint,
+                max_voter_weight_record_account,
+                weighted_amount,
+                program_id,
+                system_program_account,
+                payer_account
+            )?;
+            Some(voter_weight)
+        } else {
+            None
+        };
+
+        VestingEvent::Deposited {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            amount: total_amount,
+            voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_merge(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let source_vesting_account = next_account_info(accounts_iter)?;
+        let source_vesting_token_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let spill_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+        let (_, mint_account) = split_mint_account(accounts_iter.as_slice(), &vesting_token_account_data.mint);
+
+        if vesting_token_account.key == source_vesting_token_account.key {
+            return Err(VestingError::IncompatibleVestings.into());
+        }
+
+        let (vesting_account_key, _) = Pubkey::find_program_address(&[vesting_token_account.key.as_ref()], program_id);
+        if vesting_account_key != *vesting_account.key {
+            return Err(VestingError::InvalidVestingAccount.into());
+        }
+
+        let (source_vesting_account_key, source_vesting_account_seed) = Pubkey::find_program_address(&[source_vesting_token_account.key.as_ref()], program_id);
+        if source_vesting_account_key != *source_vesting_account.key {
+            return Err(VestingError::InvalidVestingAccount.into());
+        }
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        verify_vesting_token_account(&vesting_record, vesting_token_account, vesting_token_account_data, vesting_account_key)?;
+
+        let source_vesting_record = get_account_data::<VestingRecordV2>(program_id, source_vesting_account)?;
+        let source_vesting_token_account_data = get_vesting_token_account_data(source_vesting_token_account)?;
+        verify_vesting_owner(&source_vesting_record, vesting_owner_account)?;
+        verify_vesting_not_frozen(&vesting_record)?;
+        verify_vesting_not_frozen(&source_vesting_record)?;
+        verify_vesting_token_account(&source_vesting_record, source_vesting_token_account, source_vesting_token_account_data, source_vesting_account_key)?;
+
+        // Owner and realm are the same, so voter weight records stay unchanged
+        if vesting_record.mint != source_vesting_record.mint ||
+           vesting_record.realm != source_vesting_record.realm ||
+           vesting_record.revoke_authority != source_vesting_record.revoke_authority ||
+           vesting_record.time_base != source_vesting_record.time_base {
+            return Err(VestingError::IncompatibleVestings.into());
+        }
+
+        // Legacy layout can't store the linear schedule
+        if vesting_record.account_type != VestingAccountType::VestingRecordV2 && source_vesting_record.linear.is_some() {
+            return Err(VestingError::VestingRecordNotMigrated.into());
+        }
+
+        let mint = get_transfer_mint(spl_token_account, mint_account, &vesting_record.mint)?;
+        let merged_amount = vesting_record.merge(&source_vesting_record)?;
+
+        serialize_account_with_realloc(
+            &vesting_record,
+            vesting_account,
+            payer_account,
+            system_program_account,
+        )?;
+
+        // Move all tokens from the source vesting spl-token account to be able to close it
+        let source_vesting_signer_seeds: &[&[u8]] = &[source_vesting_token_account.key.as_ref(), &[source_vesting_account_seed]];
+        invoke_transfer_signed(
+            spl_token_account,
+            source_vesting_token_account,
+            mint,
+            vesting_token_account,
+            source_vesting_account,
+            source_vesting_token_account_data.amount,
+            &[source_vesting_signer_seeds],
+        )?;
+
+        invoke_signed(
+            &close_account(
+                spl_token_account.key,
+                source_vesting_token_account.key,
+                spill_account.key,
+                source_vesting_account.key,
+                &[],
+            )?,
+            &[
+                spl_token_account.clone(),
+                source_vesting_token_account.clone(),
+                spill_account.clone(),
+                source_vesting_account.clone(),
+            ],
+            &[source_vesting_signer_seeds],
+        )?;
+
+        dispose_account(source_vesting_account, spill_account);
+
+        VestingEvent::Closed {
+            vesting: *source_vesting_account.key,
+            owner: source_vesting_record.owner,
+        }.emit();
+        VestingEvent::Merged {
+            source: *source_vesting_account.key,
+            target: *vesting_account.key,
+            amount: merged_amount,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_propose_owner(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let proposed_vesting_owner_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        msg!("Propose owner {} -> {}", vesting_owner_account.key, proposed_vesting_owner_account.key);
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        if vesting_record.account_type != VestingAccountType::VestingRecordV2 {
+            return Err(VestingError::VestingRecordNotMigrated.into());
+        }
+
+        vesting_record.proposed_owner = Some(*proposed_vesting_owner_account.key);
+        serialize_account_with_realloc(
+            &vesting_record,
+            vesting_account,
+            payer_account,
+            system_program_account,
+        )?;
+
+        VestingEvent::OwnerProposed {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            proposed_owner: vesting_record.proposed_owner,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_set_manager(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let authority_account = next_account_info(accounts_iter)?;
+        let manager_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, authority_account)?;
+        if vesting_record.account_type != VestingAccountType::VestingRecordV2 {
+            return Err(VestingError::VestingRecordNotMigrated.into());
+        }
+
+        vesting_record.manager = Some(*manager_account.key);
+        serialize_account_with_realloc(
+            &vesting_record,
+            vesting_account,
+            payer_account,
+            system_program_account,
+        )?;
+
+        VestingEvent::ManagerSet {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            manager: vesting_record.manager,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_clear_manager(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let authority_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner_or_manager(&vesting_record, authority_account)?;
+
+        // Record can't grow: the manager is removed
+        vesting_record.manager = None;
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        VestingEvent::ManagerSet {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            manager: None,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_cancel_owner_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+
+        if vesting_record.proposed_owner.is_none() {
+            return Err(VestingError::InvalidProposedOwner.into());
+        }
+
+        vesting_record.proposed_owner = None;
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        VestingEvent::OwnerProposed {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            proposed_owner: None,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_migrate_vesting_record(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        if vesting_record.account_type != VestingAccountType::VestingRecordV1 {
+            return Err(VestingError::VestingRecordAlreadyMigrated.into());
+        }
+
+        let legacy_record = get_account_data::<VestingRecordV1>(program_id, vesting_account)?;
+        let vesting_record = VestingRecordV2::migrate(legacy_record)?;
+        serialize_account_with_realloc(
+            &vesting_record,
+            vesting_account,
+            payer_account,
+            system_program_account,
+        )?;
+
+        VestingEvent::Migrated {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_attach_realm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+
+        if vesting_record.realm.is_some() {
+            return Err(VestingError::VestingIsUnderRealm.into());
+        }
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+        let total_amount = vesting_record.total_amount()?;
+
+        vesting_record.realm = Some(*realm_account.key);
+        serialize_account_with_realloc(
+            &vesting_record,
+            vesting_account,
+            payer_account,
+            system_program_account,
+        )?;
+
+        let (lockup, vote_percentage_policy) = get_realm_voting_config(
+            program_id,
+            realm_config_account,
+            realm_account.key,
+            &vesting_record.mint,
+        )?;
+        let weighted_amount = get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &Clock::get()?)?;
+        let (voter_weight, weighted_amount) = create_or_increase_voter_weight_record(
+            realm_account.key,
+            &vesting_record.mint,
+            vesting_owner_account.key,
+            voter_weight_record_account,
+            total_amount,
+            Some(weighted_amount),
+            vote_percentage_policy.as_ref(),
+            program_id,
+            system_program_account,
+            payer_account
+        )?;
+
+        create_or_increase_max_voter_weight_record(
+            realm_account.key,
+            &vesting_record.mint,
+            max_voter_weight_record_account,
+            weighted_amount,
+            program_id,
+            system_program_account,
+            payer_account
+        )?;
+
+        VestingEvent::VoterWeightChanged {
+            realm: *realm_account.key,
+            mint: vesting_record.mint,
+            owner: vesting_record.owner,
+            voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_detach_realm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let owner_record_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        // Frozen vesting can't leave the Realm of its freeze authority
+        verify_vesting_not_frozen(&vesting_record)?;
+
+        let expected_realm_account = vesting_record.realm.ok_or(VestingError::VestingIsNotUnderRealm)?;
+        if *realm_account.key != expected_realm_account {
+            return Err(VestingError::InvalidRealmAccount.into())
+        };
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+        let owner_record_optional_data = get_token_owner_record_data_if_exists(
+            governance_account.key,
+            owner_record_account,
+            &get_token_owner_record_address_seeds(
+                realm_account.key,
+                &vesting_record.mint,
+                vesting_owner_account.key,
+            ),
+        )?;
+        if let Some(owner_record_data) = owner_record_optional_data {
+            owner_record_data.assert_can_withdraw_governing_tokens()?;
+        }
+
+        let total_amount = vesting_record.total_amount()?;
+        let (lockup, _) = get_realm_voting_config(program_id, realm_config_account, realm_account.key, &vesting_record.mint)?;
+        let weighted_amount = get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &Clock::get()?)?;
+
+        // Record can't grow: the realm is removed
+        vesting_record.realm = None;
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        let (voter_weight, weighted_amount) = decrease_voter_weight_record(
+            realm_account.key,
+            &vesting_record.mint,
+            vesting_owner_account.key,
+            voter_weight_record_account,
+            total_amount,
+            Some(weighted_amount),
+            program_id,
+        )?;
+
+        decrease_max_voter_weight_record(
+            realm_account.key,
+            &vesting_record.mint,
+            max_voter_weight_record_account,
+            weighted_amount,
+            program_id,
+        )?;
+
+        VestingEvent::VoterWeightChanged {
+            realm: *realm_account.key,
+            mint: vesting_record.mint,
+            owner: vesting_record.owner,
+            voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_move_realm(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let owner_record_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let new_governance_account = next_account_info(accounts_iter)?;
+        let new_realm_account = next_account_info(accounts_iter)?;
+        let new_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let new_max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+        let new_realm_config_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        // Frozen vesting can't leave the Realm of its freeze authority
+        verify_vesting_not_frozen(&vesting_record)?;
+
+        let expected_realm_account = vesting_record.realm.ok_or(VestingError::VestingIsNotUnderRealm)?;
+        if *realm_account.key != expected_realm_account || *new_realm_account.key == expected_realm_account {
+            return Err(VestingError::InvalidRealmAccount.into())
+        };
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+        let owner_record_optional_data = get_token_owner_record_data_if_exists(
+            governance_account.key,
+            owner_record_account,
+            &get_token_owner_record_address_seeds(
+                realm_account.key,
+                &vesting_record.mint,
+                vesting_owner_account.key,
+            ),
+        )?;
+        if let Some(owner_record_data) = owner_record_optional_data {
+            owner_record_data.assert_can_withdraw_governing_tokens()?;
+        }
+
+        let new_realm_data = get_realm_data(new_governance_account.key, new_realm_account)?;
+        new_realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+        let total_amount = vesting_record.total_amount()?;
+
+        // Record can't grow: the realm is replaced
+        vesting_record.realm = Some(*new_realm_account.key);
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        // Tokens are weighted with the lockup config of each Realm
+        let clock = Clock::get()?;
+        let (lockup, _) = get_realm_voting_config(program_id, realm_config_account, realm_account.key, &vesting_record.mint)?;
+        let weighted_amount = get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &clock)?;
+        let (voter_weight, weighted_amount) = decrease_voter_weight_record(
+            realm_account.key,
+            &vesting_record.mint,
+            vesting_owner_account.key,
+            voter_weight_record_account,
+            total_amount,
+            Some(weighted_amount),
+            program_id,
+        )?;
+
+        decrease_max_voter_weight_record(
+            realm_account.key,
+            &vesting_record.mint,
+            max_voter_weight_record_account,
+            weighted_amount,
+            program_id,
+        )?;
+
+        let (new_lockup, vote_percentage_policy) = get_realm_voting_config(
+            program_id,
+            new_realm_config_account,
+            new_realm_account.key,
+            &vesting_record.mint,
+        )?;
+        let new_weighted_amount = get_vesting_weighted_amount(&vesting_record, new_lockup.as_ref(), &clock)?;
+        let (new_voter_weight, new_weighted_amount) = create_or_increase_voter_weight_record(
+            new_realm_account.key,
+            &vesting_record.mint,
+            vesting_owner_account.key,
+            new_voter_weight_record_account,
+            total_amount,
+            Some(new_weighted_amount),
+            vote_percentage_policy.as_ref(),
+            program_id,
+            system_program_account,
+            payer_account
+        )?;
+
+        create_or_increase_max_voter_weight_record(
+            new_realm_account.key,
+            &vesting_record.mint,
+            new_max_voter_weight_record_account,
+            new_weighted_amount,
+            program_id,
+            system_program_account,
+            payer_account
+        )?;
+
+        VestingEvent::VoterWeightChanged {
+            realm: *realm_account.key,
+            mint: vesting_record.mint,
+            owner: vesting_record.owner,
+            voter_weight,
+        }.emit();
+        VestingEvent::VoterWeightChanged {
+            realm: *new_realm_account.key,
+            mint: vesting_record.mint,
+            owner: vesting_record.owner,
+            voter_weight: new_voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_compact(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let rent_receiver_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+
+        if !vesting_record.compact() {
+            return Err(VestingError::NothingToCompact.into());
+        }
+
+        serialize_account_with_shrink(
+            &vesting_record,
+            vesting_account,
+            rent_receiver_account,
+        )?;
+
+        VestingEvent::Compacted {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_set_lockup_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        lockup: Option<LockupConfig>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let realm_authority_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(mint_account.key)?;
+        verify_realm_authority(&realm_data.authority, realm_authority_account)?;
+
+        if let Some(lockup) = &lockup {
+            lockup.verify()?;
+        }
+
+        update_realm_config(
+            program_id,
+            realm_account.key,
+            mint_account.key,
+            realm_config_account,
+            payer_account,
+            system_program_account,
+            |realm_config| realm_config.lockup = lockup,
+        )
+    }
+
+    pub fn process_set_vote_percentage_policy(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        policy: Option<VotePercentagePolicy>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let realm_authority_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(mint_account.key)?;
+        verify_realm_authority(&realm_data.authority, realm_authority_account)?;
+
+        if let Some(policy) = &policy {
+            policy.verify()?;
+        }
+
+        update_realm_config(
+            program_id,
+            realm_account.key,
+            mint_account.key,
+            realm_config_account,
+            payer_account,
+            system_program_account,
+            |realm_config| realm_config.vote_percentage_policy = policy,
+        )
+    }
+
+    pub fn process_accelerate_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        from_time: u64,
+        shift: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let realm_config_account = next_account_info(accounts_iter)?;
+        let freeze_authority_account = next_account_info(accounts_iter)?;
+        let max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let vesting_accounts = accounts_iter.as_slice();
+        if vesting_accounts.len() % 2 != 0 {
+            return Err(VestingError::VestingAccountsMismatch.into());
+        }
+
+        let realm_config = get_account_data::<VestingRealmConfig>(program_id, realm_config_account)?;
+        if get_realm_config_address(program_id, &realm_config.realm, &realm_config.mint) != *realm_config_account.key {
+            return Err(VestingError::InvalidRealmConfigAccountAddress.into());
+        }
+        verify_freeze_authority(&realm_config.freeze_authority, freeze_authority_account)?;
+
+        let clock = Clock::get()?;
+        let mut vesting_keys: Vec<&Pubkey> = Vec::with_capacity(vesting_accounts.len() / 2);
+        let mut total_weighted_decrease = 0u64;
+        for vesting_accounts_pair in vesting_accounts.chunks(2) {
+            let (vesting_account, voter_weight_record_account) = (&vesting_accounts_pair[0], &vesting_accounts_pair[1]);
+            if vesting_keys.contains(&vesting_account.key) {
+                return Err(VestingError::VestingAccountsMismatch.into());
+            }
+            vesting_keys.push(vesting_account.key);
+
+            let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+            if vesting_record.account_type != VestingAccountType::VestingRecordV2 {
+                return Err(VestingError::VestingRecordNotMigrated.into());
+            }
+            if vesting_record.mint != realm_config.mint ||
+               vesting_record.realm != Some(realm_config.realm) {
+                return Err(VestingError::VestingAccountsMismatch.into());
+            }
+            verify_vesting_not_frozen(&vesting_record)?;
+
+            let mut voter_weight_record = get_voter_weight_record_data_checked(
+                    program_id,
+                    voter_weight_record_account,
+                    &realm_config.realm,
+                    &realm_config.mint,
+                    &vesting_record.owner)?;
+
+            // Accelerated entries can only be combined, so the record never grows
+            let time = vesting_record.time_base.current_time(&clock);
+            let weighted_amount_before = match &realm_config.lockup {
+                Some(lockup) => vesting_record.lockup_weighted_amount(time, lockup)?,
+                None => 0,
+            };
+            vesting_record.accelerate(from_time, shift)?;
+            vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+            // The weight of the vesting can only decrease, so the voter weight is decreased
+            // by the exact change (the record may be weighted with the older lockup, so it's limited)
+            let voter_weight = if let Some(lockup) = &realm_config.lockup {
+                let weighted_amount_after = vesting_record.lockup_weighted_amount(time, lockup)?;
+                let weighted_decrease = voter_weight_record.decrease_weighted_amount(
+                        weighted_amount_before.saturating_sub(weighted_amount_after))?;
+                voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+                total_weighted_decrease = total_weighted_decrease.checked_add(weighted_decrease).ok_or(VestingError::OverflowAmount)?;
+                Some(voter_weight_record.base.voter_weight)
+            } else {
+                None
+            };
+
+            VestingEvent::ScheduleAccelerated {
+                vesting: *vesting_account.key,
+                owner: vesting_record.owner,
+                from_time,
+                shift,
+                voter_weight,
+            }.emit();
+        }
+
+        decrease_max_voter_weight_record(
+            &realm_config.realm,
+            &realm_config.mint,
+            max_voter_weight_record_account,
+            total_weighted_decrease,
+            program_id,
+        )
+    }
+
+    pub fn process_set_freeze_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        freeze_authority: Option<Pubkey>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let realm_authority_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(mint_account.key)?;
+        verify_realm_authority(&realm_data.authority, realm_authority_account)?;
+
+        if let Some(freeze_authority) = freeze_authority {
+            let freeze_authority_account = next_account_info(accounts_iter)?;
+            if *freeze_authority_account.key != freeze_authority {
+                return Err(VestingError::InvalidFreezeAuthority.into());
+            }
+            get_governance_data_for_realm(governance_account.key, freeze_authority_account, realm_account.key)?;
+        }
+
+        update_realm_config(
+            program_id,
+            realm_account.key,
+            mint_account.key,
+            realm_config_account,
+            payer_account,
+            system_program_account,
+            |realm_config| realm_config.freeze_authority = freeze_authority,
+        )
+    }
+
+    pub fn process_set_frozen(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        frozen: bool,
+        whole_owner: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let realm_config_account = next_account_info(accounts_iter)?;
+        let freeze_authority_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = if whole_owner {
+            Some(next_account_info(accounts_iter)?)
+        } else {
+            None
+        };
+        let vesting_accounts = accounts_iter.as_slice();
+
+        let realm_config = get_account_data::<VestingRealmConfig>(program_id, realm_config_account)?;
+        if get_realm_config_address(program_id, &realm_config.realm, &realm_config.mint) != *realm_config_account.key {
+            return Err(VestingError::InvalidRealmConfigAccountAddress.into());
+        }
+        verify_freeze_authority(&realm_config.freeze_authority, freeze_authority_account)?;
+
+        let voter_weight_record = if let Some(voter_weight_record_account) = voter_weight_record_account {
+            let voter_weight_record = get_voter_weight_record_data(program_id, voter_weight_record_account)?;
+            if voter_weight_record.base.realm != realm_config.realm ||
+               voter_weight_record.base.governing_token_mint != realm_config.mint {
+                return Err(VestingError::InvalidVoterWeightRecordLinkage.into());
+            }
+            let owner = voter_weight_record.base.governing_token_owner;
+            if get_voter_weight_record_address(program_id, &realm_config.realm, &realm_config.mint, &owner) != *voter_weight_record_account.key {
+                return Err(VestingError::InvalidVoterWeightRecordAccountAddress.into());
+            }
+            Some(voter_weight_record)
+        } else {
+            None
+        };
+
+        let mut vesting_keys: Vec<&Pubkey> = Vec::with_capacity(vesting_accounts.len());
+        let mut total_amount = 0u64;
+        for vesting_account in vesting_accounts {
+            if vesting_keys.contains(&vesting_account.key) {
+                return Err(VestingError::VestingAccountsMismatch.into());
+            }
+            vesting_keys.push(vesting_account.key);
+
+            let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+            if vesting_record.account_type != VestingAccountType::VestingRecordV2 {
+                return Err(VestingError::VestingRecordNotMigrated.into());
+            }
+            if vesting_record.mint != realm_config.mint ||
+               vesting_record.realm != Some(realm_config.realm) {
+                return Err(VestingError::VestingAccountsMismatch.into());
+            }
+            if let Some(voter_weight_record) = &voter_weight_record {
+                if vesting_record.owner != voter_weight_record.base.governing_token_owner {
+                    return Err(VestingError::VestingAccountsMismatch.into());
+                }
+                total_amount = total_amount.checked_add(vesting_record.total_amount()?).ok_or(VestingError::OverflowAmount)?;
+            }
+
+            vesting_record.frozen = frozen;
+            vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+            VestingEvent::FrozenSet {
+                vesting: *vesting_account.key,
+                owner: vesting_record.owner,
+                frozen,
+            }.emit();
+        }
+
+        // Amounts of all owner's vestings are accounted in the voter weight record
+        if let Some(voter_weight_record) = &voter_weight_record {
+            if total_amount != voter_weight_record.total_amount {
+                return Err(VestingError::VestingAccountsMismatch.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn process_recalculate_voter_weight(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        weight_action: Option<(VoterWeightAction, Option<Pubkey>)>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let realm_config_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let owner_account = if weight_action.is_some() {
+            Some(next_account_info(accounts_iter)?)
+        } else {
+            None
+        };
+
+        let mut voter_weight_record = get_voter_weight_record_data(program_id, voter_weight_record_account)?;
+        let delegate_voter_weight_record_account = if voter_weight_record.delegate.is_some() {
+            Some(next_account_info(accounts_iter)?)
+        } else {
+            None
+        };
+        let vesting_accounts = accounts_iter.as_slice();
+        let (realm, mint, owner) = (
+            voter_weight_record.base.realm,
+            voter_weight_record.base.governing_token_mint,
+            voter_weight_record.base.governing_token_owner,
+        );
+        if get_voter_weight_record_address(program_id, &realm, &mint, &owner) != *voter_weight_record_account.key {
+            return Err(VestingError::InvalidVoterWeightRecordAccountAddress.into());
+        }
+
+        // Recalculation is permissionless, but only the owner can make the weight valid for an action
+        if let Some(owner_account) = owner_account {
+            if !owner_account.is_signer {
+                return Err(VestingError::MissingRequiredSigner.into());
+            }
+            if *owner_account.key != owner {
+                return Err(VestingError::InvalidVoterWeightRecordLinkage.into());
+            }
+        }
+
+        let realm_config = get_realm_config_data_if_exists(program_id, realm_config_account, &realm, &mint)?;
+        let (lockup, vote_percentage_policy) = realm_config
+            .map_or((None, None), |config| (config.lockup, config.vote_percentage_policy));
+
+        // Records created by deposits (or before the policy was set) are brought into line with the policy
+        if let Some(policy) = vote_percentage_policy {
+            voter_weight_record.vote_percentage = policy.apply(voter_weight_record.vote_percentage);
+        }
+
+        let clock = Clock::get()?;
+        let weighted_amount = get_lockup_weighted_amount(
+            program_id,
+            &voter_weight_record,
+            lockup.as_ref(),
+            vesting_accounts,
+            &clock,
+        )?;
+
+        // MaxVoterWeightRecord keeps the sum of weighted amounts of all records
+        let mut max_voter_weight_record = get_max_voter_weight_record_data_checked(
+                program_id,
+                max_voter_weight_record_account,
+                &realm,
+                &mint)?;
+        max_voter_weight_record.max_voter_weight = max_voter_weight_record.max_voter_weight
+                .checked_sub(voter_weight_record.weighted_amount).ok_or(VestingError::UnderflowAmount)?
+                .checked_add(weighted_amount).ok_or(VestingError::OverflowAmount)?;
+        max_voter_weight_record.serialize(&mut *max_voter_weight_record_account.data.borrow_mut())?;
+
+        voter_weight_record.recalculate_voter_weight_with_lockup(weighted_amount)?;
+
+        // The weight delegated by the owner follows the recalculated weighted amount
+        if let Some(delegate_voter_weight_record_account) = delegate_voter_weight_record_account {
+            let delegate = voter_weight_record.delegate.ok_or(VestingError::InvalidDelegate)?;
+            let mut delegate_voter_weight_record = get_voter_weight_record_data_checked(
+                    program_id,
+                    delegate_voter_weight_record_account,
+                    &realm,
+                    &mint,
+                    &delegate)?;
+            voter_weight_record.sync_delegated_weight(&mut delegate_voter_weight_record)?;
+            delegate_voter_weight_record.serialize(&mut *delegate_voter_weight_record_account.data.borrow_mut())?;
+        }
+
+        if let Some((action, target)) = weight_action {
+            voter_weight_record.set_weight_action(action, target, clock.slot);
+        }
+        voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+
+        VestingEvent::VoterWeightChanged {
+            realm,
+            mint,
+            owner,
+            voter_weight: voter_weight_record.base.voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_set_revoke_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        revoke_authority: Option<Pubkey>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let revoke_authority_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_revoke_authority(&vesting_record, revoke_authority_account)?;
+
+        // Record can't grow: the revoke authority is already set
+        vesting_record.revoke_authority = revoke_authority;
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        VestingEvent::RevokeAuthoritySet {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            revoke_authority,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_set_crank_withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        allow: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        if vesting_record.account_type != VestingAccountType::VestingRecordV2 {
+            return Err(VestingError::VestingRecordNotMigrated.into());
+        }
+
+        vesting_record.allow_crank_withdraw = allow;
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        VestingEvent::CrankWithdrawSet {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+            allow,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_change_owner(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        accept_proposal: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let new_vesting_owner_account = next_account_info(accounts_iter)?;
+        let realm_info = if let Some(governance) = accounts_iter.next() {
+            let realm = next_account_info(accounts_iter)?;
+            let current_owner_record = next_account_info(accounts_iter)?;
+            let current_voter_weight = next_account_info(accounts_iter)?;
+            let new_voter_weight = next_account_info(accounts_iter)?;
+            let realm_config = accounts_iter.next();
+            let delegate_voter_weight = accounts_iter.next();
+            let delegate_owner_record = accounts_iter.next();
+            Some((governance, realm, current_owner_record, current_voter_weight, new_voter_weight, realm_config, delegate_voter_weight, delegate_owner_record,))
+        } else {
+            None
+        };
+
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        if accept_proposal {
+            if vesting_record.owner != *vesting_owner_account.key {
+                return Err(VestingError::InvalidOwnerForVestingAccount.into());
+            }
+            verify_proposed_owner(&vesting_record, new_vesting_owner_account)?;
+        } else {
+            verify_vesting_owner_or_manager(&vesting_record, vesting_owner_account)?;
+        }
+        verify_vesting_not_frozen(&vesting_record)?;
+
+        // The signer may be the manager, so the current owner is taken from the record
+        let old_owner = vesting_record.owner;
+        msg!("Change owner {} -> {}", old_owner, new_vesting_owner_account.key);
+
+        let total_amount = vesting_record.total_amount()?;
+
+        vesting_record.owner = *new_vesting_owner_account.key;
+        vesting_record.proposed_owner = None;
+        // The manager and the crank withdraw permission were granted by the previous owner
+        vesting_record.manager = None;
+        vesting_record.allow_crank_withdraw = false;
+        vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
+
+        let (old_owner_voter_weight, new_owner_voter_weight) = if let Some(expected_realm_account) = vesting_record.realm {
+            let (governance_account,
+                 realm_account,
+                 owner_record_account,
+                 voter_weight_record_account,
+                 new_voter_weight_record_account,
+                 realm_config_account,
+                 delegate_voter_weight_record_account,
+                 delegate_owner_record_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
+
+            if *realm_account.key != expected_realm_account {
+                return Err(VestingError::InvalidRealmAccount.into())
+            };
+
+            let realm_data = get_realm_data(governance_account.key, realm_account)?;
+            realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+            let owner_record_optional_data = get_token_owner_record_data_if_exists(
+                governance_account.key,
+                owner_record_account,
+                &get_token_owner_record_address_seeds(
+                    realm_account.key,
+                    &vesting_record.mint,
+                    &old_owner,
+                ),
+            )?;
+            if let Some(owner_record_data) = owner_record_optional_data {
+                owner_record_data.assert_can_withdraw_governing_tokens()?;
+            }
+
+            let mut voter_weight_record = get_voter_weight_record_data_checked(
+                    program_id,
+                    voter_weight_record_account,
+                    realm_account.key,
+                    &vesting_record.mint,
+                    &old_owner)?;
+
+            undelegate_removed_amount(
+                program_id,
+                &mut voter_weight_record,
+                total_amount,
+                delegate_voter_weight_record_account,
+                Some((governance_account.key, delegate_owner_record_account)),
+            )?;
+            // Moved tokens keep their weight, so MaxVoterWeightRecord doesn't change
+            let weighted_amount = get_optional_realm_lockup(program_id, realm_config_account, realm_account.key, &vesting_record.mint)?
+                    .map(|lockup| get_vesting_weighted_amount(&vesting_record, lockup.as_ref(), &Clock::get()?))
+                    .transpose()?;
+            let weighted_amount = voter_weight_record.decrease_total_amount(total_amount, weighted_amount)?;
+            voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+
+            let mut new_voter_weight_record = get_voter_weight_record_data_checked(
+                    program_id,
+                    new_voter_weight_record_account,
+                    realm_account.key,
+                    &vesting_record.mint,
+                    new_vesting_owner_account.key)?;
+
+            new_voter_weight_record.increase_total_amount(total_amount, weighted_amount)?;
+            new_voter_weight_record.serialize(&mut *new_voter_weight_record_account.data.borrow_mut())?;
+
+            (Some(voter_weight_record.base.voter_weight), Some(new_voter_weight_record.base.voter_weight))
+        } else {
+            (None, None)
+        };
+
+        VestingEvent::OwnerChanged {
+            vesting: *vesting_account.key,
+            old_owner,
+            new_owner: *new_vesting_owner_account.key,
+            amount: total_amount,
+            old_owner_voter_weight,
+            new_owner_voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_create_voter_weight_record(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let record_owner_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+
+        let vote_percentage_policy = get_vote_percentage_policy(program_id, realm_config_account, realm_account.key, mint_account.key)?;
+
+        // Existing legacy record is upgraded to the layout storing the weighted amount
+        if !voter_weight_record_account.data_is_empty() {
+            let mut voter_weight_record = get_voter_weight_record_data_checked(
+                    program_id,
+                    voter_weight_record_account,
+                    realm_account.key,
+                    mint_account.key,
+                    record_owner_account.key)?;
+            if voter_weight_record.account_discriminator != ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2 {
+                voter_weight_record.upgrade();
+                serialize_account_with_realloc(&voter_weight_record, voter_weight_record_account, payer_account, system_program_account)?;
+
+                VestingEvent::VoterWeightRecordUpgraded {
+                    realm: *realm_account.key,
+                    mint: *mint_account.key,
+                    owner: *record_owner_account.key,
+                }.emit();
+                return Ok(());
+            }
+        }
+
+        let voter_weight_record = create_voter_weight_record(
+            program_id,
+            realm_account.key,
+            mint_account.key,
+            record_owner_account.key,
+            payer_account,
+            voter_weight_record_account,
+            system_program_account,
+            |record| {
+                match vote_percentage_policy {
+                    Some(policy) => record.set_vote_percentage(policy.default_vote_percentage),
+                    None => Ok(()),
+                }
+            },
+        )?;
+
+        VestingEvent::VoterWeightRecordCreated {
+            realm: *realm_account.key,
+            mint: *mint_account.key,
+            owner: *record_owner_account.key,
+            vote_percentage: voter_weight_record.vote_percentage,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_set_vote_percentage(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        vote_percentage: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_mint_account = next_account_info(accounts_iter)?;
+        let vesting_owner_account = next_account_info(accounts_iter)?;
+        let vesting_authority_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let owner_record_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let realm_config_account = next_account_info(accounts_iter)?;
+
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(vesting_mint_account.key)?;
+
+        if let Some(policy) = get_vote_percentage_policy(program_id, realm_config_account, realm_account.key, vesting_mint_account.key)? {
+            policy.check(vote_percentage)?;
+        }
+
+        let owner_record_data = get_token_owner_record_data_for_seeds(
+            governance_account.key,
+            owner_record_account,
+            &get_token_owner_record_address_seeds(
+                realm_account.key,
+                vesting_mint_account.key,
+                vesting_owner_account.key,
+            ),
+        )?;
+        owner_record_data.assert_token_owner_or_delegate_is_signer(vesting_authority_account)?;
 
         let mut voter_weight_record = get_voter_weight_record_data_checked(
                 program_id,
@@ -406,6 +1986,14 @@ impl Processor {
         voter_weight_record.set_vote_percentage(vote_percentage)?;
         voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
 
+        VestingEvent::VotePercentageSet {
+            realm: *realm_account.key,
+            mint: *vesting_mint_account.key,
+            owner: *vesting_owner_account.key,
+            vote_percentage,
+            voter_weight: voter_weight_record.base.voter_weight,
+        }.emit();
+
         Ok(())
     }
 
@@ -421,24 +2009,76 @@ impl Processor {
         let vesting_owner_account = next_account_info(accounts_iter)?;
         let spill_account = next_account_info(accounts_iter)?;
 
+        let realm_info = if let Some(governance) = accounts_iter.next() {
+            let realm = next_account_info(accounts_iter)?;
+            let owner_record = next_account_info(accounts_iter)?;
+            let voter_weight = next_account_info(accounts_iter)?;
+            Some((governance, realm, owner_record, voter_weight,))
+        } else {
+            None
+        };
+
         let (vesting_account_key, vesting_account_seed) = Pubkey::find_program_address(&[vesting_token_account.key.as_ref()], program_id);
         if vesting_account_key != *vesting_account.key {
             return Err(VestingError::InvalidVestingAccount.into());
         }
 
-        let mut vesting_record = get_account_data::<VestingRecord>(program_id, vesting_account)?;
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
+        let vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        let vesting_token_account_data = get_token_account_data(vesting_token_account)?;
         verify_vesting_owner(&vesting_record, vesting_owner_account)?;
         verify_vesting_token_account(&vesting_record, vesting_token_account, vesting_token_account_data, vesting_account_key)?;
 
-        let mut total_amount = 0u64;
-        for s in vesting_record.schedule.iter_mut() {
-            total_amount = total_amount.checked_add(s.amount).ok_or(VestingError::OverflowAmount)?;
-        }
-        if total_amount != 0 {
+        if vesting_record.total_amount()? != 0 {
             return Err(VestingError::VestingNotEmpty.into());
         }
 
+        if let Some((governance_account,
+                     realm_account,
+                     owner_record_account,
+                     voter_weight_record_account)) = realm_info {
+            let expected_realm_account = vesting_record.realm.ok_or(VestingError::VestingIsNotUnderRealm)?;
+            if *realm_account.key != expected_realm_account {
+                return Err(VestingError::InvalidRealmAccount.into())
+            };
+
+            let realm_data = get_realm_data(governance_account.key, realm_account)?;
+            realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+            let voter_weight_record = get_voter_weight_record_data_checked(
+                    program_id,
+                    voter_weight_record_account,
+                    realm_account.key,
+                    &vesting_record.mint,
+                    vesting_owner_account.key)?;
+
+            // The record is still used for voting while the owner has outstanding votes or proposals
+            let owner_record_optional_data = get_token_owner_record_data_if_exists(
+                governance_account.key,
+                owner_record_account,
+                &get_token_owner_record_address_seeds(
+                    realm_account.key,
+                    &vesting_record.mint,
+                    vesting_owner_account.key,
+                ),
+            )?;
+            let has_outstanding_votes = owner_record_optional_data
+                .map_or(false, |owner_record_data| owner_record_data.assert_can_withdraw_governing_tokens().is_err());
+
+            // Delegated voting weight is still accounted in the records of the delegate or delegators
+            if voter_weight_record.total_amount == 0 &&
+               voter_weight_record.delegated_out == 0 &&
+               voter_weight_record.delegated_in == 0 &&
+               !has_outstanding_votes {
+                dispose_account(voter_weight_record_account, spill_account);
+
+                VestingEvent::VoterWeightRecordClosed {
+                    realm: *realm_account.key,
+                    mint: vesting_record.mint,
+                    owner: *vesting_owner_account.key,
+                }.emit();
+            }
+        }
+
         let release_token_account_instruction = if vesting_token_account_data.amount == 0 {
             close_account(
                 spl_token_account.key,
@@ -471,6 +2111,11 @@ impl Processor {
 
         dispose_account(vesting_account, spill_account);
 
+        VestingEvent::Closed {
+            vesting: *vesting_account.key,
+            owner: vesting_record.owner,
+        }.emit();
+
         Ok(())
     }
 
@@ -497,12 +2142,20 @@ impl Processor {
                 mint_account.key,
                 owner_account.key)?;
 
-        if voter_weight_record.total_amount != 0 {
+        if voter_weight_record.total_amount != 0 ||
+           voter_weight_record.delegated_out != 0 ||
+           voter_weight_record.delegated_in != 0 {
             return Err(VestingError::VoterWeightRecordNotEmpty.into());
         }
 
         dispose_account(voter_weight_record_account, spill_account);
 
+        VestingEvent::VoterWeightRecordClosed {
+            realm: *realm_account.key,
+            mint: *mint_account.key,
+            owner: *owner_account.key,
+        }.emit();
+
         Ok(())
     }
 
@@ -510,6 +2163,7 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         schedules: Vec<VestingSchedule>,
+        linear_schedule: Option<LinearVestingSchedule>,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
 
@@ -523,17 +2177,27 @@ impl Processor {
         let new_vesting_owner_account = next_account_info(accounts_iter)?;
         let payer_account = next_account_info(accounts_iter)?;
 
+        let vesting_token_account_data = get_vesting_token_account_data(vesting_token_account)?;
+        let (accounts, mint_account) = split_mint_account(accounts_iter.as_slice(), &vesting_token_account_data.mint);
+        let accounts_iter = &mut accounts.iter();
+
         let realm_info = if let Some(governance) = accounts_iter.next() {
             let realm = next_account_info(accounts_iter)?;
             let owner_record = next_account_info(accounts_iter)?;
             let voter_weight = next_account_info(accounts_iter)?;
             let new_voter_weight = next_account_info(accounts_iter)?;
-            Some((governance, realm, owner_record, voter_weight, new_voter_weight,))
+            let realm_config = next_account_info(accounts_iter)?;
+            let delegate_voter_weight = accounts_iter.next();
+            let delegate_owner_record = accounts_iter.next();
+            Some((governance, realm, owner_record, voter_weight, new_voter_weight, realm_config, delegate_voter_weight, delegate_owner_record,))
         } else {
             None
         };
 
         verify_schedule(&schedules)?;
+        if let Some(linear_schedule) = &linear_schedule {
+            linear_schedule.verify()?;
+        }
 
         let (vesting_account_key, vesting_account_seed) = Pubkey::find_program_address(&[vesting_token_account.key.as_ref()], program_id);
         if vesting_account_key != *vesting_account.key {
@@ -541,44 +2205,70 @@ impl Processor {
         }
 
         // ================== Verify accounts related to the existing vesting =====================
-        let mut vesting_record = get_account_data::<VestingRecord>(program_id, vesting_account)?;
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
-        verify_vesting_owner(&vesting_record, vesting_owner_account)?;
+        let mut vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        verify_vesting_owner_or_manager(&vesting_record, vesting_owner_account)?;
+        verify_vesting_not_frozen(&vesting_record)?;
         verify_vesting_token_account(&vesting_record, vesting_token_account, vesting_token_account_data, vesting_account_key)?;
 
         // ================== Verify accounts related to new vesting record =======================
-        let new_vesting_token_account_data = Account::unpack(&new_vesting_token_account.data.borrow())?;
+        let new_vesting_token_account_data = get_vesting_token_account_data(new_vesting_token_account)?;
         verify_token_account_owned_by_vesting(new_vesting_account, new_vesting_token_account_data)?;
+        if new_vesting_token_account_data.mint != vesting_record.mint {
+            return Err(VestingError::InvalidVestingTokenAccount.into());
+        }
+        let mint = get_transfer_mint(spl_token_account, mint_account, &vesting_record.mint)?;
 
         let mut total_amount_to_transfer = 0u64;
-        let mut source_schedule_iterator = vesting_record.schedule.iter_mut().rev();
-        let mut source_schedule = source_schedule_iterator.next().ok_or(VestingError::InsufficientFunds)?;
-        for item in (&schedules).into_iter().rev() {
-            let mut rest_amount = item.amount;
-            total_amount_to_transfer = total_amount_to_transfer.checked_add(item.amount)
-                    .ok_or(VestingError::OverflowAmount)?;
-            while rest_amount != 0 {
-                while item.release_time < source_schedule.release_time || source_schedule.amount == 0 {
-                    source_schedule = source_schedule_iterator.next()
-                        .ok_or(VestingError::InsufficientFunds)?;
+        let mut new_linear = None;
+        if let Some(linear_schedule) = &linear_schedule {
+            // Withdrawn amount is splitted proportionally, so only not withdrawn tokens are moved
+            let source_linear = vesting_record.linear.as_mut().ok_or(VestingError::InvalidSchedule)?;
+            let linear = source_linear.split(linear_schedule)?;
+            total_amount_to_transfer = linear.schedule.amount - linear.withdrawn_amount;
+            new_linear = Some(linear);
+        } else {
+            let mut source_schedule_iterator = vesting_record.schedule.iter_mut().rev();
+            let mut source_schedule = source_schedule_iterator.next().ok_or(VestingError::InsufficientFunds)?;
+            for item in (&schedules).into_iter().rev() {
+                let mut rest_amount = item.amount;
+                total_amount_to_transfer = total_amount_to_transfer.checked_add(item.amount)
+                        .ok_or(VestingError::OverflowAmount)?;
+                while rest_amount != 0 {
+                    while item.release_time < source_schedule.release_time || source_schedule.amount == 0 {
+                        source_schedule = source_schedule_iterator.next()
+                            .ok_or(VestingError::InsufficientFunds)?;
+                    }
+                    let available_amount = rest_amount.min(source_schedule.amount);
+                    source_schedule.amount -= available_amount;
+                    rest_amount -= available_amount;
                 }
-                let available_amount = rest_amount.min(source_schedule.amount);
-                source_schedule.amount -= available_amount;
-                rest_amount -= available_amount;
             }
         }
 
         vesting_record.serialize(&mut *vesting_account.data.borrow_mut())?;
 
-        let new_vesting_record = VestingRecord {
-            account_type: VestingAccountType::VestingRecord,
+        let new_vesting_record = VestingRecordV2 {
+            account_type: VestingAccountType::VestingRecordV2,
             owner: *new_vesting_owner_account.key,
             mint: new_vesting_token_account_data.mint,
             token: *new_vesting_token_account.key,
             realm: realm_info.map(|v| *v.1.key),
-            schedule: schedules
+            schedule: schedules,
+            linear: new_linear,
+            // Splitted part of revocable vesting stays revocable
+            revoke_authority: vesting_record.revoke_authority,
+            proposed_owner: None,
+            creator: *vesting_owner_account.key,
+            created_at: Clock::get()?.unix_timestamp as u64,
+            original_total: total_amount_to_transfer,
+            // Schedules of the new vesting are checked against the source ones, so they share the time base
+            time_base: vesting_record.time_base,
+            frozen: false,
+            allow_crank_withdraw: false,
+            manager: None,
+            reserved: [0; VESTING_RECORD_RESERVED_SIZE],
         };
-        create_and_serialize_account_signed::<VestingRecord>(
+        create_and_serialize_account_signed::<VestingRecordV2>(
             payer_account,
             new_vesting_account,
             &new_vesting_record,
@@ -591,59 +2281,358 @@ impl Processor {
         invoke_transfer_signed(
             spl_token_account,
             vesting_token_account,
+            mint,
             new_vesting_token_account,
             vesting_account,
             total_amount_to_transfer,
             &[&[vesting_token_account.key.as_ref(), &[vesting_account_seed]]]
         )?;
 
-        if let Some(expected_realm_account) = vesting_record.realm {
+        let (owner_voter_weight, new_owner_voter_weight) = if let Some(expected_realm_account) = vesting_record.realm {
             let (governance_account, 
                  realm_account,
                  owner_record_account,
                  voter_weight_record_account,
-                 new_voter_weight_record_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
+                 new_voter_weight_record_account,
+                 realm_config_account,
+                 delegate_voter_weight_record_account,
+                 delegate_owner_record_account) = realm_info.ok_or(VestingError::MissingRealmAccounts)?;
+
+            if *realm_account.key != expected_realm_account {
+                return Err(VestingError::InvalidRealmAccount.into())
+            };
+
+            let realm_data = get_realm_data(governance_account.key, realm_account)?;
+            realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+
+            let owner_record_optional_data = get_token_owner_record_data_if_exists(
+                governance_account.key,
+                owner_record_account,
+                &get_token_owner_record_address_seeds(
+                    realm_account.key,
+                    &vesting_record.mint,
+                    &vesting_record.owner,
+                ),
+            )?;
+            if let Some(owner_record_data) = owner_record_optional_data {
+                owner_record_data.assert_can_withdraw_governing_tokens()?;
+            }
+
+            let mut voter_weight_record = get_voter_weight_record_data_checked(
+                program_id,
+                voter_weight_record_account,
+                realm_account.key,
+                &vesting_record.mint,
+                &vesting_record.owner)?;
+            undelegate_removed_amount(
+                program_id,
+                &mut voter_weight_record,
+                total_amount_to_transfer,
+                delegate_voter_weight_record_account,
+                Some((governance_account.key, delegate_owner_record_account)),
+            )?;
+            // Moved tokens keep their weight (the weight of the new vesting), so MaxVoterWeightRecord doesn't change
+            let (lockup, vote_percentage_policy) = get_realm_voting_config(
+                program_id,
+                realm_config_account,
+                realm_account.key,
+                &vesting_record.mint,
+            )?;
+            let weighted_amount = get_vesting_weighted_amount(&new_vesting_record, lockup.as_ref(), &Clock::get()?)?;
+            let weighted_amount = voter_weight_record.decrease_total_amount(total_amount_to_transfer, Some(weighted_amount))?;
+            voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+
+            let (new_owner_voter_weight, _) = create_or_increase_voter_weight_record(
+                &realm_account.key,
+                &vesting_record.mint,
+                new_vesting_owner_account.key,
+                new_voter_weight_record_account,
+                total_amount_to_transfer,
+                Some(weighted_amount),
+                vote_percentage_policy.as_ref(),
+                program_id,
+                system_program_account,
+                payer_account)?;
+
+            (Some(voter_weight_record.base.voter_weight), Some(new_owner_voter_weight))
+        } else {
+            (None, None)
+        };
+
+        VestingEvent::Split {
+            vesting: *vesting_account.key,
+            new_vesting: *new_vesting_account.key,
+            owner: vesting_record.owner,
+            new_owner: *new_vesting_owner_account.key,
+            amount: total_amount_to_transfer,
+            owner_voter_weight,
+            new_owner_voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_reconcile_max_voter_weight(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        after: Option<Pubkey>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let max_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_accounts = accounts_iter.as_slice();
+
+        let max_voter_weight_record = get_max_voter_weight_record_data_checked_address(program_id, max_voter_weight_record_account)?;
+
+        // Ascending order of addresses guarantees that no record is counted twice (also in the previous calls)
+        let mut last_record = after;
+        let mut weighted_amount = 0u64;
+        for voter_weight_record_account in voter_weight_record_accounts {
+            if last_record.map_or(false, |last_record| *voter_weight_record_account.key <= last_record) {
+                return Err(VestingError::VoterWeightRecordsNotSorted.into());
+            }
+            last_record = Some(*voter_weight_record_account.key);
+
+            let voter_weight_record = get_voter_weight_record_data_checked_address(program_id, voter_weight_record_account)?;
+            if voter_weight_record.base.governing_token_mint != max_voter_weight_record.governing_token_mint ||
+               voter_weight_record.base.realm != max_voter_weight_record.realm {
+                return Err(VestingError::InvalidVoterWeightRecordLinkage.into());
+            }
+            weighted_amount = weighted_amount.checked_add(voter_weight_record.weighted_amount).ok_or(VestingError::OverflowAmount)?;
+        }
+
+        if weighted_amount != max_voter_weight_record.max_voter_weight {
+            msg!("Max voter weight mismatch: max_voter_weight {}, weighted amount {} in {} voter weight records",
+                    max_voter_weight_record.max_voter_weight, weighted_amount, voter_weight_record_accounts.len());
+        }
+
+        let reconciliation = MaxVoterWeightReconciliation {
+            max_voter_weight: max_voter_weight_record.max_voter_weight,
+            weighted_amount,
+            last_record,
+        };
+        set_return_data(&reconciliation.try_to_vec()?);
+
+        Ok(())
+    }
+
+    pub fn process_delegate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let owner_account = next_account_info(accounts_iter)?;
+        let owner_record_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let delegate_voter_weight_record_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        if !owner_account.is_signer {
+            return Err(VestingError::MissingRequiredSigner.into());
+        }
+
+        let mut voter_weight_record = get_voter_weight_record_data_checked_address(program_id, voter_weight_record_account)?;
+        if voter_weight_record.base.governing_token_owner != *owner_account.key {
+            return Err(VestingError::InvalidVoterWeightRecordLinkage.into());
+        }
+        let realm = voter_weight_record.base.realm;
+        let mint = voter_weight_record.base.governing_token_mint;
+
+        let mut delegate_voter_weight_record = get_voter_weight_record_data_checked_address(program_id, delegate_voter_weight_record_account)?;
+        if delegate_voter_weight_record.base.realm != realm ||
+           delegate_voter_weight_record.base.governing_token_mint != mint {
+            return Err(VestingError::InvalidVoterWeightRecordLinkage.into());
+        }
+
+        if *realm_account.key != realm {
+            return Err(VestingError::InvalidRealmAccount.into());
+        }
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(&mint)?;
+
+        // Delegation decreases voter weight of the owner, so it's not allowed while the owner has active votes
+        let owner_record_optional_data = get_token_owner_record_data_if_exists(
+            governance_account.key,
+            owner_record_account,
+            &get_token_owner_record_address_seeds(
+                realm_account.key,
+                &mint,
+                owner_account.key,
+            ),
+        )?;
+        if let Some(owner_record_data) = owner_record_optional_data {
+            owner_record_data.assert_can_withdraw_governing_tokens()?;
+        }
+
+        voter_weight_record.upgrade();
+        delegate_voter_weight_record.upgrade();
+        voter_weight_record.delegate_to(&mut delegate_voter_weight_record, amount)?;
+
+        serialize_account_with_realloc(&voter_weight_record, voter_weight_record_account, payer_account, system_program_account)?;
+        serialize_account_with_realloc(&delegate_voter_weight_record, delegate_voter_weight_record_account, payer_account, system_program_account)?;
+
+        VestingEvent::Delegated {
+            realm,
+            mint,
+            owner: *owner_account.key,
+            delegate: delegate_voter_weight_record.base.governing_token_owner,
+            amount,
+            owner_voter_weight: voter_weight_record.base.voter_weight,
+            delegate_voter_weight: delegate_voter_weight_record.base.voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_undelegate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let governance_account = next_account_info(accounts_iter)?;
+        let realm_account = next_account_info(accounts_iter)?;
+        let owner_account = next_account_info(accounts_iter)?;
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+        let delegate_record_account = next_account_info(accounts_iter)?;
+        let delegate_voter_weight_record_account = next_account_info(accounts_iter)?;
+
+        if !owner_account.is_signer {
+            return Err(VestingError::MissingRequiredSigner.into());
+        }
+
+        let mut voter_weight_record = get_voter_weight_record_data_checked_address(program_id, voter_weight_record_account)?;
+        if voter_weight_record.base.governing_token_owner != *owner_account.key {
+            return Err(VestingError::InvalidVoterWeightRecordLinkage.into());
+        }
+        let realm = voter_weight_record.base.realm;
+        let mint = voter_weight_record.base.governing_token_mint;
+        let delegate = voter_weight_record.delegate.ok_or(VestingError::InvalidDelegate)?;
+
+        let mut delegate_voter_weight_record = get_voter_weight_record_data_checked(
+                program_id,
+                delegate_voter_weight_record_account,
+                &realm,
+                &mint,
+                &delegate)?;
+
+        if *realm_account.key != realm {
+            return Err(VestingError::InvalidRealmAccount.into());
+        }
+        let realm_data = get_realm_data(governance_account.key, realm_account)?;
+        realm_data.assert_is_valid_governing_token_mint(&mint)?;
+
+        // Undelegation decreases voter weight of the delegate, so it's not allowed while the delegate has active votes
+        let delegate_record_optional_data = get_token_owner_record_data_if_exists(
+            governance_account.key,
+            delegate_record_account,
+            &get_token_owner_record_address_seeds(
+                realm_account.key,
+                &mint,
+                &delegate,
+            ),
+        )?;
+        if let Some(delegate_record_data) = delegate_record_optional_data {
+            delegate_record_data.assert_can_withdraw_governing_tokens()?;
+        }
+
+        voter_weight_record.undelegate_from(&mut delegate_voter_weight_record, amount)?;
+
+        voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+        delegate_voter_weight_record.serialize(&mut *delegate_voter_weight_record_account.data.borrow_mut())?;
+
+        VestingEvent::Undelegated {
+            realm,
+            mint,
+            owner: *owner_account.key,
+            delegate,
+            amount,
+            owner_voter_weight: voter_weight_record.base.voter_weight,
+            delegate_voter_weight: delegate_voter_weight_record.base.voter_weight,
+        }.emit();
+
+        Ok(())
+    }
+
+    pub fn process_get_withdrawable_amount(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
 
-            if *realm_account.key != expected_realm_account {
-                return Err(VestingError::InvalidRealmAccount.into())
-            };
+        let vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        let time = vesting_record.time_base.current_time(&Clock::get()?);
+        let withdrawable_amount = vesting_record.withdrawable_amount(time)?;
+        set_return_data(&withdrawable_amount.try_to_vec()?);
 
-            let realm_data = get_realm_data(governance_account.key, realm_account)?;
-            realm_data.assert_is_valid_governing_token_mint(&vesting_record.mint)?;
+        Ok(())
+    }
 
-            let owner_record_optional_data = get_token_owner_record_data_if_exists(
-                governance_account.key,
-                owner_record_account,
-                &get_token_owner_record_address_seeds(
-                    realm_account.key,
-                    &vesting_record.mint,
-                    vesting_owner_account.key,
-                ),
-            )?;
-            if let Some(owner_record_data) = owner_record_optional_data {
-                owner_record_data.assert_can_withdraw_governing_tokens()?;
+    pub fn process_get_vesting_summary(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        let mut expected_owner_and_mint: Option<(Pubkey, Pubkey)> = None;
+        let mut vesting_keys: Vec<&Pubkey> = Vec::with_capacity(accounts.len());
+        let mut summary = VestingSummary::default();
+        for vesting_account in accounts {
+            if vesting_keys.contains(&vesting_account.key) {
+                return Err(VestingError::VestingAccountsMismatch.into());
             }
+            vesting_keys.push(vesting_account.key);
 
-            let mut voter_weight_record = get_voter_weight_record_data_checked(
-                program_id,
-                voter_weight_record_account,
-                realm_account.key,
-                &vesting_record.mint,
-                vesting_owner_account.key)?;
-            voter_weight_record.decrease_total_amount(total_amount_to_transfer)?;
-            voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+            let vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+            let owner_and_mint = *expected_owner_and_mint.get_or_insert((vesting_record.owner, vesting_record.mint));
+            if owner_and_mint != (vesting_record.owner, vesting_record.mint) {
+                return Err(VestingError::VestingAccountsMismatch.into());
+            }
 
-            create_or_increase_voter_weight_record(
-                &realm_account.key,
-                &vesting_record.mint,
-                new_vesting_owner_account.key,
-                new_voter_weight_record_account,
-                total_amount_to_transfer,
-                program_id,
-                system_program_account,
-                payer_account)?;
+            let total_amount = vesting_record.total_amount()?;
+            let time = vesting_record.time_base.current_time(&clock);
+            summary.vesting_count = summary.vesting_count.checked_add(1).ok_or(VestingError::OverflowAmount)?;
+            summary.total_amount = summary.total_amount.checked_add(total_amount).ok_or(VestingError::OverflowAmount)?;
+            summary.withdrawable_amount = summary.withdrawable_amount
+                    .checked_add(vesting_record.withdrawable_amount(time)?).ok_or(VestingError::OverflowAmount)?;
+            if vesting_record.frozen {
+                summary.frozen_amount = summary.frozen_amount.checked_add(total_amount).ok_or(VestingError::OverflowAmount)?;
+            }
         }
 
+        set_return_data(&summary.try_to_vec()?);
+
+        Ok(())
+    }
+
+    pub fn process_get_voter_weight(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let voter_weight_record_account = next_account_info(accounts_iter)?;
+
+        let voter_weight_record = get_voter_weight_record_data_checked_address(program_id, voter_weight_record_account)?;
+        let voter_weight_info = VoterWeightInfo {
+            voter_weight: voter_weight_record.base.voter_weight,
+            voter_weight_expiry: voter_weight_record.base.voter_weight_expiry,
+            total_amount: voter_weight_record.total_amount,
+            vote_percentage: voter_weight_record.vote_percentage,
+            delegated_out: voter_weight_record.delegated_out,
+            delegated_in: voter_weight_record.delegated_in,
+            delegate: voter_weight_record.delegate,
+        };
+        set_return_data(&voter_weight_info.try_to_vec()?);
+
         Ok(())
     }
 
@@ -659,13 +2648,13 @@ impl Processor {
 
         match instruction {
             VestingInstruction::Deposit {schedules} => {
-                Self::process_deposit(program_id, accounts, schedules)
+                Self::process_deposit(program_id, accounts, schedules, None, None, VestingTimeBase::UnixTimestamp)
             }
             VestingInstruction::Withdraw => {
-                Self::process_withdraw(program_id, accounts)
+                Self::process_withdraw(program_id, accounts, None, false)
             }
             VestingInstruction::ChangeOwner => {
-                Self::process_change_owner(program_id, accounts)
+                Self::process_change_owner(program_id, accounts, false)
             }
             VestingInstruction::CreateVoterWeightRecord => {
                 Self::process_create_voter_weight_record(program_id, accounts)
@@ -680,51 +2669,245 @@ impl Processor {
                 Self::process_close_voter_weight_record(program_id, accounts)
             }
             VestingInstruction::Split {schedules} => {
-                Self::process_split(program_id, accounts, schedules)
+                Self::process_split(program_id, accounts, schedules, None)
+            }
+            VestingInstruction::DepositLinear {schedule} => {
+                Self::process_deposit(program_id, accounts, Vec::new(), Some(schedule), None, VestingTimeBase::UnixTimestamp)
+            }
+            VestingInstruction::SplitLinear {schedule} => {
+                Self::process_split(program_id, accounts, Vec::new(), Some(schedule))
+            }
+            VestingInstruction::DepositRevocable {schedules, linear_schedule, revoke_authority} => {
+                Self::process_deposit(program_id, accounts, schedules, linear_schedule, Some(revoke_authority), VestingTimeBase::UnixTimestamp)
+            }
+            VestingInstruction::Revoke => {
+                Self::process_revoke(program_id, accounts)
+            }
+            VestingInstruction::SetRevokeAuthority {revoke_authority} => {
+                Self::process_set_revoke_authority(program_id, accounts, revoke_authority)
+            }
+            VestingInstruction::AddSchedules {schedules} => {
+                Self::process_add_schedules(program_id, accounts, schedules)
+            }
+            VestingInstruction::WithdrawAmount {amount} => {
+                Self::process_withdraw(program_id, accounts, Some(amount), false)
+            }
+            VestingInstruction::Merge => {
+                Self::process_merge(program_id, accounts)
+            }
+            VestingInstruction::ProposeOwner => {
+                Self::process_propose_owner(program_id, accounts)
+            }
+            VestingInstruction::AcceptOwner => {
+                Self::process_change_owner(program_id, accounts, true)
+            }
+            VestingInstruction::CancelOwnerProposal => {
+                Self::process_cancel_owner_proposal(program_id, accounts)
+            }
+            VestingInstruction::SetLockupConfig {lockup} => {
+                Self::process_set_lockup_config(program_id, accounts, lockup)
+            }
+            VestingInstruction::RecalculateVoterWeight => {
+                Self::process_recalculate_voter_weight(program_id, accounts, None)
+            }
+            VestingInstruction::UpdateVoterWeightRecord {action, target} => {
+                Self::process_recalculate_voter_weight(program_id, accounts, Some((action, target)))
+            }
+            VestingInstruction::MigrateVestingRecord => {
+                Self::process_migrate_vesting_record(program_id, accounts)
+            }
+            VestingInstruction::AttachRealm => {
+                Self::process_attach_realm(program_id, accounts)
+            }
+            VestingInstruction::DetachRealm => {
+                Self::process_detach_realm(program_id, accounts)
+            }
+            VestingInstruction::MoveRealm => {
+                Self::process_move_realm(program_id, accounts)
+            }
+            VestingInstruction::Compact => {
+                Self::process_compact(program_id, accounts)
+            }
+            VestingInstruction::BatchDeposit {schedules} => {
+                Self::process_batch_deposit(program_id, accounts, schedules)
+            }
+            VestingInstruction::ReconcileMaxVoterWeight {after} => {
+                Self::process_reconcile_max_voter_weight(program_id, accounts, after)
+            }
+            VestingInstruction::DepositWithTimeBase {schedules, linear_schedule, revoke_authority, time_base} => {
+                Self::process_deposit(program_id, accounts, schedules, linear_schedule, revoke_authority, time_base)
+            }
+            VestingInstruction::SetFreezeAuthority {freeze_authority} => {
+                Self::process_set_freeze_authority(program_id, accounts, freeze_authority)
+            }
+            VestingInstruction::SetFrozen {frozen} => {
+                Self::process_set_frozen(program_id, accounts, frozen, false)
+            }
+            VestingInstruction::SetOwnerFrozen {frozen} => {
+                Self::process_set_frozen(program_id, accounts, frozen, true)
+            }
+            VestingInstruction::Delegate {amount} => {
+                Self::process_delegate(program_id, accounts, amount)
+            }
+            VestingInstruction::Undelegate {amount} => {
+                Self::process_undelegate(program_id, accounts, amount)
+            }
+            VestingInstruction::GetWithdrawableAmount => {
+                Self::process_get_withdrawable_amount(program_id, accounts)
+            }
+            VestingInstruction::GetVestingSummary => {
+                Self::process_get_vesting_summary(program_id, accounts)
+            }
+            VestingInstruction::GetVoterWeight => {
+                Self::process_get_voter_weight(program_id, accounts)
+            }
+            VestingInstruction::SetCrankWithdraw {allow} => {
+                Self::process_set_crank_withdraw(program_id, accounts, allow)
+            }
+            VestingInstruction::CrankWithdraw => {
+                Self::process_withdraw(program_id, accounts, None, true)
+            }
+            VestingInstruction::SetManager => {
+                Self::process_set_manager(program_id, accounts)
+            }
+            VestingInstruction::ClearManager => {
+                Self::process_clear_manager(program_id, accounts)
+            }
+            VestingInstruction::SetVotePercentagePolicy {policy} => {
+                Self::process_set_vote_percentage_policy(program_id, accounts, policy)
+            }
+            VestingInstruction::AccelerateSchedule {from_time, shift} => {
+                Self::process_accelerate_schedule(program_id, accounts, from_time, shift)
             }
         }
     }
 }
 
+/// Transfers tokens with `transfer_checked` if the Mint account is passed.
+/// Without the Mint account only spl-token program is allowed (an unchecked transfer is used)
 fn invoke_transfer_signed<'a>(
         spl_token_account: &AccountInfo<'a>,
         source_account: &AccountInfo<'a>,
+        mint: Option<(&AccountInfo<'a>, u8)>,
         destination_account: &AccountInfo<'a>,
         authority_account: &AccountInfo<'a>,
         amount_to_transfer: u64,
         signers_seeds: &[&[&[u8]]],
 ) -> Result<(), ProgramError> {
-    let instruction = spl_token::instruction::transfer(
-        spl_token_account.key,
-        source_account.key,
-        destination_account.key,
-        authority_account.key,
-        &[],
-        amount_to_transfer,
-    )?;
-    invoke_signed(
-        &instruction,
-        &[
-            spl_token_account.clone(),
-            source_account.clone(),
-            destination_account.clone(),
-            authority_account.clone(),
-        ],
-        signers_seeds,
-    )?;
+    match mint {
+        Some((mint_account, decimals)) => {
+            let instruction = spl_token_2022::instruction::transfer_checked(
+                spl_token_account.key,
+                source_account.key,
+                mint_account.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                amount_to_transfer,
+                decimals,
+            )?;
+            invoke_signed(
+                &instruction,
+                &[
+                    spl_token_account.clone(),
+                    source_account.clone(),
+                    mint_account.clone(),
+                    destination_account.clone(),
+                    authority_account.clone(),
+                ],
+                signers_seeds,
+            )?;
+        }
+        None => {
+            let instruction = spl_token::instruction::transfer(
+                spl_token_account.key,
+                source_account.key,
+                destination_account.key,
+                authority_account.key,
+                &[],
+                amount_to_transfer,
+            )?;
+            invoke_signed(
+                &instruction,
+                &[
+                    spl_token_account.clone(),
+                    source_account.clone(),
+                    destination_account.clone(),
+                    authority_account.clone(),
+                ],
+                signers_seeds,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Serializes data into the account growing it (and topping up rent from the payer) if required
+fn serialize_account_with_realloc<'a, T: BorshSerialize>(
+    data: &T,
+    account: &AccountInfo<'a>,
+    payer_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    let serialized_data = data.try_to_vec()?;
+    if serialized_data.len() > account.data_len() {
+        let required_lamports = Rent::get()?
+                .minimum_balance(serialized_data.len())
+                .saturating_sub(account.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer_account.key, account.key, required_lamports),
+                &[
+                    payer_account.clone(),
+                    account.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
+        }
+        account.realloc(serialized_data.len(), false)?;
+    }
+    account.data.borrow_mut()[..serialized_data.len()].copy_from_slice(&serialized_data);
+    Ok(())
+}
+
+/// Serializes data into the account shrinking it and moving released rent lamports to the receiver
+fn serialize_account_with_shrink<T: BorshSerialize>(
+    data: &T,
+    account: &AccountInfo,
+    receiver_account: &AccountInfo,
+) -> Result<(), ProgramError> {
+    let serialized_data = data.try_to_vec()?;
+    account.data.borrow_mut()[..serialized_data.len()].copy_from_slice(&serialized_data);
+    if serialized_data.len() < account.data_len() {
+        account.realloc(serialized_data.len(), false)?;
+        let released_lamports = account.lamports()
+                .saturating_sub(Rent::get()?.minimum_balance(serialized_data.len()));
+        let receiver_lamports = receiver_account.lamports().checked_add(released_lamports)
+                .ok_or(VestingError::OverflowAmount)?;
+        **account.lamports.borrow_mut() -= released_lamports;
+        **receiver_account.lamports.borrow_mut() = receiver_lamports;
+    }
     Ok(())
 }
 
+/// Adds `total_amount` tokens having `weighted_amount` weight (the ratio of the record if it's not known)
+/// to the voter weight record.
+/// The vote percentage of the record is brought into line with the Realm policy.
+/// Returns the new voter weight and the weighted amount added to the record
+#[allow(clippy::too_many_arguments)]
 fn create_or_increase_voter_weight_record<'a>(
         realm: &Pubkey, mint: &Pubkey, vesting_owner: &Pubkey,
         voter_weight_record_account: &AccountInfo<'a>,
         total_amount: u64,
+        weighted_amount: Option<u64>,
+        vote_percentage_policy: Option<&VotePercentagePolicy>,
         program_id: &Pubkey,
         system_program_account: &AccountInfo<'a>,
         payer_account: &AccountInfo<'a>,
-) -> Result<(), ProgramError> {
-    if voter_weight_record_account.data_is_empty() {
-        create_voter_weight_record(
+) -> Result<(u64, u64), ProgramError> {
+    let (voter_weight_record, weighted_amount) = if voter_weight_record_account.data_is_empty() {
+        let weighted_amount = weighted_amount.unwrap_or(total_amount);
+        let voter_weight_record = create_voter_weight_record(
             program_id,
             realm,
             mint,
@@ -732,8 +2915,21 @@ fn create_or_increase_voter_weight_record<'a>(
             payer_account,
             voter_weight_record_account,
             system_program_account,
-            |record| {record.increase_total_amount(total_amount)},
+            |record| {
+                if let Some(policy) = vote_percentage_policy {
+                    record.vote_percentage = policy.default_vote_percentage;
+                }
+                record.increase_total_amount(total_amount, weighted_amount)
+            },
         )?;
+
+        VestingEvent::VoterWeightRecordCreated {
+            realm: *realm,
+            mint: *mint,
+            owner: *vesting_owner,
+            vote_percentage: voter_weight_record.vote_percentage,
+        }.emit();
+        (voter_weight_record, weighted_amount)
     } else {
         let mut voter_weight_record = get_voter_weight_record_data_checked(
                 program_id,
@@ -742,16 +2938,123 @@ fn create_or_increase_voter_weight_record<'a>(
                 mint,
                 vesting_owner)?;
 
-        voter_weight_record.increase_total_amount(total_amount)?;
+        if let Some(policy) = vote_percentage_policy {
+            voter_weight_record.vote_percentage = policy.apply(voter_weight_record.vote_percentage);
+        }
+        let weighted_amount = match weighted_amount {
+            Some(weighted_amount) => weighted_amount,
+            None => voter_weight_record.weighted_share(total_amount)?,
+        };
+        voter_weight_record.increase_total_amount(total_amount, weighted_amount)?;
         voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+        (voter_weight_record, weighted_amount)
+    };
+    Ok((voter_weight_record.base.voter_weight, weighted_amount))
+}
+
+/// Undelegates tokens exceeding the owner's tokens left after removing `amount` tokens from the record,
+/// so the delegation can't block the removal. The delegate's records are required only in this case.
+/// If `delegate_owner_record` (governance program and the delegate's TokenOwnerRecord) is passed,
+/// the delegate must not have active votes (like for `Undelegate`). Revocation doesn't check them
+/// (like votes of the owner), so neither the owner nor the delegate can block it
+fn undelegate_removed_amount(
+    program_id: &Pubkey,
+    voter_weight_record: &mut ExtendedVoterWeightRecord,
+    amount: u64,
+    delegate_voter_weight_record_account: Option<&AccountInfo>,
+    delegate_owner_record: Option<(&Pubkey, Option<&AccountInfo>)>,
+) -> Result<(), ProgramError> {
+    let remaining_amount = voter_weight_record.total_amount.checked_sub(amount).ok_or(VestingError::UnderflowAmount)?;
+    let excess_amount = voter_weight_record.delegated_out.saturating_sub(remaining_amount);
+    if excess_amount == 0 {
+        return Ok(());
+    }
+
+    let realm = voter_weight_record.base.realm;
+    let mint = voter_weight_record.base.governing_token_mint;
+    let delegate = voter_weight_record.delegate.ok_or(VestingError::InvalidDelegate)?;
+    let delegate_voter_weight_record_account = delegate_voter_weight_record_account.ok_or(VestingError::AmountIsDelegated)?;
+    let mut delegate_voter_weight_record = get_voter_weight_record_data_checked(
+            program_id,
+            delegate_voter_weight_record_account,
+            &realm,
+            &mint,
+            &delegate)?;
+
+    if let Some((governance, delegate_owner_record_account)) = delegate_owner_record {
+        let delegate_owner_record_account = delegate_owner_record_account.ok_or(VestingError::AmountIsDelegated)?;
+        let delegate_record_optional_data = get_token_owner_record_data_if_exists(
+            governance,
+            delegate_owner_record_account,
+            &get_token_owner_record_address_seeds(
+                &realm,
+                &mint,
+                &delegate,
+            ),
+        )?;
+        if let Some(delegate_record_data) = delegate_record_optional_data {
+            delegate_record_data.assert_can_withdraw_governing_tokens()?;
+        }
     }
+
+    voter_weight_record.undelegate_from(&mut delegate_voter_weight_record, excess_amount)?;
+    delegate_voter_weight_record.serialize(&mut *delegate_voter_weight_record_account.data.borrow_mut())?;
+
+    VestingEvent::Undelegated {
+        realm,
+        mint,
+        owner: voter_weight_record.base.governing_token_owner,
+        delegate,
+        amount: excess_amount,
+        owner_voter_weight: voter_weight_record.base.voter_weight,
+        delegate_voter_weight: delegate_voter_weight_record.base.voter_weight,
+    }.emit();
     Ok(())
 }
 
+/// Returns the vote percentage policy from the VestingRealmConfig account
+/// (the Realm has no policy if the account doesn't exist)
+fn get_vote_percentage_policy(
+    program_id: &Pubkey,
+    realm_config_account: &AccountInfo,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Option<VotePercentagePolicy>, ProgramError> {
+    Ok(get_realm_config_data_if_exists(program_id, realm_config_account, realm, mint)?
+            .and_then(|realm_config| realm_config.vote_percentage_policy))
+}
+
+/// Returns the lockup config and the vote percentage policy of the Realm
+/// (neither of them is set if VestingRealmConfig account doesn't exist)
+fn get_realm_voting_config(
+    program_id: &Pubkey,
+    realm_config_account: &AccountInfo,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<(Option<LockupConfig>, Option<VotePercentagePolicy>), ProgramError> {
+    Ok(get_realm_config_data_if_exists(program_id, realm_config_account, realm, mint)?
+            .map_or((None, None), |realm_config| (realm_config.lockup, realm_config.vote_percentage_policy)))
+}
+
+/// Returns the lockup config of the Realm if VestingRealmConfig account is passed.
+/// Without the account the lockup is not known and tokens are weighted with the ratio of the voter weight record
+fn get_optional_realm_lockup(
+    program_id: &Pubkey,
+    realm_config_account: Option<&AccountInfo>,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Option<Option<LockupConfig>>, ProgramError> {
+    realm_config_account
+        .map(|realm_config_account| get_realm_voting_config(program_id, realm_config_account, realm, mint)
+            .map(|(lockup, _)| lockup))
+        .transpose()
+}
+
+/// Adds `weighted_amount` to the max voter weight (the sum of weighted amounts of all voter weight records)
 fn create_or_increase_max_voter_weight_record<'a>(
     realm: &Pubkey, mint: &Pubkey,
     max_voter_weight_record_account: &AccountInfo<'a>,
-    total_amount: u64,
+    weighted_amount: u64,
     program_id: &Pubkey,
     system_program_account: &AccountInfo<'a>,
     payer_account: &AccountInfo<'a>,
@@ -764,7 +3067,7 @@ fn create_or_increase_max_voter_weight_record<'a>(
             payer_account,
             max_voter_weight_record_account,
             system_program_account,
-            |record| {record.max_voter_weight = total_amount; Ok(())},
+            |record| {record.max_voter_weight = weighted_amount; Ok(())},
         )?;
     } else {
         let mut max_voter_weight_record = get_max_voter_weight_record_data_checked(
@@ -774,12 +3077,239 @@ fn create_or_increase_max_voter_weight_record<'a>(
                 mint)?;
 
         let max_voter_weight = &mut max_voter_weight_record.max_voter_weight;
-        *max_voter_weight = max_voter_weight.checked_add(total_amount).ok_or(VestingError::OverflowAmount)?;
+        *max_voter_weight = max_voter_weight.checked_add(weighted_amount).ok_or(VestingError::OverflowAmount)?;
         max_voter_weight_record.serialize(&mut *max_voter_weight_record_account.data.borrow_mut())?;
     }
     Ok(())
 }
 
+/// Removes `total_amount` tokens having `weighted_amount` weight (the ratio of the record if it's not known)
+/// from the voter weight record. Returns the new voter weight and the weighted amount removed from the record
+fn decrease_voter_weight_record(
+    realm: &Pubkey, mint: &Pubkey, vesting_owner: &Pubkey,
+    voter_weight_record_account: &AccountInfo,
+    total_amount: u64,
+    weighted_amount: Option<u64>,
+    program_id: &Pubkey,
+) -> Result<(u64, u64), ProgramError> {
+    let mut voter_weight_record = get_voter_weight_record_data_checked(
+            program_id,
+            voter_weight_record_account,
+            realm,
+            mint,
+            vesting_owner)?;
+
+    let weighted_amount = voter_weight_record.decrease_total_amount(total_amount, weighted_amount)?;
+    voter_weight_record.serialize(&mut *voter_weight_record_account.data.borrow_mut())?;
+    Ok((voter_weight_record.base.voter_weight, weighted_amount))
+}
+
+fn decrease_max_voter_weight_record(
+    realm: &Pubkey, mint: &Pubkey,
+    max_voter_weight_record_account: &AccountInfo,
+    weighted_amount: u64,
+    program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    let mut max_voter_weight_record = get_max_voter_weight_record_data_checked(
+            program_id,
+            max_voter_weight_record_account,
+            realm,
+            mint)?;
+
+    let max_voter_weight = &mut max_voter_weight_record.max_voter_weight;
+    *max_voter_weight = max_voter_weight.checked_sub(weighted_amount).ok_or(VestingError::UnderflowAmount)?;
+    max_voter_weight_record.serialize(&mut *max_voter_weight_record_account.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Applies `update` to VestingRealmConfig, creating the account if it doesn't exist
+fn update_realm_config<'a, F>(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    mint: &Pubkey,
+    realm_config_account: &AccountInfo<'a>,
+    payer_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    update: F,
+) -> Result<(), ProgramError>
+where F: FnOnce(&mut VestingRealmConfig)
+{
+    let realm_config = get_realm_config_data_if_exists(program_id, realm_config_account, realm, mint)?;
+
+    let realm_config = if let Some(mut realm_config) = realm_config {
+        update(&mut realm_config);
+        serialize_account_with_realloc(
+            &realm_config,
+            realm_config_account,
+            payer_account,
+            system_program_account,
+        )?;
+        realm_config
+    } else {
+        let mut realm_config = VestingRealmConfig {
+            account_type: VestingAccountType::VestingRealmConfig,
+            realm: *realm,
+            mint: *mint,
+            lockup: None,
+            freeze_authority: None,
+            vote_percentage_policy: None,
+            reserved: [0; VESTING_REALM_CONFIG_RESERVED_SIZE],
+        };
+        update(&mut realm_config);
+        create_and_serialize_account_signed::<VestingRealmConfig>(
+            payer_account,
+            realm_config_account,
+            &realm_config,
+            &get_realm_config_seeds(realm, mint),
+            program_id,
+            system_program_account,
+            &Rent::get()?,
+        )?;
+        realm_config
+    };
+
+    VestingEvent::RealmConfigSet {
+        realm: *realm,
+        mint: *mint,
+        lockup: realm_config.lockup,
+        freeze_authority: realm_config.freeze_authority,
+        vote_percentage_policy: realm_config.vote_percentage_policy,
+    }.emit();
+    Ok(())
+}
+
+/// Returns the number of tokens in the vestings of the voter weight record owner weighted by lockup duration
+/// (or just the number of tokens if the Realm has no lockup multiplier).
+/// The vesting accounts must contain all vestings of the owner in the Realm.
+fn get_lockup_weighted_amount(
+    program_id: &Pubkey,
+    voter_weight_record: &ExtendedVoterWeightRecord,
+    lockup: Option<&LockupConfig>,
+    vesting_accounts: &[AccountInfo],
+    clock: &Clock,
+) -> Result<u64, ProgramError> {
+    let mut vesting_keys: Vec<&Pubkey> = Vec::with_capacity(vesting_accounts.len());
+    let mut total_amount = 0u64;
+    let mut weighted_amount = 0u64;
+    for vesting_account in vesting_accounts {
+        if vesting_keys.contains(&vesting_account.key) {
+            return Err(VestingError::VestingAccountsMismatch.into());
+        }
+        vesting_keys.push(vesting_account.key);
+
+        let vesting_record = get_account_data::<VestingRecordV2>(program_id, vesting_account)?;
+        if vesting_record.owner != voter_weight_record.base.governing_token_owner ||
+           vesting_record.mint != voter_weight_record.base.governing_token_mint ||
+           vesting_record.realm != Some(voter_weight_record.base.realm) {
+            return Err(VestingError::VestingAccountsMismatch.into());
+        }
+
+        let vesting_amount = vesting_record.total_amount()?;
+        let vesting_weighted_amount = get_vesting_weighted_amount(&vesting_record, lockup, clock)?;
+        total_amount = total_amount.checked_add(vesting_amount).ok_or(VestingError::OverflowAmount)?;
+        weighted_amount = weighted_amount.checked_add(vesting_weighted_amount).ok_or(VestingError::OverflowAmount)?;
+    }
+
+    // Amounts of all owner's vestings are accounted in the voter weight record
+    if total_amount != voter_weight_record.total_amount {
+        return Err(VestingError::VestingAccountsMismatch.into());
+    }
+    Ok(weighted_amount)
+}
+
+/// Returns the weight of the vesting tokens at the current time: the number of tokens
+/// weighted by the lockup duration if the Realm has the lockup config or the number of tokens otherwise
+fn get_vesting_weighted_amount(
+    vesting_record: &VestingRecordV2,
+    lockup: Option<&LockupConfig>,
+    clock: &Clock,
+) -> Result<u64, ProgramError> {
+    match lockup {
+        Some(lockup) => vesting_record.lockup_weighted_amount(vesting_record.time_base.current_time(clock), lockup),
+        None => vesting_record.total_amount(),
+    }
+}
+
+/// Returns the decrease of the vesting weight at the current time after tokens were removed from the vesting
+fn get_vesting_weight_decrease(
+    previous_vesting_record: &VestingRecordV2,
+    vesting_record: &VestingRecordV2,
+    lockup: Option<&LockupConfig>,
+    clock: &Clock,
+) -> Result<u64, ProgramError> {
+    Ok(get_vesting_weighted_amount(previous_vesting_record, lockup, clock)?
+            .saturating_sub(get_vesting_weighted_amount(vesting_record, lockup, clock)?))
+}
+
+/// Unpacks spl-token (or spl-token-2022) account skipping its extensions
+fn get_token_account_data(token_account: &AccountInfo) -> Result<Account, ProgramError> {
+    check_spl_token_program_account(token_account.owner)?;
+    let token_account_data = token_account.data.borrow();
+    Ok(StateWithExtensions::<Account>::unpack(&token_account_data)?.base)
+}
+
+/// Unpacks the vesting spl-token (or spl-token-2022) account.
+/// Account extensions which can block transfers from the vesting (memo transfer, CPI guard, etc) are rejected
+fn get_vesting_token_account_data(token_account: &AccountInfo) -> Result<Account, ProgramError> {
+    check_spl_token_program_account(token_account.owner)?;
+    let token_account_data = token_account.data.borrow();
+    let account = StateWithExtensions::<Account>::unpack(&token_account_data)?;
+    for extension_type in account.get_extension_types()? {
+        if extension_type != ExtensionType::ImmutableOwner {
+            return Err(VestingError::UnsupportedTokenAccountExtension.into());
+        }
+    }
+    Ok(account.base)
+}
+
+/// Splits the trailing Mint account (if passed) from the rest of accounts
+fn split_mint_account<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    mint: &Pubkey,
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((mint_account, accounts)) if mint_account.key == mint => (accounts, Some(mint_account)),
+        _ => (accounts, None),
+    }
+}
+
+/// Verifies the trailing Mint account and returns it with the mint decimals.
+/// The Mint account can be omitted for spl-token program only
+fn get_transfer_mint<'a, 'b>(
+    spl_token_account: &AccountInfo,
+    mint_account: Option<&'a AccountInfo<'b>>,
+    expected_mint: &Pubkey,
+) -> Result<Option<(&'a AccountInfo<'b>, u8)>, ProgramError> {
+    match mint_account {
+        Some(mint_account) => Ok(Some((mint_account, get_mint_decimals(mint_account, expected_mint)?))),
+        None if *spl_token_account.key == spl_token::id() => Ok(None),
+        None => Err(VestingError::InvalidMintAccount.into()),
+    }
+}
+
+/// Returns decimals of the vesting mint.
+/// Mints with extensions breaking the vesting accounting (transfer fees, permanent delegate, etc) are rejected
+fn get_mint_decimals(mint_account: &AccountInfo, expected_mint: &Pubkey) -> Result<u8, ProgramError> {
+    if mint_account.key != expected_mint {
+        return Err(VestingError::InvalidMintAccount.into());
+    }
+    check_spl_token_program_account(mint_account.owner)?;
+
+    let mint_data = mint_account.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    for extension_type in mint.get_extension_types()? {
+        match extension_type {
+            ExtensionType::TransferFeeConfig |
+            ExtensionType::PermanentDelegate |
+            ExtensionType::TransferHook => {
+                return Err(VestingError::UnsupportedMintExtension.into());
+            }
+            _ => {}
+        }
+    }
+    Ok(mint.base.decimals)
+}
+
 fn verify_token_account_owned_by_vesting(vesting_account: &AccountInfo, vesting_token_account_data: Account) -> Result<(), ProgramError> {
     if !vesting_account.data_is_empty() {
         return Err(VestingError::VestingAccountAlreadyExists.into());
@@ -792,7 +3322,7 @@ fn verify_token_account_owned_by_vesting(vesting_account: &AccountInfo, vesting_
     Ok(())
 }
 
-fn verify_vesting_token_account(vesting_record: &VestingRecord, vesting_token_account: &AccountInfo, vesting_token_account_data: Account, vesting_account_key: Pubkey) -> Result<(), ProgramError> {
+fn verify_vesting_token_account(vesting_record: &VestingRecordV2, vesting_token_account: &AccountInfo, vesting_token_account_data: Account, vesting_account_key: Pubkey) -> Result<(), ProgramError> {
     if vesting_record.token != *vesting_token_account.key {
         return Err(VestingError::InvalidVestingTokenAccount.into());
     }
@@ -803,7 +3333,7 @@ fn verify_vesting_token_account(vesting_record: &VestingRecord, vesting_token_ac
     Ok(())
 }
 
-fn verify_vesting_owner(vesting_record: &VestingRecord, vesting_owner_account: &AccountInfo) -> Result<(), ProgramError> {
+fn verify_vesting_owner(vesting_record: &VestingRecordV2, vesting_owner_account: &AccountInfo) -> Result<(), ProgramError> {
     if !vesting_owner_account.is_signer {
         return Err(VestingError::MissingRequiredSigner.into());
     }
@@ -813,6 +3343,89 @@ fn verify_vesting_owner(vesting_record: &VestingRecord, vesting_owner_account: &
     Ok(())
 }
 
+fn verify_crank_withdraw(
+    vesting_record: &VestingRecordV2,
+    vesting_owner_account: &AccountInfo,
+    spl_token_account: &AccountInfo,
+    destination_token_account: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if !vesting_record.allow_crank_withdraw {
+        return Err(VestingError::CrankWithdrawNotAllowed.into());
+    }
+    if vesting_record.owner != *vesting_owner_account.key {
+        return Err(VestingError::InvalidOwnerForVestingAccount.into());
+    }
+    let owner_token_account = get_associated_token_address_with_program_id(
+        &vesting_record.owner,
+        &vesting_record.mint,
+        spl_token_account.key,
+    );
+    if owner_token_account != *destination_token_account.key {
+        return Err(VestingError::InvalidDestinationAccount.into());
+    }
+    Ok(())
+}
+
+fn verify_vesting_owner_or_manager(vesting_record: &VestingRecordV2, authority_account: &AccountInfo) -> Result<(), ProgramError> {
+    if !authority_account.is_signer {
+        return Err(VestingError::MissingRequiredSigner.into());
+    }
+    if vesting_record.owner != *authority_account.key &&
+       vesting_record.manager != Some(*authority_account.key) {
+        return Err(VestingError::InvalidOwnerForVestingAccount.into());
+    }
+    Ok(())
+}
+
+fn verify_realm_authority(realm_authority: &Option<Pubkey>, realm_authority_account: &AccountInfo) -> Result<(), ProgramError> {
+    if !realm_authority_account.is_signer {
+        return Err(VestingError::MissingRequiredSigner.into());
+    }
+    if *realm_authority != Some(*realm_authority_account.key) {
+        return Err(VestingError::InvalidRealmAuthority.into());
+    }
+    Ok(())
+}
+
+fn verify_freeze_authority(freeze_authority: &Option<Pubkey>, freeze_authority_account: &AccountInfo) -> Result<(), ProgramError> {
+    if !freeze_authority_account.is_signer {
+        return Err(VestingError::MissingRequiredSigner.into());
+    }
+    if *freeze_authority != Some(*freeze_authority_account.key) {
+        return Err(VestingError::InvalidFreezeAuthority.into());
+    }
+    Ok(())
+}
+
+fn verify_vesting_not_frozen(vesting_record: &VestingRecordV2) -> Result<(), ProgramError> {
+    if vesting_record.frozen {
+        return Err(VestingError::VestingFrozen.into());
+    }
+    Ok(())
+}
+
+fn verify_proposed_owner(vesting_record: &VestingRecordV2, proposed_owner_account: &AccountInfo) -> Result<(), ProgramError> {
+    if !proposed_owner_account.is_signer {
+        return Err(VestingError::MissingRequiredSigner.into());
+    }
+    if vesting_record.proposed_owner != Some(*proposed_owner_account.key) {
+        return Err(VestingError::InvalidProposedOwner.into());
+    }
+    Ok(())
+}
+
+fn verify_revoke_authority(vesting_record: &VestingRecordV2, revoke_authority_account: &AccountInfo) -> Result<(), ProgramError> {
+    if !revoke_authority_account.is_signer {
+        return Err(VestingError::MissingRequiredSigner.into());
+    }
+    match vesting_record.revoke_authority {
+        None => Err(VestingError::VestingIsNotRevocable.into()),
+        Some(revoke_authority) if revoke_authority != *revoke_authority_account.key =>
+            Err(VestingError::InvalidRevokeAuthority.into()),
+        Some(_) => Ok(()),
+    }
+}
+
 fn verify_schedule(schedule: &[VestingSchedule]) -> Result<(), ProgramError> {
     let mut iterator = schedule.iter();
     if let Some(item) = iterator.next() {
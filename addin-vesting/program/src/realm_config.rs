@@ -0,0 +1,160 @@
+use crate::{
+    error::VestingError,
+    state::{LockupConfig, VestingAccountType},
+};
+use solana_program::{
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+    account_info::AccountInfo,
+};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use spl_governance_tools::account::{
+    AccountMaxSize,
+    get_account_data,
+};
+
+/// Realm-wide limits of the vote percentage of VoterWeightRecords
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct VotePercentagePolicy {
+    /// Vote percentage of new VoterWeightRecords (in hundredths of a percent)
+    pub default_vote_percentage: u16,
+
+    /// Maximum vote percentage which can be set by the owner (in hundredths of a percent)
+    pub max_vote_percentage: u16,
+
+    /// If not set, the vote percentage can only be equal to `max_vote_percentage`
+    pub allow_partial_voting: bool,
+}
+
+impl VotePercentagePolicy {
+    /// Checks the policy is consistent
+    pub fn verify(&self) -> Result<(), ProgramError> {
+        if self.max_vote_percentage > 10000 {
+            return Err(VestingError::InvalidPercentage.into());
+        }
+        self.check(self.default_vote_percentage)
+    }
+
+    /// Checks the vote percentage is allowed by the policy
+    pub fn check(&self, vote_percentage: u16) -> Result<(), ProgramError> {
+        if vote_percentage > self.max_vote_percentage ||
+           (!self.allow_partial_voting && vote_percentage != self.max_vote_percentage) {
+            return Err(VestingError::VotePercentageNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    /// Returns the nearest vote percentage allowed by the policy
+    pub fn apply(&self, vote_percentage: u16) -> u16 {
+        if self.allow_partial_voting {
+            vote_percentage.min(self.max_vote_percentage)
+        } else {
+            self.max_vote_percentage
+        }
+    }
+}
+
+/// Size of the area reserved in VestingRealmConfig for future fields
+pub const VESTING_REALM_CONFIG_RESERVED_SIZE: usize = 64;
+
+/// VestingRealmConfig account
+/// The account keeps Realm-level settings of the vesting addin for the governing token mint
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct VestingRealmConfig {
+    pub account_type: VestingAccountType,
+    pub realm: Pubkey,
+    pub mint: Pubkey,
+
+    /// Lockup-duration voting multiplier (voter weight doesn't depend on lockup duration if not set)
+    pub lockup: Option<LockupConfig>,
+
+    /// Governance account of the Realm which can freeze and unfreeze vestings and accelerate their schedules
+    pub freeze_authority: Option<Pubkey>,
+
+    /// Limits of the vote percentage (any vote percentage is allowed if not set)
+    pub vote_percentage_policy: Option<VotePercentagePolicy>,
+
+    /// Reserved for future fields
+    pub reserved: [u8; VESTING_REALM_CONFIG_RESERVED_SIZE],
+}
+
+impl AccountMaxSize for VestingRealmConfig {}
+
+impl IsInitialized for VestingRealmConfig {
+    fn is_initialized(&self) -> bool {
+        self.account_type == VestingAccountType::VestingRealmConfig
+    }
+}
+
+/// Returns VestingRealmConfig PDA seeds
+pub fn get_realm_config_seeds<'a>(
+    realm: &'a Pubkey,
+    mint: &'a Pubkey,
+) -> [&'a [u8]; 3] {
+    [b"vesting-realm-config", realm.as_ref(), mint.as_ref()]
+}
+
+/// Returns VestingRealmConfig PDA address
+pub fn get_realm_config_address(program_id: &Pubkey, realm: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&get_realm_config_seeds(realm, mint), program_id).0
+}
+
+/// Deserializes VestingRealmConfig account (if it was created) and checks its address and owner program
+pub fn get_realm_config_data_if_exists(
+    program_id: &Pubkey,
+    realm_config_info: &AccountInfo,
+    realm: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Option<VestingRealmConfig>, ProgramError> {
+    if get_realm_config_address(program_id, realm, mint) != *realm_config_info.key {
+        return Err(VestingError::InvalidRealmConfigAccountAddress.into());
+    }
+
+    if realm_config_info.data_is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(get_account_data::<VestingRealmConfig>(program_id, realm_config_info)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realm_config_packing() {
+        let realm_config = VestingRealmConfig {
+            account_type: VestingAccountType::VestingRealmConfig,
+            realm: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            lockup: Some(LockupConfig {baseline_factor: 10000, max_extra_factor: 10000, saturation_secs: 100}),
+            freeze_authority: Some(Pubkey::new_unique()),
+            vote_percentage_policy: None,
+            reserved: [0; VESTING_REALM_CONFIG_RESERVED_SIZE],
+        };
+        let data = realm_config.try_to_vec().unwrap();
+        assert_eq!(data.len(), 1+32+32+(1+4+4+8)+(1+32)+1+VESTING_REALM_CONFIG_RESERVED_SIZE);
+        assert_eq!(VestingRealmConfig::try_from_slice(&data).unwrap(), realm_config);
+
+        // Account without the reserved tail can't be unpacked
+        assert!(VestingRealmConfig::try_from_slice(&data[..data.len() - VESTING_REALM_CONFIG_RESERVED_SIZE]).is_err());
+    }
+
+    #[test]
+    fn test_vote_percentage_policy() {
+        let policy = VotePercentagePolicy {default_vote_percentage: 5000, max_vote_percentage: 8000, allow_partial_voting: true};
+        assert!(policy.verify().is_ok());
+        assert!(policy.check(8000).is_ok());
+        assert!(policy.check(8001).is_err());
+        assert_eq!(policy.apply(10000), 8000);
+        assert_eq!(policy.apply(3000), 3000);
+
+        let policy = VotePercentagePolicy {default_vote_percentage: 5000, max_vote_percentage: 8000, allow_partial_voting: false};
+        assert!(policy.verify().is_err());
+        let policy = VotePercentagePolicy {default_vote_percentage: 8000, ..policy};
+        assert!(policy.verify().is_ok());
+        assert!(policy.check(3000).is_err());
+        assert_eq!(policy.apply(3000), 8000);
+    }
+}
@@ -1,8 +1,12 @@
+use crate::error::VestingError;
 use solana_program::{
+    clock::{Clock, DEFAULT_MS_PER_SLOT, DEFAULT_SLOTS_PER_EPOCH},
+    program_error::ProgramError,
     program_pack::IsInitialized,
     pubkey::Pubkey,
 };
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use std::io::Write;
 use spl_governance_tools::account::AccountMaxSize;
 
 #[repr(C)]
@@ -11,8 +15,54 @@ pub enum VestingAccountType {
     /// Default uninitialized state
     Unitialized,
 
-    /// Vesting info account
-    VestingRecord,
+    /// Vesting info account (legacy layout ending right after the schedules)
+    VestingRecordV1,
+
+    /// Realm configuration account
+    VestingRealmConfig,
+
+    /// Vesting info account with creation metadata
+    VestingRecordV2,
+}
+
+/// Units of the release times of the vesting
+#[derive(Clone, Copy, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub enum VestingTimeBase {
+    /// Unix timestamp in seconds (`Clock::unix_timestamp`)
+    UnixTimestamp,
+
+    /// Slot number (`Clock::slot`)
+    Slot,
+
+    /// Epoch number (`Clock::epoch`)
+    Epoch,
+}
+
+impl Default for VestingTimeBase {
+    fn default() -> Self {
+        VestingTimeBase::UnixTimestamp
+    }
+}
+
+impl VestingTimeBase {
+    /// Returns the current time expressed in the time base
+    pub fn current_time(&self, clock: &Clock) -> u64 {
+        match self {
+            VestingTimeBase::UnixTimestamp => clock.unix_timestamp as u64,
+            VestingTimeBase::Slot => clock.slot,
+            VestingTimeBase::Epoch => clock.epoch,
+        }
+    }
+
+    /// Converts the duration expressed in the time base to seconds
+    /// (approximately, using the default slot duration and epoch length)
+    pub fn duration_secs(&self, duration: u64) -> u64 {
+        match self {
+            VestingTimeBase::UnixTimestamp => duration,
+            VestingTimeBase::Slot => duration.saturating_mul(DEFAULT_MS_PER_SLOT) / 1000,
+            VestingTimeBase::Epoch => duration.saturating_mul(DEFAULT_SLOTS_PER_EPOCH * DEFAULT_MS_PER_SLOT) / 1000,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
@@ -21,8 +71,183 @@ pub struct VestingSchedule {
     pub amount: u64,
 }
 
+/// Continuous vesting schedule: nothing is released before `cliff_time`, after that
+/// `amount` is released pro-rata per second between `start_time` and `end_time`
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct LinearVestingSchedule {
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub amount: u64,
+}
+
+impl LinearVestingSchedule {
+    /// Checks that `start_time <= cliff_time <= end_time`
+    pub fn verify(&self) -> Result<(), ProgramError> {
+        if self.start_time > self.cliff_time || self.cliff_time > self.end_time {
+            return Err(VestingError::InvalidSchedule.into());
+        }
+        Ok(())
+    }
+
+    /// Returns the amount released at the specified time (including already withdrawn tokens)
+    pub fn released_amount(&self, time: u64) -> Result<u64, ProgramError> {
+        if time >= self.end_time {
+            Ok(self.amount)
+        } else if time < self.cliff_time || time <= self.start_time {
+            Ok(0)
+        } else {
+            let released = (self.amount as u128)
+                    .checked_mul((time - self.start_time).into()).ok_or(VestingError::OverflowAmount)?
+                    .checked_div((self.end_time - self.start_time).into()).ok_or(VestingError::OverflowAmount)?;
+            Ok(released as u64)
+        }
+    }
+
+    /// Checks that the schedule releases tokens no earlier than `other` schedule
+    pub fn is_not_weaker_than(&self, other: &LinearVestingSchedule) -> bool {
+        self.start_time >= other.start_time &&
+        self.cliff_time >= other.cliff_time &&
+        self.end_time >= other.end_time
+    }
+
+    /// Moves start, cliff and end times later than `from_time` earlier by `shift` (but not earlier than `from_time`)
+    pub fn accelerate(&mut self, from_time: u64, shift: u64) {
+        self.start_time = accelerated_time(self.start_time, from_time, shift);
+        self.cliff_time = accelerated_time(self.cliff_time, from_time, shift);
+        self.end_time = accelerated_time(self.end_time, from_time, shift);
+    }
+}
+
+/// Lockup-duration voting multiplier.
+/// Tokens locked for `lockup` seconds have vote weight factor
+/// `baseline_factor + max_extra_factor * min(lockup, saturation_secs) / saturation_secs`.
+/// The weighted amount is normalized by `baseline_factor + max_extra_factor`, so it never exceeds
+/// the number of tokens and MaxVoterWeightRecord (the total amount of tokens) stays the upper bound.
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct LockupConfig {
+    /// Vote weight factor of released tokens
+    pub baseline_factor: u32,
+
+    /// Extra vote weight factor of tokens locked for `saturation_secs` or longer
+    pub max_extra_factor: u32,
+
+    /// Lockup duration giving the maximum extra vote weight factor
+    pub saturation_secs: u64,
+}
+
+impl LockupConfig {
+    /// Checks that the saturation period is not empty and the maximum factor is not zero
+    pub fn verify(&self) -> Result<(), ProgramError> {
+        if self.saturation_secs == 0 || self.max_factor() == 0 {
+            return Err(VestingError::InvalidLockupConfig.into());
+        }
+        Ok(())
+    }
+
+    fn max_factor(&self) -> u128 {
+        self.baseline_factor as u128 + self.max_extra_factor as u128
+    }
+
+    /// Returns the weight of `amount` tokens locked for `lockup_secs` scaled by `max_factor * saturation_secs`
+    fn scaled_weight(&self, amount: u64, lockup_secs: u64) -> Result<u128, ProgramError> {
+        let baseline = (self.baseline_factor as u128) * (self.saturation_secs as u128);
+        let extra = (self.max_extra_factor as u128) * (lockup_secs.min(self.saturation_secs) as u128);
+        let weight = (amount as u128).checked_mul(baseline + extra).ok_or(VestingError::OverflowAmount)?;
+        Ok(weight)
+    }
+
+    /// Returns the scaled weight of `amount` tokens released uniformly while the lockup
+    /// decreases from `max_lockup_secs` to `min_lockup_secs`
+    fn scaled_weight_uniform(&self, amount: u64, min_lockup_secs: u64, max_lockup_secs: u64) -> Result<u128, ProgramError> {
+        let (a, b, s) = (min_lockup_secs as u128, max_lockup_secs as u128, self.saturation_secs as u128);
+        let average_lockup = if b <= a {
+            b
+        } else if b <= s {
+            (a + b) / 2
+        } else if a >= s {
+            s
+        } else {
+            // Integral of min(lockup, saturation) over [a, b] divided by the range length
+            let integral = (s * s - a * a) / 2 + s * (b - s);
+            integral / (b - a)
+        };
+        self.scaled_weight(amount, average_lockup as u64)
+    }
+
+    /// Converts the scaled weight to the weighted number of tokens
+    fn unscale(&self, scaled_weight: u128) -> Result<u64, ProgramError> {
+        let weighted_amount = scaled_weight / (self.max_factor() * (self.saturation_secs as u128));
+        weighted_amount.try_into().map_err(|_| VestingError::OverflowAmount.into())
+    }
+}
+
+/// Continuous part of the vesting
 #[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
-pub struct VestingRecord {
+pub struct LinearVesting {
+    pub schedule: LinearVestingSchedule,
+
+    /// Number of tokens already withdrawn from the vesting
+    pub withdrawn_amount: u64,
+}
+
+impl LinearVesting {
+    /// Stops the vesting at the specified time and returns the number of unreleased tokens
+    pub fn revoke_unreleased(&mut self, time: u64) -> Result<u64, ProgramError> {
+        let released_amount = self.schedule.released_amount(time)?;
+        let revoked_amount = self.schedule.amount - released_amount;
+        if revoked_amount != 0 {
+            self.schedule.amount = released_amount;
+            self.schedule.start_time = self.schedule.start_time.min(time);
+            self.schedule.cliff_time = self.schedule.cliff_time.min(time);
+            self.schedule.end_time = self.schedule.end_time.min(time);
+        }
+        Ok(revoked_amount)
+    }
+
+    /// Moves `schedule.amount` tokens of the vesting to the new linear vesting with the specified schedule.
+    /// Already withdrawn amount is splitted proportionally, so released and not withdrawn part of both vestings
+    /// never exceeds released and not withdrawn part of the source vesting. The new schedule can't be stronger
+    pub fn split(&mut self, schedule: &LinearVestingSchedule) -> Result<LinearVesting, ProgramError> {
+        if !schedule.is_not_weaker_than(&self.schedule) {
+            return Err(VestingError::InvalidSchedule.into());
+        }
+        if schedule.amount > self.schedule.amount {
+            return Err(VestingError::InsufficientFunds.into());
+        }
+        // Rounded up: the new vesting never gets more than its share
+        let withdrawn_amount = if self.schedule.amount == 0 {
+            0
+        } else {
+            let share = (self.withdrawn_amount as u128) * (schedule.amount as u128);
+            let amount = self.schedule.amount as u128;
+            ((share + amount - 1) / amount) as u64
+        };
+        self.schedule.amount -= schedule.amount;
+        self.withdrawn_amount -= withdrawn_amount;
+        Ok(LinearVesting {schedule: schedule.clone(), withdrawn_amount})
+    }
+}
+
+/// Return data of GetVestingSummary instruction
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct VestingSummary {
+    /// Number of the passed vesting accounts
+    pub vesting_count: u32,
+
+    /// Number of tokens locked in the vestings (not withdrawn yet)
+    pub total_amount: u64,
+
+    /// Number of tokens which can be withdrawn at the current time
+    pub withdrawable_amount: u64,
+
+    /// Number of tokens locked in the frozen vestings
+    pub frozen_amount: u64,
+}
+
+/// Legacy vesting info account: layout of the accounts created before VestingRecordV2
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct VestingRecordV1 {
     pub account_type: VestingAccountType,
     pub owner: Pubkey,
     pub mint: Pubkey,
@@ -31,13 +256,386 @@ pub struct VestingRecord {
     pub schedule: Vec<VestingSchedule>,
 }
 
-impl IsInitialized for VestingRecord {
+impl IsInitialized for VestingRecordV1 {
     fn is_initialized(&self) -> bool {
-        self.account_type == VestingAccountType::VestingRecord
+        self.account_type == VestingAccountType::VestingRecordV1
     }
 }
 
-impl AccountMaxSize for VestingRecord {}
+impl AccountMaxSize for VestingRecordV1 {}
+
+/// Size of the area reserved in VestingRecordV2 for future fields
+pub const VESTING_RECORD_RESERVED_SIZE: usize = 61;
+
+/// Vesting info account
+/// Both VestingRecordV1 and VestingRecordV2 layouts are read and written depending on `account_type`:
+/// legacy accounts keep their layout until they are migrated with MigrateVestingRecord instruction.
+/// VestingRecordV1 layout ends right after `schedule`, so fields starting from `linear` can't be set for it
+#[derive(Clone, Debug, PartialEq, BorshSchema)]
+pub struct VestingRecordV2 {
+    pub account_type: VestingAccountType,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub token: Pubkey,
+    pub realm: Option<Pubkey>,
+    pub schedule: Vec<VestingSchedule>,
+    pub linear: Option<LinearVesting>,
+
+    /// Authority which can revoke unreleased tokens from the vesting
+    pub revoke_authority: Option<Pubkey>,
+
+    /// New owner proposed by the current owner. Ownership is transferred when it is accepted
+    pub proposed_owner: Option<Pubkey>,
+
+    /// Account which funded the vesting (default Pubkey for migrated records)
+    pub creator: Pubkey,
+
+    /// Unix timestamp of the vesting creation (0 for migrated records)
+    pub created_at: u64,
+
+    /// Number of tokens granted by the vesting (for migrated records: locked amount at the migration time)
+    pub original_total: u64,
+
+    /// Units of the release times in `schedule` and `linear` (UnixTimestamp for legacy records)
+    pub time_base: VestingTimeBase,
+
+    /// Withdraw, split and ownership change are blocked by the freeze authority of the Realm
+    pub frozen: bool,
+
+    /// Anyone can withdraw matured tokens to the owner's associated token account (CrankWithdraw instruction)
+    pub allow_crank_withdraw: bool,
+
+    /// Account which can split the vesting or change its owner on behalf of the owner.
+    /// Tokens are still withdrawn by the owner only
+    pub manager: Option<Pubkey>,
+
+    /// Reserved for future fields
+    pub reserved: [u8; VESTING_RECORD_RESERVED_SIZE],
+}
+
+impl BorshSerialize for VestingRecordV2 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.account_type.serialize(writer)?;
+        self.owner.serialize(writer)?;
+        self.mint.serialize(writer)?;
+        self.token.serialize(writer)?;
+        self.realm.serialize(writer)?;
+        self.schedule.serialize(writer)?;
+        if self.account_type == VestingAccountType::VestingRecordV2 {
+            self.linear.serialize(writer)?;
+            self.revoke_authority.serialize(writer)?;
+            self.proposed_owner.serialize(writer)?;
+            self.creator.serialize(writer)?;
+            self.created_at.serialize(writer)?;
+            self.original_total.serialize(writer)?;
+            self.time_base.serialize(writer)?;
+            self.frozen.serialize(writer)?;
+            self.allow_crank_withdraw.serialize(writer)?;
+            self.manager.serialize(writer)?;
+            self.reserved.serialize(writer)?;
+        } else if self.linear.is_some() || self.revoke_authority.is_some() || self.proposed_owner.is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Legacy vesting record layout can't store linear schedule, revoke authority or proposed owner",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for VestingRecordV2 {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let mut vesting_record = Self {
+            account_type: BorshDeserialize::deserialize(buf)?,
+            owner: BorshDeserialize::deserialize(buf)?,
+            mint: BorshDeserialize::deserialize(buf)?,
+            token: BorshDeserialize::deserialize(buf)?,
+            realm: BorshDeserialize::deserialize(buf)?,
+            schedule: BorshDeserialize::deserialize(buf)?,
+            linear: None,
+            revoke_authority: None,
+            proposed_owner: None,
+            creator: Pubkey::default(),
+            created_at: 0,
+            original_total: 0,
+            time_base: VestingTimeBase::UnixTimestamp,
+            frozen: false,
+            allow_crank_withdraw: false,
+            manager: None,
+            reserved: [0; VESTING_RECORD_RESERVED_SIZE],
+        };
+        if vesting_record.account_type == VestingAccountType::VestingRecordV2 {
+            vesting_record.linear = BorshDeserialize::deserialize(buf)?;
+            vesting_record.revoke_authority = BorshDeserialize::deserialize(buf)?;
+            vesting_record.proposed_owner = BorshDeserialize::deserialize(buf)?;
+            vesting_record.creator = BorshDeserialize::deserialize(buf)?;
+            vesting_record.created_at = BorshDeserialize::deserialize(buf)?;
+            vesting_record.original_total = BorshDeserialize::deserialize(buf)?;
+            vesting_record.time_base = BorshDeserialize::deserialize(buf)?;
+            vesting_record.frozen = BorshDeserialize::deserialize(buf)?;
+            vesting_record.allow_crank_withdraw = BorshDeserialize::deserialize(buf)?;
+            vesting_record.manager = BorshDeserialize::deserialize(buf)?;
+            vesting_record.reserved = BorshDeserialize::deserialize(buf)?;
+        }
+        Ok(vesting_record)
+    }
+}
+
+impl VestingRecordV2 {
+    /// Converts the legacy record to VestingRecordV2 layout.
+    /// Creator and creation time of the legacy vesting are unknown, the locked amount is used as the original total
+    pub fn migrate(legacy_record: VestingRecordV1) -> Result<Self, ProgramError> {
+        let mut vesting_record = Self {
+            account_type: VestingAccountType::VestingRecordV2,
+            owner: legacy_record.owner,
+            mint: legacy_record.mint,
+            token: legacy_record.token,
+            realm: legacy_record.realm,
+            schedule: legacy_record.schedule,
+            linear: None,
+            revoke_authority: None,
+            proposed_owner: None,
+            creator: Pubkey::default(),
+            created_at: 0,
+            original_total: 0,
+            time_base: VestingTimeBase::UnixTimestamp,
+            frozen: false,
+            allow_crank_withdraw: false,
+            manager: None,
+            reserved: [0; VESTING_RECORD_RESERVED_SIZE],
+        };
+        vesting_record.original_total = vesting_record.total_amount()?;
+        Ok(vesting_record)
+    }
+
+    /// Returns the number of tokens locked in the vesting (not withdrawn yet)
+    pub fn total_amount(&self) -> Result<u64, ProgramError> {
+        let mut total_amount = self.schedule.iter()
+                .try_fold(0u64, |acc, item| acc.checked_add(item.amount))
+                .ok_or(VestingError::OverflowAmount)?;
+        if let Some(linear) = &self.linear {
+            let linear_amount = linear.schedule.amount.checked_sub(linear.withdrawn_amount)
+                    .ok_or(VestingError::UnderflowAmount)?;
+            total_amount = total_amount.checked_add(linear_amount).ok_or(VestingError::OverflowAmount)?;
+        }
+        Ok(total_amount)
+    }
+
+    /// Returns the number of tokens released at the specified time and not withdrawn yet
+    pub fn matured_amount(&self, time: u64) -> Result<u64, ProgramError> {
+        let mut matured_amount = self.schedule.iter()
+                .filter(|s| time >= s.release_time)
+                .try_fold(0u64, |acc, item| acc.checked_add(item.amount))
+                .ok_or(VestingError::OverflowAmount)?;
+        if let Some(linear) = &self.linear {
+            let linear_amount = linear.schedule.released_amount(time)?
+                    .saturating_sub(linear.withdrawn_amount);
+            matured_amount = matured_amount.checked_add(linear_amount).ok_or(VestingError::OverflowAmount)?;
+        }
+        Ok(matured_amount)
+    }
+
+    /// Returns the number of tokens which can be withdrawn at the specified time (none while the vesting is frozen)
+    pub fn withdrawable_amount(&self, time: u64) -> Result<u64, ProgramError> {
+        if self.frozen {
+            return Ok(0);
+        }
+        self.matured_amount(time)
+    }
+
+    /// Marks `amount` of tokens released at the specified time as withdrawn
+    /// taking them from the earliest schedules first
+    pub fn release_amount(&mut self, time: u64, amount: u64) -> Result<(), ProgramError> {
+        if amount > self.matured_amount(time)? {
+            return Err(VestingError::NotReachedReleaseTime.into());
+        }
+        let mut remaining_amount = amount;
+        for s in self.schedule.iter_mut().take_while(|s| time >= s.release_time) {
+            let part = s.amount.min(remaining_amount);
+            s.amount -= part;
+            remaining_amount -= part;
+        }
+        if let Some(linear) = &mut self.linear {
+            linear.withdrawn_amount = linear.withdrawn_amount.checked_add(remaining_amount)
+                    .ok_or(VestingError::OverflowAmount)?;
+        }
+        Ok(())
+    }
+
+    /// Marks tokens released at the specified time as withdrawn and returns their number
+    pub fn release_matured(&mut self, time: u64) -> Result<u64, ProgramError> {
+        let mut released_amount = 0u64;
+        for s in self.schedule.iter_mut() {
+            if time >= s.release_time {
+                released_amount = released_amount.checked_add(s.amount)
+                        .ok_or(VestingError::OverflowAmount)?;
+                s.amount = 0;
+            }
+        }
+        if let Some(linear) = &mut self.linear {
+            let linear_amount = linear.schedule.released_amount(time)?
+                    .saturating_sub(linear.withdrawn_amount);
+            linear.withdrawn_amount = linear.withdrawn_amount.checked_add(linear_amount)
+                    .ok_or(VestingError::OverflowAmount)?;
+            released_amount = released_amount.checked_add(linear_amount)
+                    .ok_or(VestingError::OverflowAmount)?;
+        }
+        Ok(released_amount)
+    }
+
+    /// Drops schedules which are fully withdrawn. Returns true if the record was changed
+    pub fn compact(&mut self) -> bool {
+        let schedule_len = self.schedule.len();
+        self.schedule.retain(|s| s.amount != 0);
+        let mut compacted = self.schedule.len() != schedule_len;
+        if matches!(&self.linear, Some(linear) if linear.withdrawn_amount >= linear.schedule.amount) {
+            self.linear = None;
+            compacted = true;
+        }
+        compacted
+    }
+
+    /// Merges new entries into `schedule` keeping it ordered by release time
+    /// (entries with the same release time are combined) and returns the added number of tokens
+    pub fn add_schedules(&mut self, schedules: &[VestingSchedule]) -> Result<u64, ProgramError> {
+        let mut added_amount = 0u64;
+        for new_item in schedules {
+            added_amount = added_amount.checked_add(new_item.amount)
+                    .ok_or(VestingError::OverflowAmount)?;
+            match self.schedule.binary_search_by_key(&new_item.release_time, |s| s.release_time) {
+                Ok(index) => {
+                    let item = &mut self.schedule[index];
+                    item.amount = item.amount.checked_add(new_item.amount)
+                            .ok_or(VestingError::OverflowAmount)?;
+                }
+                Err(index) => self.schedule.insert(index, new_item.clone()),
+            }
+        }
+        Ok(added_amount)
+    }
+
+    /// Moves schedules of the other vesting into this one and returns the moved number of tokens.
+    /// Linear parts can be merged only when they have the same start, cliff and end times.
+    pub fn merge(&mut self, other: &VestingRecordV2) -> Result<u64, ProgramError> {
+        let schedules = other.schedule.iter()
+                .filter(|s| s.amount != 0)
+                .cloned()
+                .collect::<Vec<_>>();
+        let mut merged_amount = self.add_schedules(&schedules)?;
+
+        if let Some(other_linear) = &other.linear {
+            match self.linear.as_mut() {
+                Some(linear) => {
+                    let (this, other) = (&linear.schedule, &other_linear.schedule);
+                    if this.start_time != other.start_time ||
+                       this.cliff_time != other.cliff_time ||
+                       this.end_time != other.end_time {
+                        return Err(VestingError::IncompatibleVestings.into());
+                    }
+                    linear.schedule.amount = linear.schedule.amount.checked_add(other_linear.schedule.amount)
+                            .ok_or(VestingError::OverflowAmount)?;
+                    linear.withdrawn_amount = linear.withdrawn_amount.checked_add(other_linear.withdrawn_amount)
+                            .ok_or(VestingError::OverflowAmount)?;
+                }
+                None => self.linear = Some(other_linear.clone()),
+            }
+            let linear_amount = other_linear.schedule.amount.checked_sub(other_linear.withdrawn_amount)
+                    .ok_or(VestingError::UnderflowAmount)?;
+            merged_amount = merged_amount.checked_add(linear_amount).ok_or(VestingError::OverflowAmount)?;
+        }
+        self.original_total = self.original_total.checked_add(other.original_total)
+                .ok_or(VestingError::OverflowAmount)?;
+        Ok(merged_amount)
+    }
+
+    /// Returns the number of tokens locked in the vesting weighted by the remaining lockup duration.
+    /// `time` is expressed in the vesting time base, lockup durations are converted to seconds
+    pub fn lockup_weighted_amount(&self, time: u64, config: &LockupConfig) -> Result<u64, ProgramError> {
+        let add = |acc: u128, weight: u128| acc.checked_add(weight).ok_or(VestingError::OverflowAmount);
+        let secs = |duration: u64| self.time_base.duration_secs(duration);
+
+        let mut scaled_weight = 0u128;
+        for s in self.schedule.iter() {
+            scaled_weight = add(scaled_weight, config.scaled_weight(s.amount, secs(s.release_time.saturating_sub(time)))?)?;
+        }
+
+        if let Some(linear) = &self.linear {
+            let schedule = &linear.schedule;
+            let released_amount = schedule.released_amount(time)?;
+            let withdrawn_amount = linear.withdrawn_amount;
+            let end_lockup = secs(schedule.end_time.saturating_sub(time));
+
+            // Released tokens which are not withdrawn yet are not locked
+            scaled_weight = add(scaled_weight, config.scaled_weight(released_amount.saturating_sub(withdrawn_amount), 0)?)?;
+
+            // Withdrawn tokens are not in the vesting anymore even if they were withdrawn ahead of the schedule
+            if time < schedule.cliff_time {
+                // Tokens vested before the cliff are released at the cliff time, others uniformly till the end
+                let cliff_lockup = secs(schedule.cliff_time - time);
+                let cliff_amount = schedule.released_amount(schedule.cliff_time)?;
+                let uniform_amount = schedule.amount.saturating_sub(cliff_amount.max(withdrawn_amount));
+                scaled_weight = add(scaled_weight, config.scaled_weight(cliff_amount.saturating_sub(withdrawn_amount), cliff_lockup)?)?;
+                scaled_weight = add(scaled_weight, config.scaled_weight_uniform(uniform_amount, cliff_lockup, end_lockup)?)?;
+            } else {
+                let locked_amount = schedule.amount.saturating_sub(released_amount.max(withdrawn_amount));
+                scaled_weight = add(scaled_weight, config.scaled_weight_uniform(locked_amount, 0, end_lockup)?)?;
+            }
+        }
+
+        config.unscale(scaled_weight)
+    }
+
+    /// Removes tokens not released at the specified time from the vesting and returns their number
+    pub fn revoke_unreleased(&mut self, time: u64) -> Result<u64, ProgramError> {
+        let mut revoked_amount = 0u64;
+        for s in self.schedule.iter_mut() {
+            if time < s.release_time {
+                revoked_amount = revoked_amount.checked_add(s.amount)
+                        .ok_or(VestingError::OverflowAmount)?;
+                s.amount = 0;
+            }
+        }
+        if let Some(linear) = &mut self.linear {
+            revoked_amount = revoked_amount.checked_add(linear.revoke_unreleased(time)?)
+                    .ok_or(VestingError::OverflowAmount)?;
+        }
+        Ok(revoked_amount)
+    }
+
+    /// Moves release times later than `from_time` earlier by `shift` (but not earlier than `from_time`),
+    /// so tokens are never released later than before. Entries ending up at the same release time are combined
+    pub fn accelerate(&mut self, from_time: u64, shift: u64) -> Result<(), ProgramError> {
+        let schedules = std::mem::take(&mut self.schedule).into_iter()
+                .map(|s| VestingSchedule {
+                    release_time: accelerated_time(s.release_time, from_time, shift),
+                    amount: s.amount,
+                })
+                .collect::<Vec<_>>();
+        self.add_schedules(&schedules)?;
+        if let Some(linear) = &mut self.linear {
+            linear.schedule.accelerate(from_time, shift);
+        }
+        Ok(())
+    }
+}
+
+/// Returns `time` moved earlier by `shift` when it is later than `from_time`, never crossing `from_time`
+fn accelerated_time(time: u64, from_time: u64, shift: u64) -> u64 {
+    if time > from_time {
+        time.saturating_sub(shift).max(from_time)
+    } else {
+        time
+    }
+}
+
+impl IsInitialized for VestingRecordV2 {
+    fn is_initialized(&self) -> bool {
+        self.account_type == VestingAccountType::VestingRecordV1 ||
+        self.account_type == VestingAccountType::VestingRecordV2
+    }
+}
+
+impl AccountMaxSize for VestingRecordV2 {}
 
 #[cfg(test)]
 mod tests {
@@ -47,18 +645,39 @@ mod tests {
     use spl_governance_tools::account::get_account_data;
     use solana_program::clock::Epoch;
 
-    #[test]
-    fn test_vesting_record_packing() {
-        let vesting_record_source = VestingRecord {
-            account_type: VestingAccountType::VestingRecord,
+    /// Returns the vesting record with the specified schedules, other fields are set to defaults
+    fn test_record(schedule: Vec<VestingSchedule>) -> VestingRecordV2 {
+        VestingRecordV2 {
+            account_type: VestingAccountType::VestingRecordV2,
             owner: Pubkey::new_unique(),
             mint: Pubkey::new_unique(),
             token: Pubkey::new_unique(),
+            realm: None,
+            schedule,
+            linear: None,
+            revoke_authority: None,
+            proposed_owner: None,
+            creator: Pubkey::new_unique(),
+            created_at: 0,
+            original_total: 0,
+            time_base: VestingTimeBase::UnixTimestamp,
+            frozen: false,
+            allow_crank_withdraw: false,
+            manager: None,
+            reserved: [0; VESTING_RECORD_RESERVED_SIZE],
+        }
+    }
+
+    #[test]
+    fn test_vesting_record_packing() {
+        let vesting_record_source = VestingRecordV2 {
             realm: Some(Pubkey::new_unique()),
-            schedule: vec!(
+            created_at: 1650000000,
+            original_total: 1389,
+            ..test_record(vec!(
                 VestingSchedule {release_time: 30767976, amount: 969},
                 VestingSchedule {release_time: 32767076, amount: 420},
-            ),
+            ))
         };
 
         let mut vesting_data = vesting_record_source.try_to_vec().unwrap();
@@ -80,7 +699,391 @@ mod tests {
             false,
             Epoch::default(),
         );
-        let vesting_record_target = get_account_data::<VestingRecord>(&program_id, &account_info).unwrap();
+        let vesting_record_target = get_account_data::<VestingRecordV2>(&program_id, &account_info).unwrap();
         assert_eq!(vesting_record_source, vesting_record_target);
     }
+
+    #[test]
+    fn test_legacy_vesting_record_packing() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let realm = Pubkey::new_unique();
+
+        // Exact-size account created before VestingRecordV2: it ends right after the schedules
+        let mut legacy_data: Vec<u8> = vec![1];
+        legacy_data.extend_from_slice(owner.as_ref());
+        legacy_data.extend_from_slice(mint.as_ref());
+        legacy_data.extend_from_slice(token.as_ref());
+        legacy_data.push(1);
+        legacy_data.extend_from_slice(realm.as_ref());
+        legacy_data.extend_from_slice(&2u32.to_le_bytes());
+        for (release_time, amount) in [(30767976u64, 969u64), (32767076, 420)] {
+            legacy_data.extend_from_slice(&release_time.to_le_bytes());
+            legacy_data.extend_from_slice(&amount.to_le_bytes());
+        }
+        let original_data = legacy_data.clone();
+
+        let program_id = Pubkey::new_unique();
+        let info_key = Pubkey::new_unique();
+        let mut lamports = 10u64;
+        let account_info = AccountInfo::new(
+            &info_key,
+            false,
+            true,
+            &mut lamports,
+            &mut legacy_data[..],
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+        let mut vesting_record = get_account_data::<VestingRecordV2>(&program_id, &account_info).unwrap();
+        assert_eq!(vesting_record, VestingRecordV2 {
+            account_type: VestingAccountType::VestingRecordV1,
+            owner,
+            mint,
+            token,
+            realm: Some(realm),
+            creator: Pubkey::default(),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 30767976, amount: 969},
+                VestingSchedule {release_time: 32767076, amount: 420},
+            ))
+        });
+
+        // The record is written back in place without changing the layout
+        vesting_record.serialize(&mut *account_info.data.borrow_mut()).unwrap();
+        assert_eq!(*account_info.data.borrow(), original_data[..]);
+        vesting_record.release_matured(u64::MAX).unwrap();
+        vesting_record.serialize(&mut *account_info.data.borrow_mut()).unwrap();
+        assert_eq!(VestingRecordV2::try_from_slice(&account_info.data.borrow()).unwrap(), vesting_record);
+
+        vesting_record.proposed_owner = Some(Pubkey::new_unique());
+        assert!(vesting_record.try_to_vec().is_err());
+
+        // Migration decodes the account with the legacy layout
+        let legacy_record = VestingRecordV1::try_from_slice(&original_data).unwrap();
+        assert_eq!(legacy_record.try_to_vec().unwrap(), original_data);
+        let migrated_record = VestingRecordV2::migrate(legacy_record).unwrap();
+        assert_eq!(migrated_record.account_type, VestingAccountType::VestingRecordV2);
+        assert_eq!(migrated_record.realm, Some(realm));
+        assert_eq!(migrated_record.original_total, 969 + 420);
+        let migrated_data = migrated_record.try_to_vec().unwrap();
+        assert_eq!(migrated_data.len(), original_data.len() + 1 + 1 + 1 + 32 + 8 + 8 + 1 + 1 + 1 + 1 + VESTING_RECORD_RESERVED_SIZE);
+        assert_eq!(migrated_data[1..original_data.len()], original_data[1..]);
+        assert_eq!(VestingRecordV2::try_from_slice(&migrated_data).unwrap(), migrated_record);
+    }
+
+    #[test]
+    fn test_linear_vesting_release() {
+        let schedule = LinearVestingSchedule {start_time: 100, cliff_time: 150, end_time: 200, amount: 1000};
+        schedule.verify().unwrap();
+        assert_eq!(schedule.released_amount(0).unwrap(), 0);
+        assert_eq!(schedule.released_amount(149).unwrap(), 0);
+        assert_eq!(schedule.released_amount(150).unwrap(), 500);
+        assert_eq!(schedule.released_amount(199).unwrap(), 990);
+        assert_eq!(schedule.released_amount(200).unwrap(), 1000);
+        assert_eq!(schedule.released_amount(u64::MAX).unwrap(), 1000);
+
+        let cliff_only = LinearVestingSchedule {start_time: 100, cliff_time: 100, end_time: 100, amount: 1000};
+        cliff_only.verify().unwrap();
+        assert_eq!(cliff_only.released_amount(99).unwrap(), 0);
+        assert_eq!(cliff_only.released_amount(100).unwrap(), 1000);
+
+        assert!(LinearVestingSchedule {start_time: 100, cliff_time: 99, end_time: 200, amount: 1}.verify().is_err());
+        assert!(LinearVestingSchedule {start_time: 100, cliff_time: 201, end_time: 200, amount: 1}.verify().is_err());
+    }
+
+    #[test]
+    fn test_linear_vesting_record_release_matured() {
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 0, cliff_time: 25, end_time: 100, amount: 400},
+                withdrawn_amount: 0,
+            }),
+            ..test_record(Vec::new())
+        };
+        assert_eq!(vesting_record.total_amount().unwrap(), 400);
+        assert_eq!(vesting_record.release_matured(10).unwrap(), 0);
+        assert_eq!(vesting_record.release_matured(25).unwrap(), 100);
+        assert_eq!(vesting_record.release_matured(25).unwrap(), 0);
+        assert_eq!(vesting_record.release_matured(50).unwrap(), 100);
+        assert_eq!(vesting_record.total_amount().unwrap(), 200);
+        assert_eq!(vesting_record.release_matured(1000).unwrap(), 200);
+        assert_eq!(vesting_record.total_amount().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_vesting_record_revoke_unreleased() {
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 0, cliff_time: 25, end_time: 100, amount: 400},
+                withdrawn_amount: 0,
+            }),
+            revoke_authority: Some(Pubkey::new_unique()),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 10, amount: 5},
+                VestingSchedule {release_time: 60, amount: 7},
+            ))
+        };
+        assert_eq!(vesting_record.revoke_unreleased(50).unwrap(), 7 + 200);
+        assert_eq!(vesting_record.total_amount().unwrap(), 5 + 200);
+        assert_eq!(vesting_record.release_matured(50).unwrap(), 5 + 200);
+        assert_eq!(vesting_record.release_matured(1000).unwrap(), 0);
+        assert_eq!(vesting_record.total_amount().unwrap(), 0);
+
+        let mut linear = LinearVesting {
+            schedule: LinearVestingSchedule {start_time: 100, cliff_time: 200, end_time: 300, amount: 400},
+            withdrawn_amount: 0,
+        };
+        assert_eq!(linear.revoke_unreleased(50).unwrap(), 400);
+        linear.schedule.verify().unwrap();
+        assert_eq!(linear.schedule.released_amount(1000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_linear_vesting_split() {
+        let schedule = LinearVestingSchedule {start_time: 0, cliff_time: 0, end_time: 100, amount: 400};
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {schedule: schedule.clone(), withdrawn_amount: 0}),
+            ..test_record(Vec::new())
+        };
+        assert_eq!(vesting_record.release_matured(75).unwrap(), 300);
+        let source_record = vesting_record.clone();
+
+        for new_schedule in [
+            LinearVestingSchedule {amount: 200, ..schedule.clone()},
+            LinearVestingSchedule {amount: 399, ..schedule.clone()},
+            LinearVestingSchedule {start_time: 50, cliff_time: 80, end_time: 300, amount: 100},
+        ] {
+            let mut vesting_record = source_record.clone();
+            let new_linear = vesting_record.linear.as_mut().unwrap().split(&new_schedule).unwrap();
+            let new_record = VestingRecordV2 {linear: Some(new_linear), ..test_record(Vec::new())};
+
+            // Tokens are moved without changing total locked amount
+            assert_eq!(
+                vesting_record.total_amount().unwrap() + new_record.total_amount().unwrap(),
+                source_record.total_amount().unwrap()
+            );
+            for time in (0..=400).step_by(5) {
+                assert!(
+                    vesting_record.matured_amount(time).unwrap() + new_record.matured_amount(time).unwrap() <=
+                    source_record.matured_amount(time).unwrap()
+                );
+            }
+        }
+
+        let linear = vesting_record.linear.as_mut().unwrap();
+        assert!(linear.split(&LinearVestingSchedule {amount: 401, ..schedule.clone()}).is_err());
+        assert!(linear.split(&LinearVestingSchedule {end_time: 99, amount: 1, ..schedule}).is_err());
+    }
+
+    #[test]
+    fn test_vesting_record_release_amount() {
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 0, cliff_time: 0, end_time: 100, amount: 400},
+                withdrawn_amount: 0,
+            }),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 10, amount: 5},
+                VestingSchedule {release_time: 20, amount: 7},
+                VestingSchedule {release_time: 60, amount: 9},
+            ))
+        };
+        assert_eq!(vesting_record.matured_amount(50).unwrap(), 5 + 7 + 200);
+        assert!(vesting_record.release_amount(50, 5 + 7 + 201).is_err());
+
+        vesting_record.release_amount(50, 8).unwrap();
+        assert_eq!(vesting_record.schedule[0].amount, 0);
+        assert_eq!(vesting_record.schedule[1].amount, 4);
+        assert_eq!(vesting_record.schedule[2].amount, 9);
+        assert_eq!(vesting_record.matured_amount(50).unwrap(), 4 + 200);
+
+        vesting_record.release_amount(50, 104).unwrap();
+        assert_eq!(vesting_record.schedule[1].amount, 0);
+        assert_eq!(vesting_record.linear.as_ref().unwrap().withdrawn_amount, 100);
+        assert_eq!(vesting_record.matured_amount(50).unwrap(), 100);
+        assert_eq!(vesting_record.total_amount().unwrap(), 9 + 300);
+    }
+
+    #[test]
+    fn test_vesting_record_merge() {
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 0, cliff_time: 25, end_time: 100, amount: 400},
+                withdrawn_amount: 100,
+            }),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 10, amount: 5},
+            ))
+        };
+        let mut other_record = VestingRecordV2 {
+            schedule: vec!(
+                VestingSchedule {release_time: 5, amount: 0},
+                VestingSchedule {release_time: 10, amount: 2},
+                VestingSchedule {release_time: 30, amount: 3},
+            ),
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 0, cliff_time: 25, end_time: 100, amount: 200},
+                withdrawn_amount: 50,
+            }),
+            token: Pubkey::new_unique(),
+            ..vesting_record.clone()
+        };
+        assert_eq!(vesting_record.merge(&other_record).unwrap(), 2 + 3 + 150);
+        assert_eq!(vesting_record.schedule, vec!(
+            VestingSchedule {release_time: 10, amount: 7},
+            VestingSchedule {release_time: 30, amount: 3},
+        ));
+        assert_eq!(vesting_record.linear, Some(LinearVesting {
+            schedule: LinearVestingSchedule {start_time: 0, cliff_time: 25, end_time: 100, amount: 600},
+            withdrawn_amount: 150,
+        }));
+        assert_eq!(vesting_record.total_amount().unwrap(), 10 + 450);
+
+        other_record.linear.as_mut().unwrap().schedule.cliff_time = 50;
+        assert!(vesting_record.merge(&other_record).is_err());
+    }
+
+    #[test]
+    fn test_vesting_record_lockup_weighted_amount() {
+        let mut vesting_record = VestingRecordV2 {
+            realm: Some(Pubkey::new_unique()),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 0, amount: 100},
+                VestingSchedule {release_time: 100, amount: 100},
+                VestingSchedule {release_time: 150, amount: 100},
+            ))
+        };
+        let config = LockupConfig {baseline_factor: 10000, max_extra_factor: 10000, saturation_secs: 100};
+        config.verify().unwrap();
+        assert_eq!(vesting_record.lockup_weighted_amount(50, &config).unwrap(), 50 + 75 + 100);
+
+        vesting_record.schedule.clear();
+        vesting_record.linear = Some(LinearVesting {
+            schedule: LinearVestingSchedule {start_time: 0, cliff_time: 0, end_time: 200, amount: 200},
+            withdrawn_amount: 0,
+        });
+        assert_eq!(vesting_record.lockup_weighted_amount(100, &config).unwrap(), 50 + 75);
+        assert_eq!(vesting_record.lockup_weighted_amount(300, &config).unwrap(), 100);
+
+        // Tokens withdrawn ahead of the schedule are not weighted as locked
+        vesting_record.linear.as_mut().unwrap().withdrawn_amount = 120;
+        assert_eq!(vesting_record.lockup_weighted_amount(100, &config).unwrap(), 60);
+        vesting_record.linear.as_mut().unwrap().withdrawn_amount = 0;
+
+        let flat_config = LockupConfig {baseline_factor: 10000, max_extra_factor: 0, saturation_secs: 100};
+        assert_eq!(vesting_record.lockup_weighted_amount(100, &flat_config).unwrap(), vesting_record.total_amount().unwrap());
+
+        assert!(LockupConfig {baseline_factor: 0, max_extra_factor: 0, saturation_secs: 100}.verify().is_err());
+        assert!(LockupConfig {baseline_factor: 1, max_extra_factor: 0, saturation_secs: 0}.verify().is_err());
+    }
+
+    #[test]
+    fn test_vesting_time_base() {
+        let clock = Clock {slot: 1000, epoch: 2, unix_timestamp: 1650000000, ..Clock::default()};
+        assert_eq!(VestingTimeBase::UnixTimestamp.current_time(&clock), 1650000000);
+        assert_eq!(VestingTimeBase::Slot.current_time(&clock), 1000);
+        assert_eq!(VestingTimeBase::Epoch.current_time(&clock), 2);
+        assert_eq!(VestingTimeBase::Slot.duration_secs(250), 100);
+
+        // Lockup durations of slot-based vesting are converted to seconds
+        let vesting_record = VestingRecordV2 {
+            realm: Some(Pubkey::new_unique()),
+            time_base: VestingTimeBase::Slot,
+            ..test_record(vec!(
+                VestingSchedule {release_time: 1125, amount: 100},
+                VestingSchedule {release_time: 1250, amount: 100},
+            ))
+        };
+        let config = LockupConfig {baseline_factor: 10000, max_extra_factor: 10000, saturation_secs: 100};
+        let time = vesting_record.time_base.current_time(&clock);
+        assert_eq!(vesting_record.lockup_weighted_amount(time, &config).unwrap(), 75 + 100);
+        assert_eq!(vesting_record.matured_amount(time).unwrap(), 0);
+        assert_eq!(vesting_record.matured_amount(1125).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_vesting_record_add_schedules() {
+        let mut vesting_record = test_record(vec!(
+            VestingSchedule {release_time: 10, amount: 5},
+            VestingSchedule {release_time: 60, amount: 7},
+        ));
+        let added_amount = vesting_record.add_schedules(&[
+            VestingSchedule {release_time: 5, amount: 1},
+            VestingSchedule {release_time: 10, amount: 2},
+            VestingSchedule {release_time: 30, amount: 3},
+            VestingSchedule {release_time: 90, amount: 4},
+        ]).unwrap();
+        assert_eq!(added_amount, 10);
+        assert_eq!(vesting_record.schedule, vec!(
+            VestingSchedule {release_time: 5, amount: 1},
+            VestingSchedule {release_time: 10, amount: 7},
+            VestingSchedule {release_time: 30, amount: 3},
+            VestingSchedule {release_time: 60, amount: 7},
+            VestingSchedule {release_time: 90, amount: 4},
+        ));
+        assert_eq!(vesting_record.total_amount().unwrap(), 22);
+    }
+
+    #[test]
+    fn test_vesting_record_compact() {
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 0, cliff_time: 0, end_time: 50, amount: 100},
+                withdrawn_amount: 0,
+            }),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 10, amount: 5},
+                VestingSchedule {release_time: 60, amount: 7},
+                VestingSchedule {release_time: 90, amount: 4},
+            ))
+        };
+        assert!(!vesting_record.compact());
+
+        let data_len = vesting_record.try_to_vec().unwrap().len();
+        assert_eq!(vesting_record.release_matured(60).unwrap(), 112);
+        assert!(vesting_record.compact());
+        assert!(!vesting_record.compact());
+        assert_eq!(vesting_record.schedule, vec!(VestingSchedule {release_time: 90, amount: 4}));
+        assert_eq!(vesting_record.linear, None);
+        assert_eq!(vesting_record.total_amount().unwrap(), 4);
+        assert!(vesting_record.try_to_vec().unwrap().len() < data_len);
+    }
+
+    #[test]
+    fn test_vesting_record_accelerate() {
+        let mut vesting_record = VestingRecordV2 {
+            linear: Some(LinearVesting {
+                schedule: LinearVestingSchedule {start_time: 20, cliff_time: 50, end_time: 150, amount: 100},
+                withdrawn_amount: 0,
+            }),
+            ..test_record(vec!(
+                VestingSchedule {release_time: 10, amount: 5},
+                VestingSchedule {release_time: 60, amount: 7},
+                VestingSchedule {release_time: 90, amount: 4},
+                VestingSchedule {release_time: 200, amount: 3},
+            ))
+        };
+        let matured_before = (0..300).map(|t| vesting_record.matured_amount(t).unwrap()).collect::<Vec<_>>();
+
+        vesting_record.accelerate(40, 50).unwrap();
+        assert_eq!(vesting_record.schedule, vec!(
+            VestingSchedule {release_time: 10, amount: 5},
+            VestingSchedule {release_time: 40, amount: 11},
+            VestingSchedule {release_time: 150, amount: 3},
+        ));
+        assert_eq!(
+            vesting_record.linear.as_ref().unwrap().schedule,
+            LinearVestingSchedule {start_time: 20, cliff_time: 40, end_time: 100, amount: 100}
+        );
+        assert_eq!(vesting_record.total_amount().unwrap(), 119);
+        for (time, matured) in matured_before.into_iter().enumerate() {
+            assert!(vesting_record.matured_amount(time as u64).unwrap() >= matured);
+        }
+
+        vesting_record.accelerate(300, 1000).unwrap();
+        assert_eq!(vesting_record.schedule.last().unwrap().release_time, 150);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::error::VestingError;
-use std::convert::TryInto;
+use std::{convert::TryInto, io::Write};
 use solana_program::{
     pubkey::Pubkey,
     program_error::ProgramError,
@@ -15,12 +15,40 @@ use spl_governance_tools::account::{
     get_account_data,
 };
 
-pub use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+pub use spl_governance_addin_api::voter_weight::{VoterWeightAction, VoterWeightRecord};
+
+/// Return data of GetVoterWeight instruction
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct VoterWeightInfo {
+    /// Voter weight provided to the governance program
+    pub voter_weight: u64,
+
+    /// Slot after which the voter weight is not valid (None when it doesn't expire)
+    pub voter_weight_expiry: Option<u64>,
+
+    /// Total number of tokens owned by the owner
+    pub total_amount: u64,
+
+    /// Percentage of the total number of tokens for calculating the voting weight
+    pub vote_percentage: u16,
+
+    /// Number of owner's tokens delegated to `delegate`
+    pub delegated_out: u64,
+
+    /// Weighted amount of tokens delegated to the owner by other owners
+    pub delegated_in: u64,
+
+    /// Owner whose VoterWeightRecord receives delegated tokens
+    pub delegate: Option<Pubkey>,
+}
 
 /// ExtendedVoterWeightRecord account
 /// The account is used as an api interface to provide voting power to the governance program
-/// and to save information about total amount of deposited token
-#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+/// and to save information about total amount of deposited token.
+/// Delegation fields and the weighted amount are stored only by records with `ACCOUNT_DISCRIMINATOR_V2`:
+/// older records keep their layout until they take part in the delegation or are upgraded by
+/// `CreateVoterWeightRecord` instruction (so they can't be weighted by lockup duration)
+#[derive(Clone, Debug, PartialEq, BorshSchema)]
 pub struct ExtendedVoterWeightRecord {
     pub base: VoterWeightRecord,
 
@@ -35,32 +63,248 @@ pub struct ExtendedVoterWeightRecord {
     /// Percentage of the total number of tokens for calculating the voting weight
     /// (in hundredths of a percent)
     pub vote_percentage: u16,
+
+    /// Number of owner's tokens delegated to `delegate`
+    pub delegated_out: u64,
+
+    /// Weighted amount of tokens delegated to the owner by other owners
+    pub delegated_in: u64,
+
+    /// Owner whose VoterWeightRecord receives delegated tokens
+    pub delegate: Option<Pubkey>,
+
+    /// Number of owner's tokens weighted by lockup duration (equal to `total_amount` if the Realm
+    /// has no lockup multiplier or the record has the legacy layout)
+    pub weighted_amount: u64,
+
+    /// Part of the weighted amount moved to `delegated_in` of the delegate together with `delegated_out` tokens
+    pub delegated_out_weight: u64,
+}
+
+impl BorshSerialize for ExtendedVoterWeightRecord {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.base.serialize(writer)?;
+        self.account_discriminator.serialize(writer)?;
+        self.total_amount.serialize(writer)?;
+        self.vote_percentage.serialize(writer)?;
+        if self.account_discriminator == Self::ACCOUNT_DISCRIMINATOR_V2 {
+            self.delegated_out.serialize(writer)?;
+            self.delegated_in.serialize(writer)?;
+            self.delegate.serialize(writer)?;
+            self.weighted_amount.serialize(writer)?;
+            self.delegated_out_weight.serialize(writer)?;
+        } else if self.weighted_amount != self.total_amount {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Legacy record can't store weighted amount"));
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for ExtendedVoterWeightRecord {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let mut record = Self {
+            base: BorshDeserialize::deserialize(buf)?,
+            account_discriminator: BorshDeserialize::deserialize(buf)?,
+            total_amount: BorshDeserialize::deserialize(buf)?,
+            vote_percentage: BorshDeserialize::deserialize(buf)?,
+            delegated_out: 0,
+            delegated_in: 0,
+            delegate: None,
+            weighted_amount: 0,
+            delegated_out_weight: 0,
+        };
+        if record.account_discriminator == Self::ACCOUNT_DISCRIMINATOR_V2 {
+            record.delegated_out = BorshDeserialize::deserialize(buf)?;
+            record.delegated_in = BorshDeserialize::deserialize(buf)?;
+            record.delegate = BorshDeserialize::deserialize(buf)?;
+            record.weighted_amount = BorshDeserialize::deserialize(buf)?;
+            record.delegated_out_weight = BorshDeserialize::deserialize(buf)?;
+        } else {
+            record.weighted_amount = record.total_amount;
+        }
+        Ok(record)
+    }
 }
 
 impl ExtendedVoterWeightRecord {
     /// sha256("account:ExtendedVoterWeightRecord")[..8]
     pub const ACCOUNT_DISCRIMINATOR: [u8; 8] = [0x49, 0x6b, 0x79, 0x9a, 0xfd, 0x90, 0x5d, 0xe7];
 
-    fn recalculate_voter_weight(&mut self) -> Result<(), ProgramError> {
-        let voter_weight = (self.total_amount as u128)
+    /// sha256("account:ExtendedVoterWeightRecordV2")[..8]
+    pub const ACCOUNT_DISCRIMINATOR_V2: [u8; 8] = [0x87, 0x4e, 0x42, 0x5b, 0x79, 0x19, 0x7f, 0x3d];
+
+    fn calculate_voter_weight(&self, amount: u64) -> Result<u64, ProgramError> {
+        let voter_weight = (amount as u128)
                 .checked_mul(self.vote_percentage.into()).ok_or(VestingError::OverflowAmount)?
                 .checked_div(10000).ok_or(VestingError::OverflowAmount)?;
-        self.base.voter_weight = voter_weight.try_into().map_err(|_| VestingError::OverflowAmount)?;
+        voter_weight.try_into().map_err(|_| VestingError::OverflowAmount.into())
+    }
+
+    /// Returns the number of owner's tokens which are not delegated
+    pub fn undelegated_amount(&self) -> Result<u64, ProgramError> {
+        self.total_amount.checked_sub(self.delegated_out).ok_or_else(|| VestingError::AmountIsDelegated.into())
+    }
+
+    /// The weighted amount without its delegated part is counted together with the weight delegated to the owner
+    fn recalculate_voter_weight(&mut self) -> Result<(), ProgramError> {
+        self.undelegated_amount()?;
+        let amount = self.weighted_amount.saturating_sub(self.delegated_out_weight)
+                .checked_add(self.delegated_in).ok_or(VestingError::OverflowAmount)?;
+        self.base.voter_weight = self.calculate_voter_weight(amount)?;
+        Ok(())
+    }
+
+    fn is_legacy(&self) -> bool {
+        self.account_discriminator != Self::ACCOUNT_DISCRIMINATOR_V2
+    }
+
+    /// Returns the share of the weighted amount corresponding to `amount` of owner's tokens.
+    /// Tokens added to or removed from the record without the lockup config of the Realm are weighted
+    /// with this ratio until the voter weight is recalculated with the lockup of all owner's vestings
+    pub fn weighted_share(&self, amount: u64) -> Result<u64, ProgramError> {
+        if self.total_amount == 0 {
+            return Ok(amount);
+        }
+        ((amount as u128) * (self.weighted_amount as u128) / (self.total_amount as u128))
+                .try_into().map_err(|_| VestingError::OverflowAmount.into())
+    }
+
+    /// Set the number of tokens weighted by lockup duration and recalculate current voter_weight.
+    /// Legacy records can't be weighted until they are upgraded
+    pub fn recalculate_voter_weight_with_lockup(&mut self, weighted_amount: u64) -> Result<(), ProgramError> {
+        if weighted_amount > self.total_amount {
+            return Err(VestingError::OverflowAmount.into());
+        }
+        if self.is_legacy() && weighted_amount != self.total_amount {
+            return Err(VestingError::VoterWeightRecordNotUpgraded.into());
+        }
+        self.weighted_amount = weighted_amount;
+        self.recalculate_voter_weight()
+    }
+
+    /// Switch the record to the layout with delegation fields
+    /// (the account should be reallocated before the record is written)
+    pub fn upgrade(&mut self) {
+        self.account_discriminator = Self::ACCOUNT_DISCRIMINATOR_V2;
+    }
+
+    /// Move `amount` of owner's tokens with their share of the weighted amount to `delegate`
+    /// and recalculate voter_weight of both records
+    pub fn delegate_to(&mut self, delegate: &mut ExtendedVoterWeightRecord, amount: u64) -> Result<(), ProgramError> {
+        let delegate_owner = delegate.base.governing_token_owner;
+        if delegate_owner == self.base.governing_token_owner ||
+           self.delegate.map_or(false, |current| current != delegate_owner) {
+            return Err(VestingError::InvalidDelegate.into());
+        }
+        if amount > self.undelegated_amount()? {
+            return Err(VestingError::InsufficientFunds.into());
+        }
+        let weight = self.weighted_share(amount)?
+                .min(self.weighted_amount.saturating_sub(self.delegated_out_weight));
+        self.delegate = Some(delegate_owner);
+        self.delegated_out = self.delegated_out.checked_add(amount).ok_or(VestingError::OverflowAmount)?;
+        self.delegated_out_weight = self.delegated_out_weight.checked_add(weight).ok_or(VestingError::OverflowAmount)?;
+        delegate.delegated_in = delegate.delegated_in.checked_add(weight).ok_or(VestingError::OverflowAmount)?;
+        self.recalculate_voter_weight()?;
+        delegate.recalculate_voter_weight()?;
+        Ok(())
+    }
+
+    /// Return `amount` of delegated tokens from `delegate` with the same share of the delegated weight
+    /// and recalculate voter_weight of both records
+    pub fn undelegate_from(&mut self, delegate: &mut ExtendedVoterWeightRecord, amount: u64) -> Result<(), ProgramError> {
+        if self.delegate != Some(delegate.base.governing_token_owner) {
+            return Err(VestingError::InvalidDelegate.into());
+        }
+        if amount > self.delegated_out {
+            return Err(VestingError::UnderflowAmount.into());
+        }
+        let weight: u64 = ((self.delegated_out_weight as u128) * (amount as u128) / (self.delegated_out as u128).max(1))
+                .try_into().map_err(|_| VestingError::OverflowAmount)?;
+        self.delegated_out -= amount;
+        self.delegated_out_weight -= weight;
+        delegate.delegated_in = delegate.delegated_in.checked_sub(weight).ok_or(VestingError::UnderflowAmount)?;
+        if self.delegated_out == 0 {
+            self.delegate = None;
+        }
+        self.recalculate_voter_weight()?;
+        delegate.recalculate_voter_weight()?;
+        Ok(())
+    }
+
+    /// Set the delegated weight to the share of the current weighted amount of delegated tokens
+    /// (after the weighted amount was recalculated) and recalculate voter_weight of both records
+    pub fn sync_delegated_weight(&mut self, delegate: &mut ExtendedVoterWeightRecord) -> Result<(), ProgramError> {
+        if self.delegate != Some(delegate.base.governing_token_owner) {
+            return Err(VestingError::InvalidDelegate.into());
+        }
+        let weight = self.weighted_share(self.delegated_out)?;
+        delegate.delegated_in = delegate.delegated_in
+                .checked_sub(self.delegated_out_weight).ok_or(VestingError::UnderflowAmount)?
+                .checked_add(weight).ok_or(VestingError::OverflowAmount)?;
+        self.delegated_out_weight = weight;
+        self.recalculate_voter_weight()?;
+        delegate.recalculate_voter_weight()?;
         Ok(())
     }
 
-    /// Increase total_amount to specified value and recalculate current voter_weight
-    pub fn increase_total_amount(&mut self, value: u64) -> Result<(), ProgramError> {
+    /// Set the action the voter_weight was calculated for and make it valid in the current slot only
+    pub fn set_weight_action(&mut self, action: VoterWeightAction, target: Option<Pubkey>, slot: u64) {
+        self.base.weight_action = Some(action);
+        self.base.weight_action_target = target;
+        self.base.voter_weight_expiry = Some(slot);
+    }
+
+    /// Increase total_amount by `value` tokens having `weighted_value` weight and recalculate current voter_weight.
+    /// Tokens moved from another record keep their weight, new tokens are weighted with the lockup
+    /// of their vesting (or by `weighted_share` if the lockup is not known)
+    pub fn increase_total_amount(&mut self, value: u64, weighted_value: u64) -> Result<(), ProgramError> {
+        if weighted_value > value {
+            return Err(VestingError::OverflowAmount.into());
+        }
+        if self.is_legacy() && weighted_value != value {
+            return Err(VestingError::VoterWeightRecordNotUpgraded.into());
+        }
         self.total_amount = self.total_amount.checked_add(value).ok_or(VestingError::OverflowAmount)?;
+        self.weighted_amount = self.weighted_amount.checked_add(weighted_value).ok_or(VestingError::OverflowAmount)?;
         self.recalculate_voter_weight()?;
         Ok(())
     }
 
-    /// Decrease total_amount to specified value and recalculate current voter_weight
-    pub fn decrease_total_amount(&mut self, value: u64) -> Result<(), ProgramError> {
+    /// Decrease total_amount by `value` tokens having `weighted_value` weight (by default the share
+    /// of removed tokens given by `weighted_share`) and recalculate current voter_weight.
+    /// Returns the decrease of the weighted amount.
+    /// Delegated tokens can't be removed until they are undelegated and the weight delegated
+    /// to the delegate stays in the record until it's synchronized
+    pub fn decrease_total_amount(&mut self, value: u64, weighted_value: Option<u64>) -> Result<u64, ProgramError> {
+        let weighted_value = match weighted_value {
+            Some(weighted_value) => weighted_value,
+            None => self.weighted_share(value)?,
+        };
         self.total_amount = self.total_amount.checked_sub(value).ok_or(VestingError::UnderflowAmount)?;
+        // The record may be weighted with older lockups, so the weight of removed tokens can exceed it
+        let weighted_amount = self.weighted_amount.saturating_sub(weighted_value)
+                .min(self.total_amount)
+                .max(self.delegated_out_weight.min(self.total_amount))
+                .min(self.weighted_amount);
+        let weighted_decrease = self.weighted_amount - weighted_amount;
+        self.weighted_amount = weighted_amount;
         self.recalculate_voter_weight()?;
-        Ok(())
+        Ok(weighted_decrease)
+    }
+
+    /// Decrease the weighted amount by `value` (when the lockup of owner's vestings is shortened)
+    /// and recalculate current voter_weight. Returns the actual decrease limited by the weighted amount
+    /// which is not delegated (the delegated weight is decreased when it's synchronized).
+    /// Legacy records are not weighted, so they are left unchanged
+    pub fn decrease_weighted_amount(&mut self, value: u64) -> Result<u64, ProgramError> {
+        if self.is_legacy() {
+            return Ok(0);
+        }
+        let weighted_decrease = value.min(self.weighted_amount.saturating_sub(self.delegated_out_weight));
+        self.weighted_amount -= weighted_decrease;
+        self.recalculate_voter_weight()?;
+        Ok(weighted_decrease)
     }
 
     /// Set new value for vote_percentage and recalculate current voter_weight
@@ -79,6 +323,7 @@ impl AccountMaxSize for ExtendedVoterWeightRecord {}
 impl IsInitialized for ExtendedVoterWeightRecord {
     fn is_initialized(&self) -> bool {
         self.account_discriminator == ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR
+            || self.account_discriminator == ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2
             // Check for legacy discriminator which is not compatible with Anchor but is used by older plugins
             || self.account_discriminator == *b"496b799a"
     }
@@ -122,6 +367,24 @@ pub fn get_voter_weight_record_data_for_seeds(
     get_voter_weight_record_data(program_id, voter_weight_record_info)
 }
 
+/// Deserializes ExtendedVoterWeightRecord account and checks owner program and that the address
+/// is derived from the realm, mint and owner stored in the record
+pub fn get_voter_weight_record_data_checked_address(
+    program_id: &Pubkey,
+    record_info: &AccountInfo,
+) -> Result<ExtendedVoterWeightRecord, ProgramError> {
+    let record = get_voter_weight_record_data(program_id, record_info)?;
+    let record_address = get_voter_weight_record_address(
+            program_id,
+            &record.base.realm,
+            &record.base.governing_token_mint,
+            &record.base.governing_token_owner);
+    if record_address != *record_info.key {
+        return Err(VestingError::InvalidVoterWeightRecordAccountAddress.into());
+    }
+    Ok(record)
+}
+
 /// Deserialize ExtendedVoterWeightRecord account and checks owner program and linkage
 pub fn get_voter_weight_record_data_checked(
     program_id: &Pubkey,
@@ -151,7 +414,7 @@ pub fn create_voter_weight_record<'a, I>(
     record_account: &AccountInfo<'a>,
     system_program_account: &AccountInfo<'a>,
     initialize_func: I
-) -> Result<(), ProgramError>
+) -> Result<ExtendedVoterWeightRecord, ProgramError>
 where I: FnOnce(&mut ExtendedVoterWeightRecord) -> Result<(), ProgramError>
 {
     let mut record_data = ExtendedVoterWeightRecord {
@@ -166,9 +429,14 @@ where I: FnOnce(&mut ExtendedVoterWeightRecord) -> Result<(), ProgramError>
             weight_action_target: None,
             reserved: [0u8; 8],
         },
-        account_discriminator: ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR,
+        account_discriminator: ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2,
         total_amount: 0,
         vote_percentage: 10_000,
+        delegated_out: 0,
+        delegated_in: 0,
+        delegate: None,
+        weighted_amount: 0,
+        delegated_out_weight: 0,
     };
     initialize_func(&mut record_data)?;
     create_and_serialize_account_signed::<ExtendedVoterWeightRecord>(
@@ -180,5 +448,163 @@ where I: FnOnce(&mut ExtendedVoterWeightRecord) -> Result<(), ProgramError>
         system_program_account,
         &Rent::get()?
     )?;
-    Ok(())
+    Ok(record_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_record(owner: Pubkey, account_discriminator: [u8; 8], total_amount: u64) -> ExtendedVoterWeightRecord {
+        ExtendedVoterWeightRecord {
+            base: VoterWeightRecord {
+                account_discriminator: VoterWeightRecord::ACCOUNT_DISCRIMINATOR,
+                realm: Pubkey::new_unique(),
+                governing_token_mint: Pubkey::new_unique(),
+                governing_token_owner: owner,
+                voter_weight: total_amount,
+                voter_weight_expiry: None,
+                weight_action: None,
+                weight_action_target: None,
+                reserved: [0u8; 8],
+            },
+            account_discriminator,
+            total_amount,
+            vote_percentage: 10_000,
+            delegated_out: 0,
+            delegated_in: 0,
+            delegate: None,
+            weighted_amount: total_amount,
+            delegated_out_weight: 0,
+        }
+    }
+
+    #[test]
+    fn test_legacy_voter_weight_record_packing() {
+        let mut record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR, 1000);
+        let legacy_data = record.try_to_vec().unwrap();
+        assert_eq!(ExtendedVoterWeightRecord::try_from_slice(&legacy_data).unwrap(), record);
+
+        record.upgrade();
+        let data = record.try_to_vec().unwrap();
+        assert_eq!(data.len(), legacy_data.len() + 8 + 8 + 1 + 8 + 8);
+        assert_eq!(ExtendedVoterWeightRecord::try_from_slice(&data).unwrap(), record);
+    }
+
+    #[test]
+    fn test_legacy_voter_weight_record_is_not_weighted() {
+        let mut record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR, 1000);
+        assert!(record.clone().recalculate_voter_weight_with_lockup(500).is_err());
+        assert!(record.clone().increase_total_amount(100, 50).is_err());
+
+        record.weighted_amount = 500;
+        assert!(record.try_to_vec().is_err());
+
+        record.upgrade();
+        record.recalculate_voter_weight_with_lockup(500).unwrap();
+        assert_eq!(record.base.voter_weight, 500);
+    }
+
+    #[test]
+    fn test_deposit_keeps_lockup_weight() {
+        let mut record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 1000);
+        record.recalculate_voter_weight_with_lockup(400).unwrap();
+        assert_eq!(record.base.voter_weight, 400);
+
+        // New tokens are weighted with the current ratio instead of resetting the weight to the number of tokens
+        let weighted_value = record.weighted_share(500).unwrap();
+        assert_eq!(weighted_value, 200);
+        record.increase_total_amount(500, weighted_value).unwrap();
+        assert_eq!(record.total_amount, 1500);
+        assert_eq!(record.base.voter_weight, 600);
+
+        assert_eq!(record.decrease_total_amount(750, None).unwrap(), 300);
+        assert_eq!(record.base.voter_weight, 300);
+
+        // Tokens moved from another record keep their weight
+        record.increase_total_amount(250, 50).unwrap();
+        assert_eq!(record.base.voter_weight, 350);
+
+        // Tokens with the known lockup weight are removed with this weight instead of the ratio
+        assert_eq!(record.clone().decrease_total_amount(500, Some(100)).unwrap(), 100);
+
+        assert_eq!(record.decrease_total_amount(1000, None).unwrap(), 350);
+        assert_eq!(record.weighted_amount, 0);
+        assert_eq!(record.base.voter_weight, 0);
+
+        record.vote_percentage = 5000;
+        record.increase_total_amount(100, 100).unwrap();
+        assert_eq!(record.base.voter_weight, 50);
+    }
+
+    #[test]
+    fn test_decrease_weighted_amount() {
+        let mut legacy_record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR, 1000);
+        assert_eq!(legacy_record.decrease_weighted_amount(300).unwrap(), 0);
+        assert_eq!(legacy_record.base.voter_weight, 1000);
+
+        let mut record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 1000);
+        record.recalculate_voter_weight_with_lockup(800).unwrap();
+        assert_eq!(record.decrease_weighted_amount(300).unwrap(), 300);
+        assert_eq!(record.total_amount, 1000);
+        assert_eq!(record.base.voter_weight, 500);
+
+        assert_eq!(record.decrease_weighted_amount(600).unwrap(), 500);
+        assert_eq!(record.base.voter_weight, 0);
+    }
+
+    #[test]
+    fn test_delegation() {
+        let delegate_owner = Pubkey::new_unique();
+        let mut record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 1000);
+        let mut delegate = create_test_record(delegate_owner, ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 500);
+        let mut other = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 0);
+
+        record.delegate_to(&mut delegate, 600).unwrap();
+        assert_eq!(record.delegate, Some(delegate_owner));
+        assert_eq!(record.base.voter_weight, 400);
+        assert_eq!(delegate.base.voter_weight, 1100);
+
+        assert!(record.delegate_to(&mut delegate, 500).is_err());
+        assert!(record.delegate_to(&mut other, 100).is_err());
+        assert!(record.clone().decrease_total_amount(500, None).is_err());
+
+        record.undelegate_from(&mut delegate, 600).unwrap();
+        assert_eq!(record.delegate, None);
+        assert_eq!(record.base.voter_weight, 1000);
+        assert_eq!(delegate.base.voter_weight, 500);
+        assert!(record.undelegate_from(&mut delegate, 1).is_err());
+    }
+
+    #[test]
+    fn test_weighted_delegation() {
+        let mut record = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 1000);
+        let mut delegate = create_test_record(Pubkey::new_unique(), ExtendedVoterWeightRecord::ACCOUNT_DISCRIMINATOR_V2, 500);
+        record.recalculate_voter_weight_with_lockup(400).unwrap();
+
+        // The delegate receives the weighted share of delegated tokens, so the sum of weights doesn't change
+        record.delegate_to(&mut delegate, 500).unwrap();
+        assert_eq!(record.delegated_out_weight, 200);
+        assert_eq!(delegate.delegated_in, 200);
+        assert_eq!(record.base.voter_weight, 200);
+        assert_eq!(delegate.base.voter_weight, 700);
+
+        // Decreased weight is moved from the delegate when it's synchronized
+        record.recalculate_voter_weight_with_lockup(300).unwrap();
+        assert_eq!(record.base.voter_weight + delegate.base.voter_weight, 800);
+        record.sync_delegated_weight(&mut delegate).unwrap();
+        assert_eq!(record.delegated_out_weight, 150);
+        assert_eq!(record.base.voter_weight, 150);
+        assert_eq!(delegate.base.voter_weight, 650);
+
+        // The delegated weight is returned by parts and completely
+        record.undelegate_from(&mut delegate, 100).unwrap();
+        assert_eq!(record.delegated_out_weight, 120);
+        assert_eq!(delegate.delegated_in, 120);
+        record.undelegate_from(&mut delegate, 400).unwrap();
+        assert_eq!(record.delegated_out_weight, 0);
+        assert_eq!(delegate.delegated_in, 0);
+        assert_eq!(record.base.voter_weight, 300);
+        assert_eq!(delegate.base.voter_weight, 500);
+    }
 }
@@ -1,13 +1,16 @@
 #![cfg(feature = "test-bpf")]
 use std::str::FromStr;
+use borsh::BorshSerialize;
 
 use solana_program::{
     borsh::try_from_slice_unchecked,
     hash::Hash,
+    instruction::Instruction,
+    program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
 };
-use solana_program_test::{processor, ProgramTest, ProgramTestBanksClientExt};
+use solana_program_test::{processor, BanksClient, BanksClientError, ProgramTest, ProgramTestBanksClientExt};
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
@@ -18,12 +21,13 @@ use solana_sdk::{
 use spl_governance_addin_vesting::{
     entrypoint::process_instruction,
     error::VestingError,
-    state::{VestingSchedule, VestingRecord},
+    state::{VestingAccountType, VestingSchedule, VestingRecordV1, VestingRecordV2},
     voter_weight::{ExtendedVoterWeightRecord, get_voter_weight_record_address},
     max_voter_weight::{MaxVoterWeightRecord, get_max_voter_weight_record_address},
     instruction as vesting_instruction,
 };
-use spl_token::{self, instruction as token_instruction, state::Account as TokenAccount};
+use spl_token::{self, instruction as token_instruction, state::{Account as TokenAccount, AccountState}};
+use spl_associated_token_account::get_associated_token_address;
 use spl_governance::{
     instruction as governance_instruction,
     state::{
@@ -137,6 +141,7 @@ async fn test_token_vesting_without_realm() {
         vesting_instruction::deposit(
             &program_id,
             &spl_token::id(),
+            &mint.pubkey(),
             &vesting_token_account.pubkey(),
             &source_account.pubkey(),
             &source_token_account.pubkey(),
@@ -245,6 +250,7 @@ async fn test_token_vesting_without_realm() {
                 vesting_instruction::split(
                     &program_id,
                     &spl_token::id(),
+                    &mint.pubkey(),
                     &vesting_token_account.pubkey(),
                     &new_destination_account.pubkey(),
                     &splitted_vesting_token_account.pubkey(),
@@ -265,10 +271,10 @@ async fn test_token_vesting_without_realm() {
             Ok(expected_amounts) => {
                 result.unwrap();
                 assert_eq!(
-                    try_from_slice_unchecked::<VestingRecord>(&acc.as_ref().unwrap().data).unwrap().schedule,
+                    try_from_slice_unchecked::<VestingRecordV2>(&acc.as_ref().unwrap().data).unwrap().schedule,
                     make_schedule_from_amount(&expected_amounts)
                 );
-                let splitted_record = try_from_slice_unchecked::<VestingRecord>(&splitted_acc.as_ref().unwrap().data).unwrap();
+                let splitted_record = try_from_slice_unchecked::<VestingRecordV2>(&splitted_acc.as_ref().unwrap().data).unwrap();
                 assert_eq!(splitted_record.owner, splitted_vesting_owner.pubkey());
                 assert_eq!(splitted_record.realm, None);
                 assert_eq!(splitted_record.token, splitted_vesting_token_account.pubkey());
@@ -292,6 +298,7 @@ async fn test_token_vesting_without_realm() {
         vesting_instruction::withdraw(
             &program_id,
             &spl_token::id(),
+            &mint.pubkey(),
             &vesting_token_account.pubkey(),
             &destination_token_account.pubkey(),
             &new_destination_account.pubkey(),
@@ -313,7 +320,7 @@ async fn test_token_vesting_without_realm() {
 
     let acc = banks_client.get_account(vesting_account_key).await.unwrap();
     println!("Vesting: {:?}", acc);
-    println!("    {:?}", try_from_slice_unchecked::<VestingRecord>(&acc.as_ref().unwrap().data).unwrap());
+    println!("    {:?}", try_from_slice_unchecked::<VestingRecordV2>(&acc.as_ref().unwrap().data).unwrap());
 
 
     let mut close_transaction = Transaction::new_with_payer(
@@ -655,7 +662,7 @@ async fn test_token_vesting_with_realm() {
         banks_client.process_transaction(split_transaction).await.unwrap();
         let acc = banks_client.get_account(vesting_account_key).await.unwrap();
         assert_eq!(
-            try_from_slice_unchecked::<VestingRecord>(&acc.as_ref().unwrap().data).unwrap().schedule,
+            try_from_slice_unchecked::<VestingRecordV2>(&acc.as_ref().unwrap().data).unwrap().schedule,
             vec![
                 VestingSchedule {amount: 12, release_time: 0},
                 VestingSchedule {amount:  0, release_time: 2},
@@ -663,7 +670,7 @@ async fn test_token_vesting_with_realm() {
             ]
         );
         let splitted_acc = banks_client.get_account(splitted_vesting_account_key).await.unwrap();
-        let splitted_record = try_from_slice_unchecked::<VestingRecord>(&splitted_acc.as_ref().unwrap().data).unwrap();
+        let splitted_record = try_from_slice_unchecked::<VestingRecordV2>(&splitted_acc.as_ref().unwrap().data).unwrap();
         assert_eq!(splitted_record.owner, splitted_vesting_owner.pubkey());
         assert_eq!(splitted_record.realm, Some(realm_address));
         assert_eq!(splitted_record.token, splitted_vesting_token_account.pubkey());
@@ -707,8 +714,8 @@ async fn test_token_vesting_with_realm() {
     assert_eq!(destination_token_data.amount, 32);
 
 
-    let vesting_record = banks_client.get_account_data_with_borsh::<VestingRecord>(vesting_account_key).await.unwrap();
-    println!("VestingRecord: {:?}", vesting_record);
+    let vesting_record = banks_client.get_account_data_with_borsh::<VestingRecordV2>(vesting_account_key).await.unwrap();
+    println!("VestingRecordV2: {:?}", vesting_record);
     assert_eq!(vesting_record.schedule.iter().map(|v| v.amount).sum::<u64>(), 0u64);
 
     let voter_weight_record2 = banks_client.get_account_data_with_borsh::<ExtendedVoterWeightRecord>(voter_weight_record_address2).await.unwrap();
@@ -757,6 +764,740 @@ async fn test_token_vesting_with_realm() {
     assert_eq!(banks_client.get_account(vesting_token_account.pubkey()).await.unwrap(), None);
 }
 
+#[tokio::test]
+async fn test_revoke_delegated_vesting() {
+
+    // Create program and test environment
+    let program_id = Pubkey::from_str("VestingbGKPFXCWuBvfkegQfZyiNwAJb9Ss623VQ5DA").unwrap();
+    let governance_id = Pubkey::from_str("5ZYgDTqLbYJ2UAtF7rbUboSt9Q6bunCQgGEwxDFrQrXb").unwrap();
+    let mint_authority = Keypair::new();
+    let mint = Keypair::new();
+
+    let source_account = Keypair::new();
+    let source_token_account = Keypair::new();
+
+    let vesting_owner = Keypair::new();
+    let delegate = Keypair::new();
+    let revoke_authority = Keypair::new();
+    let revoke_destination_token_account = Keypair::new();
+
+    let vesting_token_account = Keypair::new();
+    let (vesting_account_key,_) = Pubkey::find_program_address(&[&vesting_token_account.pubkey().as_ref()], &program_id);
+
+    let mut program_test = ProgramTest::new(
+        "spl_governance_addin_vesting",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    program_test.add_account(
+        source_account.pubkey(),
+        Account {
+            lamports: 5000000,
+            ..Account::default()
+        },
+    );
+
+    program_test.add_program(
+        "spl_governance",
+        governance_id,
+        None,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    banks_client.process_transaction(mint_init_transaction(
+        &payer,
+        &mint,
+        &mint_authority,
+        recent_blockhash
+    )).await.unwrap();
+
+    banks_client.process_transaction(
+        create_token_account(&payer, &mint, recent_blockhash, &source_token_account, &source_account.pubkey())
+    ).await.unwrap();
+    banks_client.process_transaction(
+        create_token_account(&payer, &mint, recent_blockhash, &vesting_token_account, &vesting_account_key)
+    ).await.unwrap();
+    banks_client.process_transaction(
+        create_token_account(&payer, &mint, recent_blockhash, &revoke_destination_token_account, &revoke_authority.pubkey())
+    ).await.unwrap();
+
+    let mut setup_transaction = Transaction::new_with_payer(
+        &[
+            token_instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &source_token_account.pubkey(),
+                &mint_authority.pubkey(),
+                &[],
+                60
+            ).unwrap()
+        ],
+        Some(&payer.pubkey()),
+    );
+    setup_transaction.partial_sign(&[&payer, &mint_authority], recent_blockhash);
+    banks_client.process_transaction(setup_transaction).await.unwrap();
+
+    // Create realm
+    let realm_name = "testing realm".to_string();
+    let realm_address = get_realm_address(&governance_id, &realm_name);
+    let mut create_realm_transaction = Transaction::new_with_payer(
+        &[
+            governance_instruction::create_realm(
+                &governance_id,
+                &mint_authority.pubkey(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None, None, None,
+                realm_name,
+                1,
+                MintMaxVoteWeightSource::SupplyFraction(10_000_000_000)
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    create_realm_transaction.partial_sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(create_realm_transaction).await.unwrap();
+
+    // Create revocable vesting and delegate the most of its tokens
+    let schedules = vec![
+        VestingSchedule {amount: 60, release_time: 4_000_000_000},
+    ];
+
+    let mut deposit_transaction = Transaction::new_with_payer(
+        &[
+            vesting_instruction::deposit_revocable_with_realm(
+                &program_id,
+                &spl_token::id(),
+                &vesting_token_account.pubkey(),
+                &source_account.pubkey(),
+                &source_token_account.pubkey(),
+                &vesting_owner.pubkey(),
+                &payer.pubkey(),
+                schedules,
+                None,
+                &revoke_authority.pubkey(),
+                &realm_address,
+                &mint.pubkey(),
+            ).unwrap(),
+            vesting_instruction::create_voter_weight_record(
+                &program_id,
+                &delegate.pubkey(),
+                &payer.pubkey(),
+                &realm_address,
+                &mint.pubkey(),
+            ).unwrap(),
+            vesting_instruction::delegate(
+                &program_id,
+                &governance_id,
+                &realm_address,
+                &mint.pubkey(),
+                &vesting_owner.pubkey(),
+                &delegate.pubkey(),
+                &payer.pubkey(),
+                50,
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    deposit_transaction.partial_sign(&[&payer, &source_account, &vesting_owner], recent_blockhash);
+    banks_client.process_transaction(deposit_transaction).await.unwrap();
+
+    let revoke_instruction = vesting_instruction::revoke_with_realm(
+        &program_id,
+        &spl_token::id(),
+        &vesting_token_account.pubkey(),
+        &revoke_destination_token_account.pubkey(),
+        &revoke_authority.pubkey(),
+        &vesting_owner.pubkey(),
+        &governance_id,
+        &realm_address,
+        &mint.pubkey(),
+    ).unwrap();
+
+    // Revoked tokens can't be undelegated without the delegate's voter weight record
+    let mut revoke_transaction = Transaction::new_with_payer(
+        &[revoke_instruction.clone()],
+        Some(&payer.pubkey()),
+    );
+    revoke_transaction.partial_sign(&[&payer, &revoke_authority], recent_blockhash);
+    assert_eq!(
+        banks_client.process_transaction(revoke_transaction).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::AmountIsDelegated)
+    );
+
+    let mut revoke_transaction = Transaction::new_with_payer(
+        &[
+            vesting_instruction::with_delegate_voter_weight_record(
+                revoke_instruction,
+                &realm_address,
+                &mint.pubkey(),
+                &delegate.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    revoke_transaction.partial_sign(&[&payer, &revoke_authority], recent_blockhash);
+    banks_client.process_transaction(revoke_transaction).await.unwrap();
+
+    let revoke_destination_token_data = banks_client.get_packed_account_data::<TokenAccount>(revoke_destination_token_account.pubkey()).await.unwrap();
+    assert_eq!(revoke_destination_token_data.amount, 60);
+
+    let voter_weight_record = banks_client.get_account_data_with_borsh::<ExtendedVoterWeightRecord>(
+        get_voter_weight_record_address(&program_id, &realm_address, &mint.pubkey(), &vesting_owner.pubkey())
+    ).await.unwrap();
+    assert_eq!(voter_weight_record.total_amount, 0);
+    assert_eq!(voter_weight_record.delegated_out, 0);
+    assert_eq!(voter_weight_record.base.voter_weight, 0);
+
+    let delegate_voter_weight_record = banks_client.get_account_data_with_borsh::<ExtendedVoterWeightRecord>(
+        get_voter_weight_record_address(&program_id, &realm_address, &mint.pubkey(), &delegate.pubkey())
+    ).await.unwrap();
+    assert_eq!(delegate_voter_weight_record.delegated_in, 0);
+    assert_eq!(delegate_voter_weight_record.base.voter_weight, 0);
+
+    let max_voter_weight_record = banks_client.get_account_data_with_borsh::<MaxVoterWeightRecord>(
+        get_max_voter_weight_record_address(&program_id, &realm_address, &mint.pubkey())
+    ).await.unwrap();
+    assert_eq!(max_voter_weight_record.max_voter_weight, 0);
+}
+
+#[tokio::test]
+async fn test_owner_proposal_with_realm() {
+    let mut context = RealmTestContext::start(Keypair::new(), vec![]).await;
+    let vesting_owner = Keypair::new();
+    let proposed_owner = Keypair::new();
+    let other_owner = Keypair::new();
+
+    let vesting_token_account = context.deposit(
+        &vesting_owner.pubkey(),
+        vec![VestingSchedule {amount: 60, release_time: 4_000_000_000}],
+    ).await;
+    let vesting_token = vesting_token_account.pubkey();
+
+    context.process(
+        &[
+            vesting_instruction::propose_owner(
+                &context.program_id,
+                &vesting_token,
+                &vesting_owner.pubkey(),
+                &proposed_owner.pubkey(),
+                &context.payer.pubkey(),
+            ).unwrap(),
+            vesting_instruction::create_voter_weight_record(
+                &context.program_id,
+                &proposed_owner.pubkey(),
+                &context.payer.pubkey(),
+                &context.realm,
+                &context.mint.pubkey(),
+            ).unwrap(),
+        ],
+        &[&vesting_owner],
+    ).await.unwrap();
+    assert_eq!(context.get_vesting_record(&vesting_token).await.proposed_owner, Some(proposed_owner.pubkey()));
+
+    // Only the proposed owner can accept the ownership
+    let accept_instruction = |new_owner: &Pubkey| vesting_instruction::accept_owner_with_realm(
+        &context.program_id,
+        &vesting_token,
+        &vesting_owner.pubkey(),
+        new_owner,
+        &context.governance_id,
+        &context.realm,
+        &context.mint.pubkey(),
+    ).unwrap();
+    let other_accept_instruction = accept_instruction(&other_owner.pubkey());
+    let proposed_accept_instruction = accept_instruction(&proposed_owner.pubkey());
+
+    assert_eq!(
+        context.process(&[other_accept_instruction], &[&other_owner]).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::InvalidProposedOwner)
+    );
+
+    // Cancelled proposal can't be accepted
+    context.process(
+        &[
+            vesting_instruction::cancel_owner_proposal(
+                &context.program_id,
+                &vesting_token,
+                &vesting_owner.pubkey(),
+            ).unwrap(),
+        ],
+        &[&vesting_owner],
+    ).await.unwrap();
+    assert_eq!(context.get_vesting_record(&vesting_token).await.proposed_owner, None);
+    assert_eq!(
+        context.process(&[proposed_accept_instruction.clone()], &[&proposed_owner]).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::InvalidProposedOwner)
+    );
+
+    context.process(
+        &[
+            vesting_instruction::propose_owner(
+                &context.program_id,
+                &vesting_token,
+                &vesting_owner.pubkey(),
+                &proposed_owner.pubkey(),
+                &context.payer.pubkey(),
+            ).unwrap(),
+            proposed_accept_instruction,
+        ],
+        &[&vesting_owner, &proposed_owner],
+    ).await.unwrap();
+
+    let vesting_record = context.get_vesting_record(&vesting_token).await;
+    assert_eq!(vesting_record.owner, proposed_owner.pubkey());
+    assert_eq!(vesting_record.proposed_owner, None);
+
+    // Voting weight is moved with the vesting, the max voter weight is not changed
+    assert_eq!(context.get_voter_weight_record(&vesting_owner.pubkey()).await.total_amount, 0);
+    let new_voter_weight_record = context.get_voter_weight_record(&proposed_owner.pubkey()).await;
+    assert_eq!(new_voter_weight_record.total_amount, 60);
+    assert_eq!(new_voter_weight_record.base.voter_weight, 60);
+    assert_eq!(context.get_max_voter_weight().await, 60);
+}
+
+#[tokio::test]
+async fn test_crank_withdraw_with_realm() {
+    let mint = Keypair::new();
+    let vesting_owner = Keypair::new();
+
+    // The owner's associated token account is expected to exist
+    let owner_token_account = get_associated_token_address(&vesting_owner.pubkey(), &mint.pubkey());
+    let mut owner_token_data = vec![0u8; TokenAccount::LEN];
+    TokenAccount {
+        mint: mint.pubkey(),
+        owner: vesting_owner.pubkey(),
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    }.pack_into_slice(&mut owner_token_data);
+    let owner_token = Account {
+        lamports: Rent::default().minimum_balance(TokenAccount::LEN),
+        data: owner_token_data,
+        owner: spl_token::id(),
+        ..Account::default()
+    };
+
+    let mut context = RealmTestContext::start(mint, vec![(owner_token_account, owner_token)]).await;
+    let vesting_token_account = context.deposit(
+        &vesting_owner.pubkey(),
+        vec![
+            VestingSchedule {amount: 20, release_time: 0},
+            VestingSchedule {amount: 40, release_time: 4_000_000_000},
+        ],
+    ).await;
+
+    let crank_withdraw_instruction = vesting_instruction::crank_withdraw_with_realm(
+        &context.program_id,
+        &spl_token::id(),
+        &vesting_token_account.pubkey(),
+        &vesting_owner.pubkey(),
+        &context.governance_id,
+        &context.realm,
+        &context.mint.pubkey(),
+    ).unwrap();
+
+    // Anyone can withdraw released tokens to the owner only after the owner allowed it
+    assert_eq!(
+        context.process(&[crank_withdraw_instruction.clone()], &[]).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::CrankWithdrawNotAllowed)
+    );
+
+    context.process(
+        &[
+            vesting_instruction::set_crank_withdraw(
+                &context.program_id,
+                &vesting_token_account.pubkey(),
+                &vesting_owner.pubkey(),
+                true,
+            ).unwrap(),
+        ],
+        &[&vesting_owner],
+    ).await.unwrap();
+    assert!(context.get_vesting_record(&vesting_token_account.pubkey()).await.allow_crank_withdraw);
+
+    context.refresh_blockhash().await;
+    context.process(&[crank_withdraw_instruction], &[]).await.unwrap();
+
+    let owner_token_data = context.banks_client.get_packed_account_data::<TokenAccount>(owner_token_account).await.unwrap();
+    assert_eq!(owner_token_data.amount, 20);
+    let vesting_token_data = context.banks_client.get_packed_account_data::<TokenAccount>(vesting_token_account.pubkey()).await.unwrap();
+    assert_eq!(vesting_token_data.amount, 40);
+
+    assert_eq!(context.get_voter_weight_record(&vesting_owner.pubkey()).await.total_amount, 40);
+    assert_eq!(context.get_max_voter_weight().await, 40);
+}
+
+#[tokio::test]
+async fn test_withdraw_without_mint_account() {
+    let mut context = RealmTestContext::start(Keypair::new(), vec![]).await;
+    let vesting_owner = Keypair::new();
+    let vesting_token_account = context.deposit(
+        &vesting_owner.pubkey(),
+        vec![
+            VestingSchedule {amount: 20, release_time: 0},
+            VestingSchedule {amount: 40, release_time: 4_000_000_000},
+        ],
+    ).await;
+
+    let destination_token_account = Keypair::new();
+    let payer = context.payer.pubkey();
+    context.banks_client.process_transaction(
+        create_token_account(&context.payer, &context.mint, context.recent_blockhash, &destination_token_account, &payer)
+    ).await.unwrap();
+
+    // Clients built for the previous layout don't pass the trailing Mint account of spl-token mints
+    let mut withdraw_instruction = vesting_instruction::withdraw_with_realm(
+        &context.program_id,
+        &spl_token::id(),
+        &vesting_token_account.pubkey(),
+        &destination_token_account.pubkey(),
+        &vesting_owner.pubkey(),
+        &context.governance_id,
+        &context.realm,
+        &context.mint.pubkey(),
+    ).unwrap();
+    assert_eq!(withdraw_instruction.accounts.pop().unwrap().pubkey, context.mint.pubkey());
+    context.process(&[withdraw_instruction], &[&vesting_owner]).await.unwrap();
+
+    let destination_token_data = context.banks_client.get_packed_account_data::<TokenAccount>(destination_token_account.pubkey()).await.unwrap();
+    assert_eq!(destination_token_data.amount, 20);
+    assert_eq!(context.get_voter_weight_record(&vesting_owner.pubkey()).await.total_amount, 40);
+    assert_eq!(context.get_max_voter_weight().await, 40);
+}
+
+#[tokio::test]
+async fn test_vesting_manager_with_realm() {
+    let mut context = RealmTestContext::start(Keypair::new(), vec![]).await;
+    let vesting_owner = Keypair::new();
+    let manager = Keypair::new();
+    let new_owner = Keypair::new();
+
+    let vesting_token_account = context.deposit(
+        &vesting_owner.pubkey(),
+        vec![
+            VestingSchedule {amount: 20, release_time: 0},
+            VestingSchedule {amount: 40, release_time: 4_000_000_000},
+        ],
+    ).await;
+    let vesting_token = vesting_token_account.pubkey();
+
+    context.process(
+        &[
+            vesting_instruction::set_manager(
+                &context.program_id,
+                &vesting_token,
+                &vesting_owner.pubkey(),
+                &manager.pubkey(),
+                &context.payer.pubkey(),
+            ).unwrap(),
+            vesting_instruction::set_crank_withdraw(
+                &context.program_id,
+                &vesting_token,
+                &vesting_owner.pubkey(),
+                true,
+            ).unwrap(),
+            vesting_instruction::create_voter_weight_record(
+                &context.program_id,
+                &new_owner.pubkey(),
+                &context.payer.pubkey(),
+                &context.realm,
+                &context.mint.pubkey(),
+            ).unwrap(),
+        ],
+        &[&vesting_owner],
+    ).await.unwrap();
+    assert_eq!(context.get_vesting_record(&vesting_token).await.manager, Some(manager.pubkey()));
+
+    // Released tokens are withdrawn only by the owner
+    let manager_withdraw_instruction = vesting_instruction::withdraw_with_realm(
+        &context.program_id,
+        &spl_token::id(),
+        &vesting_token,
+        &Pubkey::new_unique(),
+        &manager.pubkey(),
+        &context.governance_id,
+        &context.realm,
+        &context.mint.pubkey(),
+    ).unwrap();
+    assert_eq!(
+        context.process(&[manager_withdraw_instruction], &[&manager]).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::InvalidOwnerForVestingAccount)
+    );
+
+    let change_owner_instruction = |vesting_owner: &Pubkey, new_owner: &Pubkey| vesting_instruction::signed_by_manager(
+        vesting_instruction::change_owner_with_realm(
+            &context.program_id,
+            &vesting_token,
+            vesting_owner,
+            new_owner,
+            &context.governance_id,
+            &context.realm,
+            &context.mint.pubkey(),
+        ).unwrap(),
+        vesting_owner,
+        &manager.pubkey(),
+    ).unwrap();
+    let first_change_owner_instruction = change_owner_instruction(&vesting_owner.pubkey(), &new_owner.pubkey());
+    let second_change_owner_instruction = change_owner_instruction(&new_owner.pubkey(), &vesting_owner.pubkey());
+
+    context.process(&[first_change_owner_instruction], &[&manager]).await.unwrap();
+
+    // The manager and the crank withdraw permission granted by the previous owner are removed
+    let vesting_record = context.get_vesting_record(&vesting_token).await;
+    assert_eq!(vesting_record.owner, new_owner.pubkey());
+    assert_eq!(vesting_record.manager, None);
+    assert!(!vesting_record.allow_crank_withdraw);
+    assert_eq!(context.get_voter_weight_record(&vesting_owner.pubkey()).await.total_amount, 0);
+    assert_eq!(context.get_voter_weight_record(&new_owner.pubkey()).await.total_amount, 60);
+    assert_eq!(context.get_max_voter_weight().await, 60);
+
+    // The stale manager can't act on behalf of the new owner nor set itself again
+    assert_eq!(
+        context.process(&[second_change_owner_instruction], &[&manager]).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::InvalidOwnerForVestingAccount)
+    );
+    let manager_set_manager_instruction = vesting_instruction::set_manager(
+        &context.program_id,
+        &vesting_token,
+        &manager.pubkey(),
+        &manager.pubkey(),
+        &context.payer.pubkey(),
+    ).unwrap();
+    assert_eq!(
+        context.process(&[manager_set_manager_instruction], &[&manager]).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::InvalidOwnerForVestingAccount)
+    );
+    assert_eq!(context.get_vesting_record(&vesting_token).await.owner, new_owner.pubkey());
+}
+
+#[tokio::test]
+async fn test_close_with_voter_weight_record() {
+    let mut context = RealmTestContext::start(Keypair::new(), vec![]).await;
+    let vesting_owner = Keypair::new();
+    let delegate = Keypair::new();
+    let delegator = Keypair::new();
+    let spill = Pubkey::new_unique();
+
+    // The delegate has its own vesting and receives voting weight from the delegator
+    let vesting_token_account = context.deposit(&vesting_owner.pubkey(), vec![VestingSchedule {amount: 20, release_time: 0}]).await;
+    let delegate_vesting_token_account = context.deposit(&delegate.pubkey(), vec![VestingSchedule {amount: 30, release_time: 0}]).await;
+    context.deposit(&delegator.pubkey(), vec![VestingSchedule {amount: 40, release_time: 4_000_000_000}]).await;
+
+    context.process(
+        &[
+            vesting_instruction::delegate(
+                &context.program_id,
+                &context.governance_id,
+                &context.realm,
+                &context.mint.pubkey(),
+                &delegator.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+                10,
+            ).unwrap(),
+        ],
+        &[&delegator],
+    ).await.unwrap();
+
+    for (owner, vesting_token) in [
+        (&vesting_owner, vesting_token_account.pubkey()),
+        (&delegate, delegate_vesting_token_account.pubkey()),
+    ] {
+        let destination_token_account = Keypair::new();
+        context.banks_client.process_transaction(
+            create_token_account(&context.payer, &context.mint, context.recent_blockhash, &destination_token_account, &owner.pubkey())
+        ).await.unwrap();
+
+        context.process(
+            &[
+                vesting_instruction::withdraw_with_realm(
+                    &context.program_id,
+                    &spl_token::id(),
+                    &vesting_token,
+                    &destination_token_account.pubkey(),
+                    &owner.pubkey(),
+                    &context.governance_id,
+                    &context.realm,
+                    &context.mint.pubkey(),
+                ).unwrap(),
+                vesting_instruction::close_with_realm(
+                    &context.program_id,
+                    &spl_token::id(),
+                    &vesting_token,
+                    &owner.pubkey(),
+                    &spill,
+                    &context.governance_id,
+                    &context.realm,
+                    &context.mint.pubkey(),
+                ).unwrap(),
+            ],
+            &[owner],
+        ).await.unwrap();
+
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token.as_ref()], &context.program_id);
+        assert_eq!(context.banks_client.get_account(vesting_account).await.unwrap(), None);
+        assert_eq!(context.banks_client.get_account(vesting_token).await.unwrap(), None);
+    }
+
+    // Empty record is closed together with the vesting
+    let voter_weight_record_address = context.voter_weight_record_address(&vesting_owner.pubkey());
+    assert_eq!(context.banks_client.get_account(voter_weight_record_address).await.unwrap(), None);
+
+    // Record with delegated voting weight is left untouched
+    let delegate_voter_weight_record = context.get_voter_weight_record(&delegate.pubkey()).await;
+    assert_eq!(delegate_voter_weight_record.total_amount, 0);
+    assert_eq!(delegate_voter_weight_record.delegated_in, 10);
+    assert_eq!(delegate_voter_weight_record.base.voter_weight, 10);
+    assert_eq!(context.get_max_voter_weight().await, 40);
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_vesting_record() {
+
+    // Create program and test environment
+    let program_id = Pubkey::from_str("VestingbGKPFXCWuBvfkegQfZyiNwAJb9Ss623VQ5DA").unwrap();
+    let mint_authority = Keypair::new();
+    let mint = Keypair::new();
+
+    let destination_account = Keypair::new();
+    let destination_token_account = Keypair::new();
+
+    let vesting_token_account = Keypair::new();
+    let (vesting_account_key,_) = Pubkey::find_program_address(&[&vesting_token_account.pubkey().as_ref()], &program_id);
+
+    let mut program_test = ProgramTest::new(
+        "spl_governance_addin_vesting",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    // Exact-size vesting account created by the previous program version
+    let legacy_record = VestingRecordV1 {
+        account_type: VestingAccountType::VestingRecordV1,
+        owner: destination_account.pubkey(),
+        mint: mint.pubkey(),
+        token: vesting_token_account.pubkey(),
+        realm: None,
+        schedule: vec![
+            VestingSchedule {amount: 20, release_time: 0},
+            VestingSchedule {amount: 30, release_time: u64::MAX},
+        ],
+    };
+    let legacy_data = legacy_record.try_to_vec().unwrap();
+    program_test.add_account(
+        vesting_account_key,
+        Account {
+            lamports: Rent::default().minimum_balance(legacy_data.len()),
+            data: legacy_data.clone(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+
+    // Start and process transactions on the test network
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    banks_client.process_transaction(mint_init_transaction(
+        &payer,
+        &mint,
+        &mint_authority,
+        recent_blockhash
+    )).await.unwrap();
+
+    banks_client.process_transaction(
+        create_token_account(&payer, &mint, recent_blockhash, &vesting_token_account, &vesting_account_key)
+    ).await.unwrap();
+    banks_client.process_transaction(
+        create_token_account(&payer, &mint, recent_blockhash, &destination_token_account, &destination_account.pubkey())
+    ).await.unwrap();
+
+    let mut mint_transaction = Transaction::new_with_payer(
+        &[
+            token_instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &vesting_token_account.pubkey(),
+                &mint_authority.pubkey(),
+                &[],
+                50,
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    mint_transaction.partial_sign(&[&payer, &mint_authority], recent_blockhash);
+    banks_client.process_transaction(mint_transaction).await.unwrap();
+
+    // Legacy record is withdrawn from without migration and keeps its layout
+    let mut withdraw_transaction = Transaction::new_with_payer(
+        &[
+            vesting_instruction::withdraw(
+                &program_id,
+                &spl_token::id(),
+                &mint.pubkey(),
+                &vesting_token_account.pubkey(),
+                &destination_token_account.pubkey(),
+                &destination_account.pubkey(),
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    withdraw_transaction.partial_sign(&[&payer, &destination_account], recent_blockhash);
+    banks_client.process_transaction(withdraw_transaction).await.unwrap();
+
+    let destination_token_data = banks_client.get_packed_account_data::<TokenAccount>(destination_token_account.pubkey()).await.unwrap();
+    assert_eq!(destination_token_data.amount, 20);
+
+    let acc = banks_client.get_account(vesting_account_key).await.unwrap().unwrap();
+    assert_eq!(acc.data.len(), legacy_data.len());
+    let vesting_record = try_from_slice_unchecked::<VestingRecordV2>(&acc.data).unwrap();
+    assert_eq!(vesting_record.account_type, VestingAccountType::VestingRecordV1);
+    assert_eq!(vesting_record.schedule[0].amount, 0);
+
+    let mut migrate_transaction = Transaction::new_with_payer(
+        &[
+            vesting_instruction::migrate_vesting_record(
+                &program_id,
+                &vesting_token_account.pubkey(),
+                &payer.pubkey(),
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    migrate_transaction.partial_sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(migrate_transaction).await.unwrap();
+
+    let acc = banks_client.get_account(vesting_account_key).await.unwrap().unwrap();
+    assert!(acc.data.len() > legacy_data.len());
+    let vesting_record = try_from_slice_unchecked::<VestingRecordV2>(&acc.data).unwrap();
+    assert_eq!(vesting_record.account_type, VestingAccountType::VestingRecordV2);
+    assert_eq!(vesting_record.owner, destination_account.pubkey());
+    assert_eq!(vesting_record.token, vesting_token_account.pubkey());
+    assert_eq!(vesting_record.schedule, vec![
+        VestingSchedule {amount: 0, release_time: 0},
+        VestingSchedule {amount: 30, release_time: u64::MAX},
+    ]);
+    assert_eq!(vesting_record.original_total, 30);
+
+    let recent_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let mut migrate_transaction = Transaction::new_with_payer(
+        &[
+            vesting_instruction::migrate_vesting_record(
+                &program_id,
+                &vesting_token_account.pubkey(),
+                &payer.pubkey(),
+            ).unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    migrate_transaction.partial_sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client.process_transaction(migrate_transaction).await.unwrap_err().unwrap(),
+        trx_instruction_error(0, VestingError::VestingRecordAlreadyMigrated)
+    );
+}
+
 fn mint_init_transaction(
     payer: &Keypair, 
     mint:&Keypair, 
@@ -828,3 +1569,157 @@ fn create_token_account(
     );
     transaction
 }
+
+/// Test environment with the Realm of the vesting mint
+struct RealmTestContext {
+    program_id: Pubkey,
+    governance_id: Pubkey,
+    banks_client: BanksClient,
+    payer: Keypair,
+    recent_blockhash: Hash,
+    mint: Keypair,
+    mint_authority: Keypair,
+    realm: Pubkey,
+}
+
+impl RealmTestContext {
+    /// Starts the test network with the additional `accounts` and creates the mint and the Realm
+    async fn start(mint: Keypair, accounts: Vec<(Pubkey, Account)>) -> Self {
+        let program_id = Pubkey::from_str("VestingbGKPFXCWuBvfkegQfZyiNwAJb9Ss623VQ5DA").unwrap();
+        let governance_id = Pubkey::from_str("5ZYgDTqLbYJ2UAtF7rbUboSt9Q6bunCQgGEwxDFrQrXb").unwrap();
+        let mint_authority = Keypair::new();
+
+        let mut program_test = ProgramTest::new(
+            "spl_governance_addin_vesting",
+            program_id,
+            processor!(process_instruction),
+        );
+        program_test.add_program(
+            "spl_governance",
+            governance_id,
+            None,
+        );
+        for (pubkey, account) in accounts {
+            program_test.add_account(pubkey, account);
+        }
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        banks_client.process_transaction(mint_init_transaction(
+            &payer,
+            &mint,
+            &mint_authority,
+            recent_blockhash
+        )).await.unwrap();
+
+        let realm_name = "testing realm".to_string();
+        let realm = get_realm_address(&governance_id, &realm_name);
+        let mut context = Self {
+            program_id,
+            governance_id,
+            banks_client,
+            payer,
+            recent_blockhash,
+            mint,
+            mint_authority,
+            realm,
+        };
+        context.process(
+            &[
+                governance_instruction::create_realm(
+                    &governance_id,
+                    &context.mint_authority.pubkey(),
+                    &context.mint.pubkey(),
+                    &context.payer.pubkey(),
+                    None, None, None,
+                    realm_name,
+                    1,
+                    MintMaxVoteWeightSource::SupplyFraction(10_000_000_000)
+                ),
+            ],
+            &[],
+        ).await.unwrap();
+        context
+    }
+
+    /// Creates the transaction paid by the payer
+    fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&self.payer.pubkey()));
+        transaction.partial_sign(&[&self.payer], self.recent_blockhash);
+        transaction.partial_sign(signers, self.recent_blockhash);
+        transaction
+    }
+
+    async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers);
+        self.banks_client.process_transaction(transaction).await
+    }
+
+    /// Switches to the new blockhash, so the transactions which were already processed can be repeated
+    async fn refresh_blockhash(&mut self) {
+        self.recent_blockhash = self.banks_client.get_new_latest_blockhash(&self.recent_blockhash).await.unwrap();
+    }
+
+    /// Creates the vesting of `vesting_owner` for the Realm and returns its spl-token account
+    async fn deposit(&mut self, vesting_owner: &Pubkey, schedules: Vec<VestingSchedule>) -> Keypair {
+        let source_token_account = Keypair::new();
+        let vesting_token_account = Keypair::new();
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.pubkey().as_ref()], &self.program_id);
+        let amount = schedules.iter().map(|s| s.amount).sum::<u64>();
+
+        let payer = self.payer.pubkey();
+        self.banks_client.process_transaction(
+            create_token_account(&self.payer, &self.mint, self.recent_blockhash, &source_token_account, &payer)
+        ).await.unwrap();
+        self.banks_client.process_transaction(
+            create_token_account(&self.payer, &self.mint, self.recent_blockhash, &vesting_token_account, &vesting_account)
+        ).await.unwrap();
+
+        let transaction = self.transaction(
+            &[
+                token_instruction::mint_to(
+                    &spl_token::id(),
+                    &self.mint.pubkey(),
+                    &source_token_account.pubkey(),
+                    &self.mint_authority.pubkey(),
+                    &[],
+                    amount
+                ).unwrap(),
+                vesting_instruction::deposit_with_realm(
+                    &self.program_id,
+                    &spl_token::id(),
+                    &vesting_token_account.pubkey(),
+                    &payer,
+                    &source_token_account.pubkey(),
+                    vesting_owner,
+                    &payer,
+                    schedules,
+                    &self.realm,
+                    &self.mint.pubkey(),
+                ).unwrap(),
+            ],
+            &[&self.mint_authority],
+        );
+        self.banks_client.process_transaction(transaction).await.unwrap();
+        vesting_token_account
+    }
+
+    async fn get_vesting_record(&mut self, vesting_token_account: &Pubkey) -> VestingRecordV2 {
+        let (vesting_account, _) = Pubkey::find_program_address(&[vesting_token_account.as_ref()], &self.program_id);
+        self.banks_client.get_account_data_with_borsh::<VestingRecordV2>(vesting_account).await.unwrap()
+    }
+
+    fn voter_weight_record_address(&self, owner: &Pubkey) -> Pubkey {
+        get_voter_weight_record_address(&self.program_id, &self.realm, &self.mint.pubkey(), owner)
+    }
+
+    async fn get_voter_weight_record(&mut self, owner: &Pubkey) -> ExtendedVoterWeightRecord {
+        let address = self.voter_weight_record_address(owner);
+        self.banks_client.get_account_data_with_borsh::<ExtendedVoterWeightRecord>(address).await.unwrap()
+    }
+
+    async fn get_max_voter_weight(&mut self) -> u64 {
+        let address = get_max_voter_weight_record_address(&self.program_id, &self.realm, &self.mint.pubkey());
+        self.banks_client.get_account_data_with_borsh::<MaxVoterWeightRecord>(address).await.unwrap().max_voter_weight
+    }
+}